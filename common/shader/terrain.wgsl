@@ -0,0 +1,107 @@
+#import bevy_pbr::{
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::alpha_discard,
+}
+
+#ifdef PREPASS_PIPELINE
+#import bevy_pbr::{
+    prepass_io::{VertexOutput, FragmentOutput},
+    pbr_deferred_functions::deferred_output,
+}
+#else
+#import bevy_pbr::{
+    forward_io::{VertexOutput, FragmentOutput},
+    pbr_functions::{apply_pbr_lighting, main_pass_post_lighting_processing},
+}
+#endif
+
+struct TerrainHighlight {
+    selected: u32,
+    hovered: u32,
+    map_size: vec2<f32>,
+};
+
+@group(2) @binding(100) var<uniform> highlight: TerrainHighlight;
+@group(2) @binding(101) var province_ids: texture_2d<u32>;
+@group(2) @binding(102) var province_colors: texture_2d<f32>;
+@group(2) @binding(103) var province_visibility: texture_2d<f32>;
+
+const SELECTED_COLOR: vec4<f32> = vec4<f32>(1.0, 0.85, 0.2, 1.0);
+const SELECTED_STRENGTH: f32 = 0.35;
+const HOVERED_STRENGTH: f32 = 0.15;
+const UNEXPLORED_BRIGHTNESS: f32 = 0.12;
+const EXPLORED_BRIGHTNESS: f32 = 0.55;
+const EXPLORED_SATURATION: f32 = 0.4;
+
+fn province_at(world_position: vec2<f32>) -> u32 {
+    let size = vec2<f32>(textureDimensions(province_ids));
+    // Копии чанков при зацикливании смещены на ширину карты, поэтому x берём по модулю
+    let uv = vec2<f32>(fract(world_position.x / highlight.map_size.x), world_position.y / highlight.map_size.y);
+    let pixel = clamp(uv * size, vec2<f32>(0.0), size - 1.0);
+    return textureLoad(province_ids, vec2<i32>(pixel), 0).r;
+}
+
+// Таблица цветов режима карты хранится построчно: строка = id / ширина таблицы
+fn province_color(province: u32) -> vec4<f32> {
+    let width = textureDimensions(province_colors).x;
+    let coords = vec2<u32>(province % width, province / width);
+    if coords.y >= textureDimensions(province_colors).y {
+        return vec4<f32>(0.0);
+    }
+    return textureLoad(province_colors, vec2<i32>(coords), 0);
+}
+
+// Провинции вне таблицы тумана считаются видимыми
+fn province_fog(province: u32) -> f32 {
+    let width = textureDimensions(province_visibility).x;
+    let coords = vec2<u32>(province % width, province / width);
+    if coords.y >= textureDimensions(province_visibility).y {
+        return 1.0;
+    }
+    return textureLoad(province_visibility, vec2<i32>(coords), 0).r;
+}
+
+// Неразведанное почти чёрное, разведанное, но не видимое сейчас — приглушённое и обесцвеченное
+fn apply_fog(color: vec3<f32>, fog: f32) -> vec3<f32> {
+    if fog < 0.25 {
+        return color * UNEXPLORED_BRIGHTNESS;
+    }
+    if fog < 0.75 {
+        let grey = vec3<f32>(dot(color, vec3<f32>(0.299, 0.587, 0.114)));
+        return mix(grey, color, EXPLORED_SATURATION) * EXPLORED_BRIGHTNESS;
+    }
+    return color;
+}
+
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+
+    let province = province_at(in.world_position.xz);
+    let overlay = province_color(province);
+    pbr_input.material.base_color = vec4<f32>(mix(pbr_input.material.base_color.rgb, overlay.rgb, overlay.a), pbr_input.material.base_color.a);
+
+    let fogged = apply_fog(pbr_input.material.base_color.rgb, province_fog(province));
+    pbr_input.material.base_color = vec4<f32>(fogged, pbr_input.material.base_color.a);
+
+    if province != 0u && province == highlight.selected {
+        pbr_input.material.base_color = mix(pbr_input.material.base_color, SELECTED_COLOR, SELECTED_STRENGTH);
+    } else if province != 0u && province == highlight.hovered {
+        pbr_input.material.base_color = mix(pbr_input.material.base_color, vec4<f32>(1.0), HOVERED_STRENGTH);
+    }
+
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+#ifdef PREPASS_PIPELINE
+    let out = deferred_output(in, pbr_input);
+#else
+    var out: FragmentOutput;
+    out.color = apply_pbr_lighting(pbr_input);
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+#endif
+
+    return out;
+}
@@ -0,0 +1,363 @@
+use crate::core::ai::{AiBehaviour, AiContext, AiDecision, AiGoal, AiGoalKind};
+use crate::core::ai::snapshot::AiSnapshot;
+use crate::core::country::CountryId;
+use crate::core::diplomacy::{DiplomaticAction, PeaceDeal, ALLIANCE_MIN_OPINION};
+use crate::core::economy::RECRUIT_COST;
+use crate::core::map::province::ProvinceId;
+use bevy::prelude::Entity;
+use std::collections::BTreeSet;
+
+// Армия, нужная для цели, берётся с запасом относительно видимой силы противника
+const STRENGTH_MARGIN: f32 = 1.3;
+// Для захвата пустой провинции хватает одной армии хотя бы такой силы
+const MIN_EXPEDITION_STRENGTH: u32 = 1000;
+const DEFENCE_PRIORITY: f32 = 10.0;
+const CAPITAL_PRIORITY: f32 = 5.0;
+
+const TARGET_ARMY_STRENGTH: u32 = 8000;
+const NEW_ARMY_STRENGTH: u32 = 2000;
+// Часть казны, которую ИИ не тратит на войска
+const GOLD_RESERVE: f64 = 20.0;
+
+// Войну объявляют, только если своя армия во столько раз сильнее цели вместе с её союзниками
+const WAR_STRENGTH_RATIO: u32 = 2;
+// Раньше этого мир не предлагается; после второго срока соглашаются и на ничью
+const PEACE_AFTER_DAYS: u64 = 90;
+const STALEMATE_DAYS: u64 = 365;
+const WAR_PRIORITY: f32 = 3.0;
+
+// Соседние провинции противника, которые по силам взять
+pub struct Expansion;
+
+impl AiBehaviour for Expansion {
+    fn name(&self) -> &'static str {
+        "expansion"
+    }
+
+    fn evaluate(&self, context: &mut AiContext) {
+        let country = context.country;
+        let snapshot = context.snapshot;
+        let Some(own) = snapshot.countries.get(&country) else {
+            return;
+        };
+
+        let own_strength: u32 = snapshot.units.iter()
+            .filter(|unit| unit.owner == country)
+            .map(|unit| unit.strength)
+            .sum();
+
+        let mut targets: Vec<_> = own.owned.iter()
+            .filter_map(|id| snapshot.provinces.get(id))
+            .flat_map(|province| province.neighbours.iter().copied())
+            .collect();
+        targets.sort();
+        targets.dedup();
+
+        for target in targets {
+            let Some(province) = snapshot.provinces.get(&target) else {
+                continue;
+            };
+            let Some(controller) = province.controller else {
+                continue;
+            };
+            if !snapshot.at_war(country, controller) {
+                continue;
+            }
+
+            let defence = if snapshot.is_visible_to(country, target) {
+                snapshot.strength_in(|owner| snapshot.at_war(country, owner), target)
+            } else {
+                0
+            };
+            let required = ((defence as f32 * STRENGTH_MARGIN) as u32).max(MIN_EXPEDITION_STRENGTH);
+            if required > own_strength {
+                continue;
+            }
+
+            context.goals.push(AiGoal {
+                kind: AiGoalKind::Expand { province: target },
+                priority: province.income + 1.0 - defence as f32 / 1000.0,
+                required,
+                assigned: Vec::new(),
+            });
+        }
+    }
+}
+
+// Свои провинции, где или рядом стоят вражеские армии, и занятые противником свои земли
+pub struct Defence;
+
+impl AiBehaviour for Defence {
+    fn name(&self) -> &'static str {
+        "defence"
+    }
+
+    fn evaluate(&self, context: &mut AiContext) {
+        let country = context.country;
+        let snapshot = context.snapshot;
+        let Some(own) = snapshot.countries.get(&country) else {
+            return;
+        };
+
+        let hostile = |owner: CountryId| snapshot.at_war(country, owner);
+
+        for id in own.owned.iter() {
+            let Some(province) = snapshot.provinces.get(id) else {
+                continue;
+            };
+
+            let nearby = province.neighbours.iter()
+                .filter(|neighbour| snapshot.is_visible_to(country, **neighbour))
+                .map(|neighbour| snapshot.strength_in(hostile, *neighbour))
+                .max()
+                .unwrap_or(0);
+            let threat = snapshot.strength_in(hostile, *id).max(nearby);
+            let occupied = province.controller.is_some_and(hostile);
+            if threat == 0 && !occupied {
+                continue;
+            }
+
+            let mut priority = DEFENCE_PRIORITY + province.income;
+            if own.capital == Some(*id) {
+                priority += CAPITAL_PRIORITY;
+            }
+
+            context.goals.push(AiGoal {
+                kind: AiGoalKind::Defend { province: *id, threat },
+                priority,
+                required: ((threat as f32 * STRENGTH_MARGIN) as u32).max(MIN_EXPEDITION_STRENGTH),
+                assigned: Vec::new(),
+            });
+        }
+    }
+}
+
+// Распределяет армии по целям в порядке приоритета, ближние армии идут первыми
+pub struct ArmyMovement;
+
+impl AiBehaviour for ArmyMovement {
+    fn name(&self) -> &'static str {
+        "army_movement"
+    }
+
+    fn evaluate(&self, context: &mut AiContext) {
+        let country = context.country;
+        let snapshot = context.snapshot;
+
+        let mut available: Vec<_> = snapshot.units.iter()
+            .filter(|unit| unit.owner == country && !unit.in_battle && unit.province.is_some())
+            .collect();
+
+        context.goals.sort_by(|a, b| b.priority.total_cmp(&a.priority));
+
+        for goal in context.goals.iter_mut() {
+            let Some(target) = goal.kind.province() else {
+                continue;
+            };
+
+            let distances = snapshot.distances_from(target);
+            available.sort_by_key(|unit| unit.province.and_then(|p| distances.get(&p).copied()).unwrap_or(u32::MAX));
+
+            let mut assigned_strength = 0;
+            let mut taken: Vec<Entity> = Vec::new();
+            for unit in available.iter() {
+                if assigned_strength >= goal.required {
+                    break;
+                }
+                let Some(province) = unit.province else {
+                    continue;
+                };
+                if !distances.contains_key(&province) {
+                    continue;
+                }
+
+                assigned_strength += unit.strength;
+                taken.push(unit.entity);
+
+                let heading = unit.destination.or(unit.province);
+                if heading != Some(target) {
+                    context.decisions.push(AiDecision::Move { unit: unit.entity, target });
+                }
+            }
+
+            // Сил не хватает: армии не отправляются по частям на верную гибель
+            if assigned_strength < goal.required {
+                context.decisions.retain(|decision| !matches!(decision, AiDecision::Move { unit, .. } if taken.contains(unit)));
+                continue;
+            }
+
+            available.retain(|unit| !taken.contains(&unit.entity));
+            goal.assigned = taken;
+        }
+    }
+}
+
+// Пополняет поредевшие армии, а при лишних деньгах набирает новую в столице
+pub struct EconomySpending;
+
+impl AiBehaviour for EconomySpending {
+    fn name(&self) -> &'static str {
+        "economy"
+    }
+
+    fn evaluate(&self, context: &mut AiContext) {
+        let country = context.country;
+        let snapshot = context.snapshot;
+        let Some(own) = snapshot.countries.get(&country) else {
+            return;
+        };
+
+        let mut gold = own.gold - GOLD_RESERVE;
+        let mut manpower = own.manpower;
+        let affordable = |gold: f64, manpower: u32| ((gold.max(0.0) / RECRUIT_COST) as u32).min(manpower);
+
+        let own_units: Vec<_> = snapshot.units.iter().filter(|unit| unit.owner == country).collect();
+
+        let mut reinforced = 0;
+        for unit in own_units.iter().filter(|unit| !unit.in_battle && unit.strength < TARGET_ARMY_STRENGTH) {
+            let men = affordable(gold, manpower).min(TARGET_ARMY_STRENGTH - unit.strength);
+            if men == 0 {
+                break;
+            }
+
+            gold -= men as f64 * RECRUIT_COST;
+            manpower -= men;
+            reinforced += men;
+            context.decisions.push(AiDecision::Reinforce { unit: unit.entity, men });
+        }
+
+        let mut raised = 0;
+        let army_cap = own.owned.len();
+        if let Some(capital) = own.capital.filter(|capital| own.owned.contains(capital)) {
+            if own_units.len() < army_cap && affordable(gold, manpower) >= NEW_ARMY_STRENGTH {
+                raised = NEW_ARMY_STRENGTH;
+                context.decisions.push(AiDecision::Raise { province: capital, men: NEW_ARMY_STRENGTH });
+            }
+        }
+
+        if reinforced + raised > 0 {
+            context.goals.push(AiGoal {
+                kind: AiGoalKind::Recruit { men: reinforced + raised },
+                priority: 0.0,
+                required: 0,
+                assigned: Vec::new(),
+            });
+        }
+    }
+}
+
+// Объявляет войну заметно более слабому соседу, заключает мир и ищет союзников среди друзей
+pub struct DiplomaticRelations;
+
+impl AiBehaviour for DiplomaticRelations {
+    fn name(&self) -> &'static str {
+        "diplomacy"
+    }
+
+    fn evaluate(&self, context: &mut AiContext) {
+        let country = context.country;
+        let snapshot = context.snapshot;
+        let diplomacy = &snapshot.diplomacy;
+        let Some(own) = snapshot.countries.get(&country) else {
+            return;
+        };
+
+        let strength = |owner: CountryId| -> u32 {
+            snapshot.units.iter().filter(|unit| unit.owner == owner).map(|unit| unit.strength).sum()
+        };
+
+        for war in diplomacy.wars() {
+            let Some(enemies) = war.enemies(country) else {
+                continue;
+            };
+            let days = snapshot.day.saturating_sub(war.started);
+            if days < PEACE_AFTER_DAYS {
+                continue;
+            }
+
+            for enemy in enemies.iter().filter(|enemy| war.leads(country) || war.leads(**enemy)) {
+                let Some(other) = snapshot.countries.get(enemy) else {
+                    continue;
+                };
+
+                // Противник соглашается, только если получает назад всё своё, что сам занял,
+                // и отдаёт не больше провинций, чем позволяют условия мира
+                let mut deal = PeaceDeal {
+                    demanded: held_by(snapshot, &other.owned, country),
+                    ceded: held_by(snapshot, &own.owned, *enemy),
+                };
+                let acceptable = diplomacy.peace_terms(country, *enemy, snapshot.day, other.owned.len(), strength)
+                    .map_or(0, |terms| terms.acceptable_demands());
+                deal.demanded.truncate(acceptable);
+                if deal.demanded.len() <= deal.ceded.len() && days < STALEMATE_DAYS {
+                    continue;
+                }
+
+                context.goals.push(AiGoal {
+                    kind: AiGoalKind::Peace { enemy: *enemy },
+                    priority: WAR_PRIORITY,
+                    required: 0,
+                    assigned: Vec::new(),
+                });
+                context.decisions.push(AiDecision::Diplomatic { target: *enemy, action: DiplomaticAction::OfferPeace(deal) });
+            }
+        }
+
+
+        if !diplomacy.is_at_war(country) {
+            let mut neighbours: Vec<CountryId> = own.owned.iter()
+                .filter_map(|id| snapshot.provinces.get(id))
+                .flat_map(|province| province.neighbours.iter())
+                .filter_map(|id| snapshot.provinces.get(id).and_then(|p| p.controller))
+                .filter(|other| *other != country)
+                .collect();
+            neighbours.sort();
+            neighbours.dedup();
+
+            let own_strength = strength(country);
+            let target = neighbours.into_iter()
+                .filter(|other| {
+                    !diplomacy.are_allied(country, *other)
+                        && diplomacy.truce_until(country, *other, snapshot.day).is_none()
+                        && diplomacy.opinion(country, *other) < ALLIANCE_MIN_OPINION
+                })
+                .map(|other| (other, strength(other) + diplomacy.allies(other).map(strength).sum::<u32>()))
+                .filter(|(_, defence)| own_strength >= defence.saturating_mul(WAR_STRENGTH_RATIO).max(1))
+                .min_by_key(|(_, defence)| *defence);
+
+            if let Some((enemy, _)) = target {
+                context.goals.push(AiGoal {
+                    kind: AiGoalKind::War { enemy },
+                    priority: WAR_PRIORITY,
+                    required: 0,
+                    assigned: Vec::new(),
+                });
+                context.decisions.push(AiDecision::Diplomatic { target: enemy, action: DiplomaticAction::DeclareWar });
+            }
+        }
+
+        for partner in snapshot.countries.keys() {
+            let friendly = diplomacy.opinion(country, *partner) >= ALLIANCE_MIN_OPINION
+                && diplomacy.opinion(*partner, country) >= ALLIANCE_MIN_OPINION;
+            if *partner == country || !friendly || diplomacy.are_allied(country, *partner) || diplomacy.at_war(country, *partner) {
+                continue;
+            }
+
+            context.goals.push(AiGoal {
+                kind: AiGoalKind::Alliance { partner: *partner },
+                priority: 0.0,
+                required: 0,
+                assigned: Vec::new(),
+            });
+            context.decisions.push(AiDecision::Diplomatic { target: *partner, action: DiplomaticAction::ProposeAlliance });
+        }
+    }
+}
+
+// Провинции из списка, которые сейчас контролирует указанная страна
+fn held_by(snapshot: &AiSnapshot, owned: &BTreeSet<ProvinceId>, controller: CountryId) -> Vec<ProvinceId> {
+    owned.iter()
+        .filter(|id| snapshot.provinces.get(id).is_some_and(|p| p.controller == Some(controller)))
+        .copied()
+        .collect()
+}
@@ -0,0 +1,99 @@
+use crate::core::ai::{AiGoalKind, AiState};
+use crate::core::country::{CountryId, CountryRegistry};
+use crate::core::input::{ActionState, InputAction};
+use crate::core::map::province::{ProvinceId, ProvinceRegistry};
+use bevy::color::Color;
+use bevy::prelude::{default, resource_changed, Alpha, App, BackgroundColor, BuildChildren, ChildBuild, Children, Commands, Component, IntoSystemConfigs, Node, PositionType, Query, Res, Single, Startup, Text, TextFont, UiRect, Update, Val, Visibility, With};
+use std::fmt::Write;
+
+#[derive(Component)]
+struct AiGoalsPanel;
+
+pub fn build(app: &mut App) {
+    app.add_systems(Startup, init);
+    app.add_systems(Update, (
+        toggle_panel,
+        update_panel_text.run_if(resource_changed::<AiState>),
+    ).chain());
+}
+
+fn init(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(48.0),
+                right: Val::Px(8.0),
+                padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                ..default()
+            },
+            BackgroundColor(Color::BLACK.with_alpha(0.55)),
+            Visibility::Hidden,
+            AiGoalsPanel,
+        ))
+        .with_children(|p| {
+            p.spawn((
+                Text::default(),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn toggle_panel(
+    actions: Res<ActionState>,
+    mut panel: Single<&mut Visibility, With<AiGoalsPanel>>,
+) {
+    if actions.just_pressed(InputAction::ToggleAiGoals) {
+        **panel = match **panel {
+            Visibility::Hidden => Visibility::Inherited,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+fn update_panel_text(
+    state: Res<AiState>,
+    countries: Res<CountryRegistry>,
+    registry: Res<ProvinceRegistry>,
+    panel: Single<(&Visibility, &Children), With<AiGoalsPanel>>,
+    mut texts: Query<&mut Text>,
+) {
+    let (visibility, children) = *panel;
+    if *visibility == Visibility::Hidden {
+        return;
+    }
+    let Some(mut text) = children.first().and_then(|child| texts.get_mut(*child).ok()) else {
+        return;
+    };
+
+    let province_name = |id: ProvinceId| registry.get(id).map(|p| p.name.clone()).unwrap_or_else(|| id.0.to_string());
+    let country_tag = |id: CountryId| countries.get(id).map(|c| c.tag.as_str()).unwrap_or("?");
+
+    let mut content = String::from("Цели ИИ");
+    for (country, plan) in state.plans.iter() {
+        let _ = write!(content, "\n{} — день {}:", country_tag(*country), plan.day);
+        for (behaviour, elapsed) in plan.timings.iter() {
+            let _ = write!(content, " {} {:.2} мс", behaviour, elapsed.as_secs_f64() * 1000.0);
+        }
+
+        if plan.goals.is_empty() {
+            content.push_str("\n  нет целей");
+        }
+        for goal in plan.goals.iter() {
+            let description = match goal.kind {
+                AiGoalKind::Expand { province } => format!("захват {}", province_name(province)),
+                AiGoalKind::Defend { province, threat } => format!("оборона {} (угроза {})", province_name(province), threat),
+                AiGoalKind::Recruit { men } => format!("набор {} солдат", men),
+                AiGoalKind::War { enemy } => format!("война с {}", country_tag(enemy)),
+                AiGoalKind::Peace { enemy } => format!("мир с {}", country_tag(enemy)),
+                AiGoalKind::Alliance { partner } => format!("союз с {}", country_tag(partner)),
+            };
+            let _ = write!(content, "\n  [{:.1}] {}, армий: {}", goal.priority, description, goal.assigned.len());
+        }
+    }
+
+    text.0 = content;
+}
@@ -0,0 +1,381 @@
+pub(crate) mod behaviours;
+mod debug;
+pub(crate) mod snapshot;
+
+use crate::core::ai::behaviours::{ArmyMovement, Defence, DiplomaticRelations, EconomySpending, Expansion};
+use crate::core::ai::snapshot::{AiSnapshot, AiWorld};
+use crate::core::async_tasks::{BackgroundTaskResult, BackgroundTaskSystem};
+use crate::core::country::{CountryId, CountryRegistry, PlayerCountry};
+use crate::core::diplomacy::{DiplomaticAction, DiplomaticRequest, PendingDiplomaticRequests};
+use crate::core::economy::{Treasuries, RECRUIT_COST};
+use crate::core::fog::update_fog_of_war;
+use crate::core::map::province::{ProvinceId, ProvinceMap, ProvinceRegistry};
+use crate::core::pathfinding::Pathfinder;
+use crate::core::simulation::calendar::GameCalendar;
+use crate::core::simulation::{DailyTick, SimulationHolds};
+use crate::core::unit::occupation::occupy_provinces;
+use crate::core::unit::{spawn_unit, NextUnitIndex, SpawnUnit, Unit, UnitPath, UnitPosition};
+use bevy::prelude::{App, Commands, Entity, IntoSystemConfigs, Query, Res, ResMut, Resource, Update};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Каждая страна пересматривает планы раз в столько дней, страны разнесены по дням по id
+const AI_INTERVAL_DAYS: u64 = 5;
+// Сколько стран входит в одну оценку; остальные ждут в очереди следующей
+const AI_COUNTRIES_PER_EVALUATION: usize = 8;
+// Одна фоновая задача не дольше этого; не уложившиеся страны той же оценки уходят в следующую задачу
+const AI_TIME_BUDGET: Duration = Duration::from_millis(20);
+// Решения применяются ровно через столько дней после снимка; если оценка к этому дню не готова, часы ждут её
+const AI_APPLY_DELAY_DAYS: u64 = 2;
+const AI_HOLD: &str = "ai";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AiGoalKind {
+    Expand { province: ProvinceId },
+    Defend { province: ProvinceId, threat: u32 },
+    Recruit { men: u32 },
+    War { enemy: CountryId },
+    Peace { enemy: CountryId },
+    Alliance { partner: CountryId },
+}
+
+impl AiGoalKind {
+    pub fn province(&self) -> Option<ProvinceId> {
+        match self {
+            AiGoalKind::Expand { province } | AiGoalKind::Defend { province, .. } => Some(*province),
+            AiGoalKind::Recruit { .. }
+            | AiGoalKind::War { .. }
+            | AiGoalKind::Peace { .. }
+            | AiGoalKind::Alliance { .. } => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AiGoal {
+    pub kind: AiGoalKind,
+    pub priority: f32,
+    // Сила, которую нужно собрать для цели
+    pub required: u32,
+    pub assigned: Vec<Entity>,
+}
+
+#[derive(Clone, Debug)]
+pub enum AiDecision {
+    Move { unit: Entity, target: ProvinceId },
+    Reinforce { unit: Entity, men: u32 },
+    Raise { province: ProvinceId, men: u32 },
+    Diplomatic { target: CountryId, action: DiplomaticAction },
+}
+
+pub struct AiContext<'a> {
+    pub country: CountryId,
+    pub snapshot: &'a AiSnapshot,
+    pub goals: Vec<AiGoal>,
+    pub decisions: Vec<AiDecision>,
+}
+
+// Поведения вызываются по порядку регистрации и видят цели и решения предыдущих
+pub trait AiBehaviour: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn evaluate(&self, context: &mut AiContext);
+}
+
+#[derive(Resource, Default)]
+pub struct AiBehaviours {
+    behaviours: Vec<Arc<dyn AiBehaviour>>,
+}
+
+pub trait RegisterAiBehaviour {
+    fn register_ai_behaviour(&mut self, behaviour: impl AiBehaviour + 'static) -> &mut Self;
+}
+
+impl RegisterAiBehaviour for App {
+    fn register_ai_behaviour(&mut self, behaviour: impl AiBehaviour + 'static) -> &mut Self {
+        self.init_resource::<AiBehaviours>();
+        self.world_mut().resource_mut::<AiBehaviours>().behaviours.push(Arc::new(behaviour));
+        self
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AiPlan {
+    pub day: u64,
+    pub goals: Vec<AiGoal>,
+    // Время каждого поведения, только для отладочного окна: на решения оно не влияет
+    pub timings: Vec<(&'static str, Duration)>,
+}
+
+type CountryPlan = (CountryId, AiPlan, Vec<AiDecision>);
+
+// Результат одной фоновой задачи
+pub struct AiEvaluation {
+    generation: u64,
+    plans: Vec<CountryPlan>,
+    // Страны, не уложившиеся в бюджет задачи
+    deferred: Vec<CountryId>,
+}
+
+// Оценка одного снимка: идёт фоновыми задачами, пока не будут оценены все её страны, и применяется целиком
+struct PendingEvaluation {
+    apply_day: u64,
+    snapshot: Arc<AiSnapshot>,
+    remaining: Vec<CountryId>,
+    in_flight: bool,
+    plans: Vec<CountryPlan>,
+}
+
+impl PendingEvaluation {
+    fn is_complete(&self) -> bool {
+        !self.in_flight && self.remaining.is_empty()
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct AiState {
+    queue: VecDeque<CountryId>,
+    pending: Option<PendingEvaluation>,
+    // Растёт при загрузке игры: задача, начатая до неё, отбрасывается
+    generation: u64,
+    // Последний план каждой страны, для отладочного окна
+    pub plans: BTreeMap<CountryId, AiPlan>,
+}
+
+impl AiState {
+    pub(crate) fn receive(&mut self, evaluation: AiEvaluation) {
+        if evaluation.generation != self.generation {
+            return;
+        }
+        let Some(pending) = self.pending.as_mut() else {
+            return;
+        };
+
+        pending.in_flight = false;
+        pending.plans.extend(evaluation.plans);
+        pending.remaining = evaluation.deferred;
+    }
+
+    pub(crate) fn reset(&mut self) {
+        *self = Self {
+            generation: self.generation + 1,
+            ..Self::default()
+        };
+    }
+}
+
+// Запуск фоновых задач оценки и задержка часов до их готовности
+#[derive(bevy::ecs::system::SystemParam)]
+struct AiTasks<'w> {
+    behaviours: Res<'w, AiBehaviours>,
+    tasks: Res<'w, BackgroundTaskSystem>,
+    holds: ResMut<'w, SimulationHolds>,
+}
+
+impl AiTasks<'_> {
+    fn spawn(&self, snapshot: Arc<AiSnapshot>, due: Vec<CountryId>, generation: u64) {
+        let behaviours = self.behaviours.behaviours.clone();
+        let sender = self.tasks.sender.clone();
+        thread::spawn(move || {
+            let evaluation = evaluate(&snapshot, &behaviours, due, generation);
+            if let Err(e) = sender.send(BackgroundTaskResult::AiEvaluated(evaluation)) {
+                println!("Не удалось отправить решения ИИ в основной поток: {:?}", e);
+            }
+        });
+    }
+}
+
+// Куда решения ИИ передаются дальше: поиск пути, найм и дипломатия
+#[derive(bevy::ecs::system::SystemParam)]
+pub(crate) struct AiOrders<'w, 's> {
+    commands: Commands<'w, 's>,
+    map: Option<Res<'w, ProvinceMap>>,
+    next_index: ResMut<'w, NextUnitIndex>,
+    pathfinder: Res<'w, Pathfinder>,
+    diplomatic: ResMut<'w, PendingDiplomaticRequests>,
+}
+
+pub fn build(app: &mut App) {
+    app.init_resource::<AiState>();
+    app.register_ai_behaviour(DiplomaticRelations);
+    app.register_ai_behaviour(Expansion);
+    app.register_ai_behaviour(Defence);
+    app.register_ai_behaviour(ArmyMovement);
+    app.register_ai_behaviour(EconomySpending);
+
+    app.add_systems(DailyTick, (
+        apply_ai_decisions.after(occupy_provinces),
+        schedule_ai_evaluation.after(update_fog_of_war),
+    ));
+    app.add_systems(Update, continue_ai_evaluation);
+
+    debug::build(app);
+}
+
+// Решения применяются в назначенный день, а не по приходу: иначе итог партии зависел бы от скорости машины.
+// Часы не пускают симуляцию в этот день, пока оценка не готова. Казна и армии перепроверяются, снимок мог устареть
+pub(crate) fn apply_ai_decisions(
+    calendar: Res<GameCalendar>,
+    mut state: ResMut<AiState>,
+    mut treasuries: ResMut<Treasuries>,
+    registry: Res<ProvinceRegistry>,
+    mut units: Query<(&mut Unit, &UnitPosition, &mut UnitPath)>,
+    orders: AiOrders,
+) {
+    let state = &mut *state;
+    let AiOrders { mut commands, map, mut next_index, pathfinder, mut diplomatic } = orders;
+
+    let today = calendar.days_elapsed();
+    let Some(pending) = state.pending.take_if(|pending| today >= pending.apply_day && pending.is_complete()) else {
+        return;
+    };
+
+    for (country, plan, decisions) in pending.plans {
+        for decision in decisions {
+            match decision {
+                // Маршрут по графу провинций считается прямо в тике, чтобы армия вышла в тот же день
+                AiDecision::Move { unit, target } => {
+                    let Ok((unit, position, mut path)) = units.get_mut(unit) else {
+                        continue;
+                    };
+                    let Some(from) = position.province.filter(|_| unit.owner == country) else {
+                        continue;
+                    };
+                    let Some(found) = pathfinder.find_now(from, target) else {
+                        continue;
+                    };
+
+                    path.waypoints.clear();
+                    path.waypoints.extend(found.waypoints);
+                }
+                AiDecision::Reinforce { unit, men } => {
+                    let treasury = treasuries.countries.entry(country).or_default();
+                    let cost = men as f64 * RECRUIT_COST;
+                    let Ok((mut unit, ..)) = units.get_mut(unit) else {
+                        continue;
+                    };
+                    if unit.owner != country || treasury.gold < cost || treasury.manpower < men {
+                        continue;
+                    }
+
+                    treasury.gold -= cost;
+                    treasury.manpower -= men;
+                    unit.strength += men;
+                }
+                AiDecision::Raise { province, men } => {
+                    let treasury = treasuries.countries.entry(country).or_default();
+                    let cost = men as f64 * RECRUIT_COST;
+                    let Some(province) = registry.get(province) else {
+                        continue;
+                    };
+                    if treasury.gold < cost || treasury.manpower < men {
+                        continue;
+                    }
+
+                    treasury.gold -= cost;
+                    treasury.manpower -= men;
+                    let spawn = SpawnUnit { owner: country, strength: men, position: province.centroid, path: Vec::new() };
+                    spawn_unit(&mut commands, map.as_deref(), &mut next_index, &spawn);
+                }
+                // Проверка и последствия — общие с игроком, отказ придёт событием
+                AiDecision::Diplomatic { target, action } => {
+                    diplomatic.push(DiplomaticRequest { actor: country, target, action });
+                }
+            }
+        }
+
+        state.plans.insert(country, plan);
+    }
+}
+
+fn schedule_ai_evaluation(
+    calendar: Res<GameCalendar>,
+    player: Res<PlayerCountry>,
+    countries: Res<CountryRegistry>,
+    world: AiWorld,
+    mut state: ResMut<AiState>,
+    mut tasks: AiTasks,
+) {
+    let day = calendar.days_elapsed();
+    for country in countries.iter() {
+        let due = (country.id.0 as u64 + day).is_multiple_of(AI_INTERVAL_DAYS);
+        if due && player.0 != Some(country.id) && !state.queue.contains(&country.id) {
+            state.queue.push_back(country.id);
+        }
+    }
+
+    if state.pending.is_some() || state.queue.is_empty() {
+        return;
+    }
+
+    let count = state.queue.len().min(AI_COUNTRIES_PER_EVALUATION);
+    let due: Vec<CountryId> = state.queue.drain(..count).collect();
+    let snapshot = Arc::new(world.snapshot(day));
+    let apply_day = day + AI_APPLY_DELAY_DAYS;
+
+    tasks.spawn(snapshot.clone(), due, state.generation);
+    tasks.holds.hold(AI_HOLD, apply_day);
+    state.pending = Some(PendingEvaluation {
+        apply_day,
+        snapshot,
+        remaining: Vec::new(),
+        in_flight: true,
+        plans: Vec::new(),
+    });
+}
+
+// Страны, не уложившиеся в бюджет, оцениваются следующей задачей по тому же снимку;
+// готовая оценка отпускает часы
+fn continue_ai_evaluation(
+    mut state: ResMut<AiState>,
+    mut tasks: AiTasks,
+) {
+    let Some(pending) = state.pending.as_ref().filter(|pending| !pending.is_complete()) else {
+        if tasks.holds.is_held_by(AI_HOLD) {
+            tasks.holds.release(AI_HOLD);
+        }
+        return;
+    };
+    if pending.in_flight {
+        return;
+    }
+
+    let generation = state.generation;
+    let Some(pending) = state.pending.as_mut() else {
+        return;
+    };
+    pending.in_flight = true;
+    tasks.spawn(pending.snapshot.clone(), std::mem::take(&mut pending.remaining), generation);
+}
+
+// Хотя бы одна страна оценивается всегда, иначе оценка на медленной машине никогда не завершится
+fn evaluate(snapshot: &AiSnapshot, behaviours: &[Arc<dyn AiBehaviour>], due: Vec<CountryId>, generation: u64) -> AiEvaluation {
+    let deadline = Instant::now() + AI_TIME_BUDGET;
+    let mut plans = Vec::new();
+    let mut due = due.into_iter();
+
+    for country in due.by_ref() {
+        let mut context = AiContext {
+            country,
+            snapshot,
+            goals: Vec::new(),
+            decisions: Vec::new(),
+        };
+        let mut timings = Vec::with_capacity(behaviours.len());
+        for behaviour in behaviours {
+            let started = Instant::now();
+            behaviour.evaluate(&mut context);
+            timings.push((behaviour.name(), started.elapsed()));
+        }
+
+        let plan = AiPlan { day: snapshot.day, goals: context.goals, timings };
+        plans.push((country, plan, context.decisions));
+
+        if Instant::now() >= deadline {
+            break;
+        }
+    }
+
+    AiEvaluation { generation, plans, deferred: due.collect() }
+}
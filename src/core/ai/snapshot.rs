@@ -0,0 +1,157 @@
+use crate::core::country::{CountryId, CountryRegistry};
+use crate::core::diplomacy::Diplomacy;
+use crate::core::economy::{Economy, Treasuries};
+use crate::core::fog::FogOfWar;
+use crate::core::map::province::adjacency::{AdjacencyKind, ProvinceGraph};
+use crate::core::map::province::{ProvinceId, ProvinceMap, ProvinceRegistry};
+use crate::core::unit::battle::InBattle;
+use crate::core::unit::{Unit, UnitPath, UnitPosition};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::{Entity, Has, Query, Res};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+#[derive(Clone, Debug)]
+pub struct CountrySnapshot {
+    pub capital: Option<ProvinceId>,
+    pub owned: BTreeSet<ProvinceId>,
+    pub gold: f64,
+    pub manpower: u32,
+    // ИИ знает о чужих армиях только в видимых ему провинциях
+    pub visible: BTreeSet<ProvinceId>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ProvinceSnapshot {
+    pub controller: Option<CountryId>,
+    pub income: f32,
+    // Только соседи, куда можно пройти армией
+    pub neighbours: Vec<ProvinceId>,
+}
+
+#[derive(Clone, Debug)]
+pub struct UnitSnapshot {
+    pub entity: Entity,
+    pub owner: CountryId,
+    pub strength: u32,
+    pub province: Option<ProvinceId>,
+    // Провинция последней точки маршрута, если армия в пути
+    pub destination: Option<ProvinceId>,
+    pub in_battle: bool,
+}
+
+// Всё, из чего собирается снимок для ИИ
+#[derive(SystemParam)]
+pub struct AiWorld<'w, 's> {
+    countries: Res<'w, CountryRegistry>,
+    registry: Res<'w, ProvinceRegistry>,
+    graph: Res<'w, ProvinceGraph>,
+    economy: Res<'w, Economy>,
+    treasuries: Res<'w, Treasuries>,
+    fog: Res<'w, FogOfWar>,
+    diplomacy: Res<'w, Diplomacy>,
+    map: Option<Res<'w, ProvinceMap>>,
+    units: Query<'w, 's, (Entity, &'static Unit, &'static UnitPosition, &'static UnitPath, Has<InBattle>)>,
+}
+
+// Копия состояния мира для фоновой оценки: поток ИИ не трогает ECS
+#[derive(Clone, Debug, Default)]
+pub struct AiSnapshot {
+    pub day: u64,
+    pub countries: BTreeMap<CountryId, CountrySnapshot>,
+    pub provinces: BTreeMap<ProvinceId, ProvinceSnapshot>,
+    pub units: Vec<UnitSnapshot>,
+    pub diplomacy: Diplomacy,
+}
+
+impl AiWorld<'_, '_> {
+    pub fn snapshot(&self, day: u64) -> AiSnapshot {
+        let countries = self.countries.iter()
+            .map(|country| {
+                let treasury = self.treasuries.get(country.id);
+                (country.id, CountrySnapshot {
+                    capital: country.capital,
+                    owned: country.owned.clone(),
+                    gold: treasury.gold,
+                    manpower: treasury.manpower,
+                    visible: self.fog.countries.get(&country.id).map(|v| v.visible.clone()).unwrap_or_default(),
+                })
+            })
+            .collect();
+
+        let provinces = self.registry.iter()
+            .filter(|province| !province.is_sea)
+            .map(|province| {
+                let neighbours = self.graph.neighbours(province.id).iter()
+                    .filter(|a| matches!(a.kind, AdjacencyKind::Land | AdjacencyKind::Strait))
+                    .map(|a| a.neighbour)
+                    .collect();
+
+                (province.id, ProvinceSnapshot {
+                    controller: self.countries.controller_of(province.id),
+                    income: self.economy.province(province.id).map(|p| p.output.income()).unwrap_or(0.0),
+                    neighbours,
+                })
+            })
+            .collect();
+
+        let map = self.map.as_deref();
+        let mut units: Vec<UnitSnapshot> = self.units.iter()
+            .map(|(entity, unit, position, path, in_battle)| UnitSnapshot {
+                entity,
+                owner: unit.owner,
+                strength: unit.strength,
+                province: position.province,
+                destination: path.waypoints.back().and_then(|end| map.and_then(|map| map.province_at(end.x, end.y))),
+                in_battle,
+            })
+            .collect();
+        units.sort_by_key(|unit| unit.entity);
+
+        AiSnapshot {
+            day,
+            countries,
+            provinces,
+            units,
+            diplomacy: self.diplomacy.clone(),
+        }
+    }
+}
+
+impl AiSnapshot {
+    pub fn at_war(&self, a: CountryId, b: CountryId) -> bool {
+        self.diplomacy.at_war(a, b)
+    }
+
+    pub fn is_visible_to(&self, country: CountryId, province: ProvinceId) -> bool {
+        self.countries.get(&country).is_some_and(|c| c.visible.contains(&province))
+    }
+
+    // Суммарная сила армий в провинции у стран, подходящих под фильтр
+    pub fn strength_in(&self, owner: impl Fn(CountryId) -> bool, province: ProvinceId) -> u32 {
+        self.units.iter()
+            .filter(|unit| unit.province == Some(province) && owner(unit.owner))
+            .map(|unit| unit.strength)
+            .sum()
+    }
+
+    // Число переходов до каждой провинции; недостижимые по суше в результат не попадают
+    pub fn distances_from(&self, start: ProvinceId) -> BTreeMap<ProvinceId, u32> {
+        let mut distances = BTreeMap::from([(start, 0)]);
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(current) = queue.pop_front() {
+            let distance = distances[&current];
+            let Some(province) = self.provinces.get(&current) else {
+                continue;
+            };
+            for neighbour in province.neighbours.iter() {
+                if !distances.contains_key(neighbour) {
+                    distances.insert(*neighbour, distance + 1);
+                    queue.push_back(*neighbour);
+                }
+            }
+        }
+
+        distances
+    }
+}
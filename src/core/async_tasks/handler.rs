@@ -1,15 +1,21 @@
 use bevy::prelude::*;
 use bevy::render::mesh::{Indices, VertexAttributeValues};
+use crate::core::ai::AiState;
 use crate::core::async_tasks::{BackgroundTaskResult, BackgroundTaskSystem};
 use crate::core::async_tasks::chunk_loading::process_loaded_chunk;
+use crate::core::map::borders::ProvinceBorders;
 use crate::core::map::components::WorldChunk;
 use crate::core::map::terrain::mesh_pool::MeshPool;
+use crate::core::pathfinding::Pathfinder;
 
 pub fn handle_background_tasks(
+    mut commands: Commands,
     task_system: ResMut<BackgroundTaskSystem>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut mesh_pool: ResMut<MeshPool>,
     mut q: Query<(Entity, &mut Mesh3d, &mut WorldChunk)>,
+    mut pathfinder: ResMut<Pathfinder>,
+    mut ai: ResMut<AiState>,
 ) {
     let max_tasks_per_frame = 4;
     let mut processed_tasks = 0;
@@ -28,6 +34,18 @@ pub fn handle_background_tasks(
                 if let Ok((_, _, mut chunk)) = q.get_mut(chunk_data.entity) {
                     chunk.generated = true;
                 }
+            },
+            BackgroundTaskResult::BordersExtracted(lines) => {
+                commands.insert_resource(ProvinceBorders { lines });
+            }
+            BackgroundTaskResult::PathFound(result) => {
+                pathfinder.complete(result);
+            }
+            BackgroundTaskResult::PassabilityReady(grid) => {
+                pathfinder.set_grid(grid);
+            }
+            BackgroundTaskResult::AiEvaluated(evaluation) => {
+                ai.receive(evaluation);
             }
         }
     }
@@ -3,12 +3,20 @@ mod chunk_loading;
 
 use bevy::prelude::*;
 use crossbeam_channel::{unbounded, Receiver, Sender};
+use crate::core::ai::AiEvaluation;
 use crate::core::async_tasks::handler::handle_background_tasks;
+use crate::core::map::borders::extraction::BorderLine;
 use crate::core::map::terrain::cache::LodLevel;
+use crate::core::pathfinding::passability::PassabilityGrid;
+use crate::core::pathfinding::PathQueryResult;
 
 pub enum BackgroundTaskResult {
     ChunkLoaded(ChunkData),
-    ChunkGenerated(GeneratedChunkData)
+    ChunkGenerated(GeneratedChunkData),
+    BordersExtracted(Vec<BorderLine>),
+    PathFound(PathQueryResult),
+    PassabilityReady(PassabilityGrid),
+    AiEvaluated(AiEvaluation),
 }
 
 pub struct GeneratedChunkData {
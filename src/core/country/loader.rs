@@ -0,0 +1,133 @@
+use crate::core::country::{CountryRegistry, PlayerCountry};
+use crate::core::map::province::{ProvinceId, ProvinceRegistry};
+use bevy::prelude::{Commands, Res};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+
+#[derive(Deserialize)]
+struct CountryDefinitions {
+    countries: Vec<CountryDefinition>,
+}
+
+#[derive(Deserialize)]
+struct CountryDefinition {
+    tag: String,
+    name: String,
+    color: (u8, u8, u8),
+    #[serde(default)]
+    capital: Option<u16>,
+}
+
+// Владельцы задаются списком провинций на страну, контролёры — только для оккупированных провинций
+#[derive(Deserialize, Default)]
+struct Scenario {
+    #[serde(default)]
+    owners: BTreeMap<String, Vec<u16>>,
+    #[serde(default)]
+    controllers: BTreeMap<u16, String>,
+    #[serde(default)]
+    player: Option<String>,
+}
+
+pub fn load_countries(
+    mut commands: Commands,
+    provinces: Res<ProvinceRegistry>,
+) {
+    let definitions = load_definitions("common/data/countries.ron");
+    let scenario = load_scenario("common/data/scenario.ron");
+
+    let player = scenario.player.clone();
+    let countries = build_countries(definitions, scenario, &provinces);
+
+    let player = player.and_then(|tag| {
+        let id = countries.id_by_tag(&tag);
+        if id.is_none() {
+            eprintln!("Игрок выбран за неизвестную страну {}", tag);
+        }
+        id
+    });
+    commands.insert_resource(PlayerCountry(player));
+
+    if countries.is_empty() {
+        eprintln!("Не загружено ни одной страны");
+    }
+
+    #[cfg(debug_assertions)]
+    println!("Загружено {} стран", countries.len());
+
+    commands.insert_resource(countries);
+}
+
+fn build_countries(definitions: CountryDefinitions, scenario: Scenario, provinces: &ProvinceRegistry) -> CountryRegistry {
+    let mut countries = CountryRegistry::default();
+
+    for definition in definitions.countries {
+        if countries.id_by_tag(&definition.tag).is_some() {
+            eprintln!("Страна {} описана несколько раз", definition.tag);
+            continue;
+        }
+
+        let color = [definition.color.0, definition.color.1, definition.color.2];
+        countries.insert(definition.tag, definition.name, color, definition.capital.map(ProvinceId));
+    }
+
+    for (tag, owned) in scenario.owners {
+        let Some(country) = countries.id_by_tag(&tag) else {
+            eprintln!("В сценарии указана неизвестная страна {}", tag);
+            continue;
+        };
+
+        for id in owned {
+            let province = ProvinceId(id);
+            if provinces.get(province).is_none() {
+                eprintln!("Страна {} владеет неизвестной провинцией {}", tag, id);
+                continue;
+            }
+
+            if let Some(previous) = countries.set_owner(province, Some(country)) {
+                eprintln!("Провинция {} указана у нескольких стран, владельцем остаётся последняя ({} вместо {:?})", id, tag, previous);
+            }
+            countries.set_controller(province, Some(country));
+        }
+    }
+
+    for (id, tag) in scenario.controllers {
+        match countries.id_by_tag(&tag) {
+            Some(country) => {
+                countries.set_controller(ProvinceId(id), Some(country));
+            }
+            None => eprintln!("Провинцию {} контролирует неизвестная страна {}", id, tag),
+        }
+    }
+
+    for country in countries.iter() {
+        if let Some(capital) = country.capital {
+            if !country.owned.contains(&capital) {
+                eprintln!("Столица страны {} (провинция {}) ей не принадлежит", country.tag, capital.0);
+            }
+        }
+    }
+
+    countries
+}
+
+fn load_definitions(path: &str) -> CountryDefinitions {
+    let content = fs::read_to_string(path).expect("Failed to read country definitions");
+    ron::from_str(&content).expect("Failed to parse country definitions")
+}
+
+fn load_scenario(path: &str) -> Scenario {
+    let Ok(content) = fs::read_to_string(path) else {
+        eprintln!("Файл сценария {} не найден, провинции остаются без владельцев", path);
+        return Scenario::default();
+    };
+
+    match ron::from_str(&content) {
+        Ok(scenario) => scenario,
+        Err(e) => {
+            eprintln!("Не удалось разобрать {}: {}", path, e);
+            Scenario::default()
+        }
+    }
+}
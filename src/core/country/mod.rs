@@ -0,0 +1,192 @@
+pub(crate) mod loader;
+
+use crate::core::country::loader::load_countries;
+use crate::core::map::province::loader::load_provinces;
+use crate::core::map::province::ProvinceId;
+use crate::core::simulation::DailyTick;
+use bevy::prelude::{App, Event, EventReader, EventWriter, IntoSystemConfigs, ResMut, Resource, Startup};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CountryId(pub u16);
+
+#[derive(Clone, Debug)]
+pub struct Country {
+    pub id: CountryId,
+    pub tag: String,
+    pub name: String,
+    pub color: [u8; 3],
+    pub capital: Option<ProvinceId>,
+    pub owned: BTreeSet<ProvinceId>,
+    pub controlled: BTreeSet<ProvinceId>,
+}
+
+#[derive(Resource, Default)]
+pub struct CountryRegistry {
+    countries: Vec<Country>,
+    by_tag: HashMap<String, CountryId>,
+    owners: HashMap<ProvinceId, CountryId>,
+    controllers: HashMap<ProvinceId, CountryId>,
+}
+
+impl CountryRegistry {
+    pub fn insert(&mut self, tag: String, name: String, color: [u8; 3], capital: Option<ProvinceId>) -> CountryId {
+        let id = CountryId(self.countries.len() as u16);
+        self.by_tag.insert(tag.clone(), id);
+        self.countries.push(Country {
+            id,
+            tag,
+            name,
+            color,
+            capital,
+            owned: BTreeSet::new(),
+            controlled: BTreeSet::new(),
+        });
+        id
+    }
+
+    pub fn get(&self, id: CountryId) -> Option<&Country> {
+        self.countries.get(id.0 as usize)
+    }
+
+    pub fn id_by_tag(&self, tag: &str) -> Option<CountryId> {
+        self.by_tag.get(tag).copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Country> {
+        self.countries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.countries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.countries.is_empty()
+    }
+
+    pub fn owner_of(&self, province: ProvinceId) -> Option<CountryId> {
+        self.owners.get(&province).copied()
+    }
+
+    pub fn controller_of(&self, province: ProvinceId) -> Option<CountryId> {
+        self.controllers.get(&province).copied()
+    }
+
+    // Напрямую вызывается только при загрузке сценария, в игре владелец меняется через ChangeProvinceOwner
+    pub(crate) fn set_owner(&mut self, province: ProvinceId, owner: Option<CountryId>) -> Option<CountryId> {
+        let previous = match owner {
+            Some(owner) => self.owners.insert(province, owner),
+            None => self.owners.remove(&province),
+        };
+
+        if let Some(country) = previous.and_then(|id| self.countries.get_mut(id.0 as usize)) {
+            country.owned.remove(&province);
+        }
+        if let Some(country) = owner.and_then(|id| self.countries.get_mut(id.0 as usize)) {
+            country.owned.insert(province);
+        }
+
+        previous
+    }
+
+    pub(crate) fn set_controller(&mut self, province: ProvinceId, controller: Option<CountryId>) -> Option<CountryId> {
+        let previous = match controller {
+            Some(controller) => self.controllers.insert(province, controller),
+            None => self.controllers.remove(&province),
+        };
+
+        if let Some(country) = previous.and_then(|id| self.countries.get_mut(id.0 as usize)) {
+            country.controlled.remove(&province);
+        }
+        if let Some(country) = controller.and_then(|id| self.countries.get_mut(id.0 as usize)) {
+            country.controlled.insert(province);
+        }
+
+        previous
+    }
+}
+
+// Страна, за которую играет человек: её туман войны показывается на карте, остальными управляет ИИ
+#[derive(Resource, Default, Clone, Copy, Debug)]
+pub struct PlayerCountry(pub Option<CountryId>);
+
+// Передача провинции: меняется и владелец, и контролёр
+#[derive(Event)]
+pub struct ChangeProvinceOwner {
+    pub province: ProvinceId,
+    pub owner: Option<CountryId>,
+}
+
+// Оккупация: владелец остаётся прежним
+#[derive(Event)]
+pub struct ChangeProvinceController {
+    pub province: ProvinceId,
+    pub controller: Option<CountryId>,
+}
+
+#[derive(Event)]
+pub struct ProvinceOwnerChanged {
+    pub province: ProvinceId,
+    pub previous: Option<CountryId>,
+    pub owner: Option<CountryId>,
+}
+
+#[derive(Event)]
+pub struct ProvinceControllerChanged {
+    pub province: ProvinceId,
+    pub previous: Option<CountryId>,
+    pub controller: Option<CountryId>,
+}
+
+pub fn build(app: &mut App) {
+    app.init_resource::<CountryRegistry>();
+    app.init_resource::<PlayerCountry>();
+    app.add_event::<ChangeProvinceOwner>();
+    app.add_event::<ChangeProvinceController>();
+    app.add_event::<ProvinceOwnerChanged>();
+    app.add_event::<ProvinceControllerChanged>();
+    app.add_systems(Startup, load_countries.after(load_provinces));
+    // Владельцы меняются в том же тике, где их сменили: следующий тик кадра уже видит новых
+    app.add_systems(DailyTick, apply_ownership_changes);
+}
+
+pub fn apply_ownership_changes(
+    mut countries: ResMut<CountryRegistry>,
+    mut owner_requests: EventReader<ChangeProvinceOwner>,
+    mut controller_requests: EventReader<ChangeProvinceController>,
+    mut owner_changes: EventWriter<ProvinceOwnerChanged>,
+    mut controller_changes: EventWriter<ProvinceControllerChanged>,
+) {
+    for request in owner_requests.read() {
+        let previous = countries.set_owner(request.province, request.owner);
+        if previous != request.owner {
+            owner_changes.send(ProvinceOwnerChanged {
+                province: request.province,
+                previous,
+                owner: request.owner,
+            });
+        }
+
+        let previous = countries.set_controller(request.province, request.owner);
+        if previous != request.owner {
+            controller_changes.send(ProvinceControllerChanged {
+                province: request.province,
+                previous,
+                controller: request.owner,
+            });
+        }
+    }
+
+    for request in controller_requests.read() {
+        let previous = countries.set_controller(request.province, request.controller);
+        if previous != request.controller {
+            controller_changes.send(ProvinceControllerChanged {
+                province: request.province,
+                previous,
+                controller: request.controller,
+            });
+        }
+    }
+}
@@ -1,7 +1,9 @@
 use std::panic;
 use backtrace::Backtrace;
 use std::fs;
-use bevy::prelude::{ButtonInput, KeyCode, Res};
+use bevy::prelude::Res;
+use crate::core::input::{ActionState, InputAction};
+use crate::core::save::autosave::emergency_save;
 use chrono::Local;
 
 pub fn setup_panic_handler() {
@@ -13,6 +15,8 @@ pub fn setup_panic_handler() {
             backtrace
         );
 
+        emergency_save();
+
         let log_dir = "tmp/logs";
         if let Err(e) = fs::create_dir_all(log_dir) {
             eprintln!("Failed to create logs directory: {}", e);
@@ -30,8 +34,8 @@ pub fn setup_panic_handler() {
     }));
 }
 
-pub fn trigger_panic(keys: Res<ButtonInput<KeyCode>>) {
-    if keys.just_pressed(KeyCode::KeyP) {
+pub fn trigger_panic(actions: Res<ActionState>) {
+    if actions.just_pressed(InputAction::DebugPanic) {
         panic!("Manual panic triggered!");
     }
 }
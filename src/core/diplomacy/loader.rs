@@ -0,0 +1,55 @@
+use crate::core::country::CountryRegistry;
+use crate::core::diplomacy::Diplomacy;
+use bevy::prelude::{Res, ResMut};
+use serde::Deserialize;
+use std::fs;
+
+// Союзы и начальные мнения задаются парами тегов, мнение — от первой страны о второй
+#[derive(Deserialize, Default)]
+struct ScenarioDiplomacy {
+    #[serde(default)]
+    alliances: Vec<(String, String)>,
+    #[serde(default)]
+    opinions: Vec<(String, String, i32)>,
+}
+
+pub(crate) fn load_diplomacy(
+    countries: Res<CountryRegistry>,
+    mut diplomacy: ResMut<Diplomacy>,
+) {
+    let path = "common/data/scenario.ron";
+    let Ok(content) = fs::read_to_string(path) else {
+        return;
+    };
+
+    let scenario = match ron::from_str::<ScenarioDiplomacy>(&content) {
+        Ok(scenario) => scenario,
+        Err(e) => {
+            eprintln!("Не удалось разобрать дипломатию сценария {}: {}", path, e);
+            return;
+        }
+    };
+
+    let id = |tag: &str| {
+        let id = countries.id_by_tag(tag);
+        if id.is_none() {
+            eprintln!("В дипломатии сценария указана неизвестная страна {}", tag);
+        }
+        id
+    };
+
+    for (a, b) in scenario.alliances.iter() {
+        if let (Some(a), Some(b)) = (id(a), id(b)) {
+            diplomacy.insert_alliance(a, b);
+        }
+    }
+
+    for (of, about, opinion) in scenario.opinions.iter() {
+        if let (Some(of), Some(about)) = (id(of), id(about)) {
+            diplomacy.change_opinion(of, about, *opinion);
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    println!("Загружено союзов: {}", diplomacy.alliance_entries().count());
+}
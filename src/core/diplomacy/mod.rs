@@ -0,0 +1,724 @@
+pub(crate) mod loader;
+mod panel;
+
+use crate::core::country::loader::load_countries;
+use crate::core::ai::apply_ai_decisions;
+use crate::core::country::{apply_ownership_changes, ChangeProvinceController, ChangeProvinceOwner, CountryId, CountryRegistry};
+use crate::core::diplomacy::loader::load_diplomacy;
+use crate::core::economy::Treasuries;
+use crate::core::map::province::ProvinceId;
+use crate::core::simulation::calendar::GameCalendar;
+use crate::core::simulation::{run_simulation, DailyTick, MonthlyTick};
+use crate::core::unit::Unit;
+use bevy::prelude::{App, Event, EventReader, EventWriter, IntoSystemConfigs, Query, Res, ResMut, Resource, Startup, Update};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+pub const MIN_OPINION: i32 = -200;
+pub const MAX_OPINION: i32 = 200;
+// Ежемесячно мнение сдвигается к нулю на столько, пока страны не воюют
+const OPINION_DECAY: i32 = 2;
+const WAR_DECLARED_OPINION: i32 = -100;
+const ALLIANCE_OPINION: i32 = 25;
+const PEACE_OPINION: i32 = 20;
+pub const GIFT_GOLD: f64 = 25.0;
+pub const GIFT_OPINION: i32 = 20;
+// Союз принимается, только если мнение о предлагающем не ниже этого
+pub const ALLIANCE_MIN_OPINION: i32 = 50;
+pub const TRUCE_DAYS: u64 = 730;
+// Раньше этого срока войны цель не отдаёт ни одной своей провинции
+const PEACE_DEMAND_MIN_DAYS: u64 = 30;
+// К этому сроку усталость от войны полная: при достаточном перевесе противника цель отдаёт всё занятое
+const WAR_EXHAUSTION_DAYS: u64 = 365;
+// Перевес в силе, при котором цель уступает наибольшую долю земель
+const FULL_STRENGTH_ADVANTAGE: f32 = 2.0;
+
+#[derive(Clone, Debug)]
+pub struct War {
+    // Первые в списках — зачинщик и его цель, мир между ними завершает всю войну
+    pub attacker: CountryId,
+    pub defender: CountryId,
+    pub attackers: BTreeSet<CountryId>,
+    pub defenders: BTreeSet<CountryId>,
+    pub started: u64,
+}
+
+impl War {
+    // Сторона страны в войне: true — нападающие
+    pub fn side_of(&self, country: CountryId) -> Option<bool> {
+        if self.attackers.contains(&country) {
+            Some(true)
+        } else if self.defenders.contains(&country) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    pub fn leads(&self, country: CountryId) -> bool {
+        country == self.attacker || country == self.defender
+    }
+
+    pub fn enemies(&self, country: CountryId) -> Option<&BTreeSet<CountryId>> {
+        match self.side_of(country)? {
+            true => Some(&self.defenders),
+            false => Some(&self.attackers),
+        }
+    }
+}
+
+// Что цель взвешивает, соглашаясь отдать земли: длительность войны и силы сторон
+#[derive(Clone, Copy, Debug)]
+pub struct PeaceTerms {
+    pub days_at_war: u64,
+    pub target_provinces: usize,
+    pub actor_strength: u32,
+    pub target_strength: u32,
+}
+
+impl PeaceTerms {
+    // Сколько своих провинций цель готова отдать. Общее правило для ИИ и игрока: сразу после
+    // объявления войны не отдаётся ничего, а целиком страна уступается только долгой войной при двойном перевесе
+    pub fn acceptable_demands(&self) -> usize {
+        if self.days_at_war < PEACE_DEMAND_MIN_DAYS {
+            return 0;
+        }
+
+        let exhaustion = (self.days_at_war as f32 / WAR_EXHAUSTION_DAYS as f32).min(1.0);
+        let advantage = (self.actor_strength as f32 / self.target_strength.max(1) as f32 / FULL_STRENGTH_ADVANTAGE).min(1.0);
+        (self.target_provinces as f32 * exhaustion * advantage).floor() as usize
+    }
+}
+
+// Пара стран хранится упорядоченной: отношения союза и перемирия симметричны
+fn pair(a: CountryId, b: CountryId) -> (CountryId, CountryId) {
+    if a < b { (a, b) } else { (b, a) }
+}
+
+#[derive(Resource, Clone, Debug, Default)]
+pub struct Diplomacy {
+    // Мнение первой страны о второй, отсутствие записи означает 0
+    opinions: BTreeMap<(CountryId, CountryId), i32>,
+    alliances: BTreeSet<(CountryId, CountryId)>,
+    // День окончания перемирия в днях от начала партии
+    truces: BTreeMap<(CountryId, CountryId), u64>,
+    wars: Vec<War>,
+}
+
+impl Diplomacy {
+    pub fn opinion(&self, of: CountryId, about: CountryId) -> i32 {
+        self.opinions.get(&(of, about)).copied().unwrap_or(0)
+    }
+
+    pub fn change_opinion(&mut self, of: CountryId, about: CountryId, delta: i32) {
+        if of == about {
+            return;
+        }
+        let opinion = self.opinions.entry((of, about)).or_insert(0);
+        *opinion = (*opinion + delta).clamp(MIN_OPINION, MAX_OPINION);
+    }
+
+    pub fn are_allied(&self, a: CountryId, b: CountryId) -> bool {
+        self.alliances.contains(&pair(a, b))
+    }
+
+    pub fn allies(&self, country: CountryId) -> impl Iterator<Item = CountryId> + '_ {
+        self.alliances.iter().filter_map(move |(a, b)| match (*a == country, *b == country) {
+            (true, _) => Some(*b),
+            (_, true) => Some(*a),
+            _ => None,
+        })
+    }
+
+    pub fn truce_until(&self, a: CountryId, b: CountryId, today: u64) -> Option<u64> {
+        self.truces.get(&pair(a, b)).copied().filter(|until| *until > today)
+    }
+
+    pub fn wars(&self) -> &[War] {
+        &self.wars
+    }
+
+    pub fn war_between(&self, a: CountryId, b: CountryId) -> Option<&War> {
+        self.wars.iter().find(|war| war.enemies(a).is_some_and(|enemies| enemies.contains(&b)))
+    }
+
+    // Условия мира в общей войне; сила стороны — сумма армий всех её участников
+    pub fn peace_terms(
+        &self,
+        actor: CountryId,
+        target: CountryId,
+        today: u64,
+        target_provinces: usize,
+        strength: impl Fn(CountryId) -> u32,
+    ) -> Option<PeaceTerms> {
+        let war = self.war_between(actor, target)?;
+        let side_strength = |enemies: Option<&BTreeSet<CountryId>>| -> u32 {
+            enemies.into_iter().flatten().map(|country| strength(*country)).sum()
+        };
+
+        Some(PeaceTerms {
+            days_at_war: today.saturating_sub(war.started),
+            target_provinces,
+            actor_strength: side_strength(war.enemies(target)),
+            target_strength: side_strength(war.enemies(actor)),
+        })
+    }
+
+    pub fn at_war(&self, a: CountryId, b: CountryId) -> bool {
+        self.war_between(a, b).is_some()
+    }
+
+    pub fn is_at_war(&self, country: CountryId) -> bool {
+        self.wars.iter().any(|war| war.side_of(country).is_some())
+    }
+
+    pub fn alliance_entries(&self) -> impl Iterator<Item = (CountryId, CountryId)> + '_ {
+        self.alliances.iter().copied()
+    }
+
+    pub fn opinion_entries(&self) -> impl Iterator<Item = ((CountryId, CountryId), i32)> + '_ {
+        self.opinions.iter().map(|(key, value)| (*key, *value))
+    }
+
+    pub fn truce_entries(&self) -> impl Iterator<Item = ((CountryId, CountryId), u64)> + '_ {
+        self.truces.iter().map(|(key, value)| (*key, *value))
+    }
+
+    pub(crate) fn insert_alliance(&mut self, a: CountryId, b: CountryId) {
+        if a != b {
+            self.alliances.insert(pair(a, b));
+        }
+    }
+
+    pub(crate) fn insert_truce(&mut self, a: CountryId, b: CountryId, until: u64) {
+        self.truces.insert(pair(a, b), until);
+    }
+
+    pub(crate) fn insert_war(&mut self, war: War) {
+        self.wars.push(war);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PeaceDeal {
+    // Провинции цели, занятые предлагающим, которые отходят ему
+    pub demanded: Vec<ProvinceId>,
+    // Свои провинции, занятые целью, которые предлагающий уступает
+    pub ceded: Vec<ProvinceId>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiplomaticAction {
+    DeclareWar,
+    OfferPeace(PeaceDeal),
+    ProposeAlliance,
+    BreakAlliance,
+    SendGift,
+}
+
+// Единая точка входа для ИИ и интерфейса: действие проверяется и либо исполняется, либо отклоняется
+#[derive(Event, Clone, Debug)]
+pub struct DiplomaticRequest {
+    pub actor: CountryId,
+    pub target: CountryId,
+    pub action: DiplomaticAction,
+}
+
+// Запросы исполняются на ближайшем тике и датируются его днём. Интерфейс шлёт событие, которое
+// сюда перекладывается; ИИ кладёт запросы прямо в тике, иначе они ждали бы конца всех тиков кадра
+#[derive(Resource, Default)]
+pub struct PendingDiplomaticRequests {
+    requests: Vec<DiplomaticRequest>,
+}
+
+impl PendingDiplomaticRequests {
+    pub fn push(&mut self, request: DiplomaticRequest) {
+        self.requests.push(request);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiplomacyError {
+    SelfTarget,
+    UnknownCountry,
+    AlreadyAtWar,
+    Truce { until: u64 },
+    Allied,
+    NotAtWar,
+    NotAllied,
+    // Ни одна из сторон сделки не ведёт войну
+    NotWarLeader,
+    OpinionTooLow,
+    InvalidDemand(ProvinceId),
+    InvalidCession(ProvinceId),
+    // Цель не согласна: за ней остаются занятые земли, которые сделка не отдаёт
+    Refused,
+    // Цель не готова отдать столько провинций
+    DemandsTooHigh { acceptable: usize },
+    NotEnoughGold,
+}
+
+impl fmt::Display for DiplomacyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiplomacyError::SelfTarget => write!(f, "нельзя обратиться к самому себе"),
+            DiplomacyError::UnknownCountry => write!(f, "неизвестная страна"),
+            DiplomacyError::AlreadyAtWar => write!(f, "страны уже воюют"),
+            DiplomacyError::Truce { .. } => write!(f, "действует перемирие"),
+            DiplomacyError::Allied => write!(f, "страны в союзе"),
+            DiplomacyError::NotAtWar => write!(f, "страны не воюют"),
+            DiplomacyError::NotAllied => write!(f, "страны не в союзе"),
+            DiplomacyError::NotWarLeader => write!(f, "мир заключается только с лидером войны"),
+            DiplomacyError::OpinionTooLow => write!(f, "слишком плохое мнение"),
+            DiplomacyError::InvalidDemand(province) => write!(f, "провинцию {} нельзя потребовать", province.0),
+            DiplomacyError::InvalidCession(province) => write!(f, "провинцию {} нельзя уступить", province.0),
+            DiplomacyError::Refused => write!(f, "предложение отклонено"),
+            DiplomacyError::DemandsTooHigh { acceptable: 0 } => write!(f, "цель пока не готова уступить ни одной провинции"),
+            DiplomacyError::DemandsTooHigh { acceptable } => write!(f, "цель готова уступить не больше {} пров.", acceptable),
+            DiplomacyError::NotEnoughGold => write!(f, "не хватает денег"),
+        }
+    }
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct DiplomaticActionRejected {
+    pub request: DiplomaticRequest,
+    pub error: DiplomacyError,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct WarDeclared {
+    pub attacker: CountryId,
+    pub defender: CountryId,
+    // Союзники цели, вступившие в войну на её стороне
+    pub joined: Vec<CountryId>,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct PeaceSigned {
+    pub actor: CountryId,
+    pub target: CountryId,
+    pub deal: PeaceDeal,
+    pub war_ended: bool,
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct AllianceChanged {
+    pub a: CountryId,
+    pub b: CountryId,
+    pub allied: bool,
+}
+
+pub fn build(app: &mut App) {
+    app.init_resource::<Diplomacy>();
+    app.init_resource::<PendingDiplomaticRequests>();
+    app.add_event::<DiplomaticRequest>();
+    app.add_event::<DiplomaticActionRejected>();
+    app.add_event::<WarDeclared>();
+    app.add_event::<PeaceSigned>();
+    app.add_event::<AllianceChanged>();
+    app.add_systems(Startup, load_diplomacy.after(load_countries));
+    app.add_systems(Update, queue_diplomatic_requests.before(run_simulation));
+    app.add_systems(DailyTick, process_diplomatic_requests.after(apply_ai_decisions).before(apply_ownership_changes));
+    app.add_systems(MonthlyTick, decay_opinions);
+
+    panel::build(app);
+}
+
+// Параметры выделены в структуру: проверка и исполнение действия берут одно и то же
+struct DiplomacyContext<'a> {
+    diplomacy: &'a mut Diplomacy,
+    countries: &'a CountryRegistry,
+    treasuries: &'a mut Treasuries,
+    // Суммарная сила армий каждой страны, для условий мира
+    strengths: BTreeMap<CountryId, u32>,
+    today: u64,
+}
+
+fn queue_diplomatic_requests(
+    mut requests: EventReader<DiplomaticRequest>,
+    mut pending: ResMut<PendingDiplomaticRequests>,
+) {
+    if !requests.is_empty() {
+        pending.requests.extend(requests.read().cloned());
+    }
+}
+
+fn process_diplomatic_requests(
+    mut diplomacy: ResMut<Diplomacy>,
+    mut treasuries: ResMut<Treasuries>,
+    countries: Res<CountryRegistry>,
+    calendar: Res<GameCalendar>,
+    units: Query<&Unit>,
+    mut pending: ResMut<PendingDiplomaticRequests>,
+    mut outcomes: DiplomacyOutcomes,
+) {
+    // Без запросов ресурсы не трогаются, чтобы не помечать их изменёнными каждый тик
+    if pending.requests.is_empty() {
+        return;
+    }
+
+    let mut strengths = BTreeMap::new();
+    for unit in units.iter() {
+        *strengths.entry(unit.owner).or_insert(0) += unit.strength;
+    }
+
+    let mut context = DiplomacyContext {
+        diplomacy: &mut diplomacy,
+        countries: &countries,
+        treasuries: &mut treasuries,
+        strengths,
+        today: calendar.days_elapsed(),
+    };
+
+    for request in std::mem::take(&mut pending.requests) {
+        if let Err(error) = context.execute(&request, &mut outcomes) {
+            #[cfg(debug_assertions)]
+            println!("Дипломатическое действие {:?} отклонено: {}", request.action, error);
+
+            outcomes.rejected.send(DiplomaticActionRejected { request, error });
+        }
+    }
+}
+
+#[derive(bevy::ecs::system::SystemParam)]
+struct DiplomacyOutcomes<'w> {
+    rejected: EventWriter<'w, DiplomaticActionRejected>,
+    wars: EventWriter<'w, WarDeclared>,
+    peace: EventWriter<'w, PeaceSigned>,
+    alliances: EventWriter<'w, AllianceChanged>,
+    owners: EventWriter<'w, ChangeProvinceOwner>,
+    controllers: EventWriter<'w, ChangeProvinceController>,
+}
+
+impl DiplomacyContext<'_> {
+    fn execute(&mut self, request: &DiplomaticRequest, outcomes: &mut DiplomacyOutcomes) -> Result<(), DiplomacyError> {
+        let (actor, target) = (request.actor, request.target);
+        if actor == target {
+            return Err(DiplomacyError::SelfTarget);
+        }
+        if self.countries.get(actor).is_none() || self.countries.get(target).is_none() {
+            return Err(DiplomacyError::UnknownCountry);
+        }
+
+        match &request.action {
+            DiplomaticAction::DeclareWar => self.declare_war(actor, target, outcomes),
+            DiplomaticAction::OfferPeace(deal) => self.make_peace(actor, target, deal, outcomes),
+            DiplomaticAction::ProposeAlliance => {
+                if self.diplomacy.at_war(actor, target) {
+                    return Err(DiplomacyError::AlreadyAtWar);
+                }
+                if self.diplomacy.are_allied(actor, target) {
+                    return Err(DiplomacyError::Allied);
+                }
+                if self.diplomacy.opinion(target, actor) < ALLIANCE_MIN_OPINION {
+                    return Err(DiplomacyError::OpinionTooLow);
+                }
+
+                self.diplomacy.insert_alliance(actor, target);
+                self.diplomacy.change_opinion(actor, target, ALLIANCE_OPINION);
+                self.diplomacy.change_opinion(target, actor, ALLIANCE_OPINION);
+                outcomes.alliances.send(AllianceChanged { a: actor, b: target, allied: true });
+                Ok(())
+            }
+            DiplomaticAction::BreakAlliance => {
+                if !self.diplomacy.alliances.remove(&pair(actor, target)) {
+                    return Err(DiplomacyError::NotAllied);
+                }
+
+                self.diplomacy.change_opinion(target, actor, -ALLIANCE_OPINION * 2);
+                outcomes.alliances.send(AllianceChanged { a: actor, b: target, allied: false });
+                Ok(())
+            }
+            DiplomaticAction::SendGift => {
+                let treasury = self.treasuries.countries.entry(actor).or_default();
+                if treasury.gold < GIFT_GOLD {
+                    return Err(DiplomacyError::NotEnoughGold);
+                }
+
+                treasury.gold -= GIFT_GOLD;
+                self.treasuries.countries.entry(target).or_default().gold += GIFT_GOLD;
+                self.diplomacy.change_opinion(target, actor, GIFT_OPINION);
+                Ok(())
+            }
+        }
+    }
+
+    // Союзники цели вступают в войну, если сами не связаны с зачинщиком союзом или перемирием
+    fn declare_war(&mut self, actor: CountryId, target: CountryId, outcomes: &mut DiplomacyOutcomes) -> Result<(), DiplomacyError> {
+        if self.diplomacy.at_war(actor, target) {
+            return Err(DiplomacyError::AlreadyAtWar);
+        }
+        if self.diplomacy.are_allied(actor, target) {
+            return Err(DiplomacyError::Allied);
+        }
+        if let Some(until) = self.diplomacy.truce_until(actor, target, self.today) {
+            return Err(DiplomacyError::Truce { until });
+        }
+
+        let joined: Vec<CountryId> = self.diplomacy.allies(target)
+            .filter(|ally| {
+                *ally != actor
+                    && !self.diplomacy.are_allied(*ally, actor)
+                    && self.diplomacy.truce_until(*ally, actor, self.today).is_none()
+            })
+            .collect();
+
+        let mut defenders = BTreeSet::from([target]);
+        defenders.extend(joined.iter().copied());
+        for defender in defenders.iter() {
+            self.diplomacy.change_opinion(*defender, actor, WAR_DECLARED_OPINION);
+        }
+
+        self.diplomacy.insert_war(War {
+            attacker: actor,
+            defender: target,
+            attackers: BTreeSet::from([actor]),
+            defenders,
+            started: self.today,
+        });
+
+        #[cfg(debug_assertions)]
+        println!("Страна {:?} объявила войну {:?}, на стороне обороны также {:?}", actor, target, joined);
+
+        outcomes.wars.send(WarDeclared { attacker: actor, defender: target, joined });
+        Ok(())
+    }
+
+    fn make_peace(&mut self, actor: CountryId, target: CountryId, deal: &PeaceDeal, outcomes: &mut DiplomacyOutcomes) -> Result<(), DiplomacyError> {
+        let Some(index) = self.diplomacy.wars.iter().position(|war| war.enemies(actor).is_some_and(|e| e.contains(&target))) else {
+            return Err(DiplomacyError::NotAtWar);
+        };
+
+        // Мир заключают с лидером войны: двум союзникам лидеров неясно, кто из них выходит из войны
+        let war = &self.diplomacy.wars[index];
+        let war_ended = war.leads(actor) && war.leads(target);
+        if !war.leads(actor) && !war.leads(target) {
+            return Err(DiplomacyError::NotWarLeader);
+        }
+
+        for province in deal.demanded.iter() {
+            if self.countries.owner_of(*province) != Some(target) || self.countries.controller_of(*province) != Some(actor) {
+                return Err(DiplomacyError::InvalidDemand(*province));
+            }
+        }
+        for province in deal.ceded.iter() {
+            if self.countries.owner_of(*province) != Some(actor) || self.countries.controller_of(*province) != Some(target) {
+                return Err(DiplomacyError::InvalidCession(*province));
+            }
+        }
+
+        // Цель соглашается, только если получает всё, что уже заняла у предлагающего
+        let held_by_target = self.countries.get(actor).into_iter()
+            .flat_map(|country| country.owned.iter())
+            .filter(|province| self.countries.controller_of(**province) == Some(target));
+        for province in held_by_target {
+            if !deal.ceded.contains(province) {
+                return Err(DiplomacyError::Refused);
+            }
+        }
+
+        let target_provinces = self.countries.get(target).map_or(0, |country| country.owned.len());
+        let strength = |country: CountryId| self.strengths.get(&country).copied().unwrap_or(0);
+        let acceptable = self.diplomacy.peace_terms(actor, target, self.today, target_provinces, strength)
+            .map_or(0, |terms| terms.acceptable_demands());
+        if deal.demanded.len() > acceptable {
+            return Err(DiplomacyError::DemandsTooHigh { acceptable });
+        }
+
+        for province in deal.demanded.iter() {
+            outcomes.owners.send(ChangeProvinceOwner { province: *province, owner: Some(actor) });
+        }
+        for province in deal.ceded.iter() {
+            outcomes.owners.send(ChangeProvinceOwner { province: *province, owner: Some(target) });
+        }
+
+        // Остальные занятые друг у друга провинции возвращаются владельцам
+        for (owner, occupier) in [(actor, target), (target, actor)] {
+            let Some(country) = self.countries.get(owner) else {
+                continue;
+            };
+            for province in country.owned.iter() {
+                let transferred = deal.demanded.contains(province) || deal.ceded.contains(province);
+                if !transferred && self.countries.controller_of(*province) == Some(occupier) {
+                    outcomes.controllers.send(ChangeProvinceController { province: *province, controller: Some(owner) });
+                }
+            }
+        }
+
+        let mut truces = Vec::new();
+        if war_ended {
+            let war = self.diplomacy.wars.remove(index);
+            for a in war.attackers.iter() {
+                for b in war.defenders.iter() {
+                    truces.push((*a, *b));
+                }
+            }
+        } else {
+            // Сепаратный мир: из войны выходит тот, кто в ней не лидер
+            let war = &mut self.diplomacy.wars[index];
+            let leaving = if war.leads(target) { actor } else { target };
+            war.attackers.remove(&leaving);
+            war.defenders.remove(&leaving);
+            truces.push((actor, target));
+        }
+
+        for (a, b) in truces {
+            self.diplomacy.insert_truce(a, b, self.today + TRUCE_DAYS);
+        }
+        self.diplomacy.change_opinion(target, actor, PEACE_OPINION);
+        self.diplomacy.change_opinion(actor, target, PEACE_OPINION);
+
+        outcomes.peace.send(PeaceSigned { actor, target, deal: deal.clone(), war_ended });
+        Ok(())
+    }
+}
+
+fn decay_opinions(
+    mut diplomacy: ResMut<Diplomacy>,
+    calendar: Res<GameCalendar>,
+) {
+    let diplomacy = &mut *diplomacy;
+    let today = calendar.days_elapsed();
+
+    let wars = &diplomacy.wars;
+    diplomacy.opinions.retain(|(of, about), opinion| {
+        let at_war = wars.iter().any(|war| war.enemies(*of).is_some_and(|e| e.contains(about)));
+        if !at_war {
+            *opinion -= opinion.signum() * (*opinion).abs().min(OPINION_DECAY);
+        }
+        *opinion != 0
+    });
+
+    diplomacy.truces.retain(|_, until| *until > today);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::event::Events;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::prelude::World;
+
+    const AVR: CountryId = CountryId(0);
+    const COR: CountryId = CountryId(1);
+    const EAS: CountryId = CountryId(2);
+
+    fn terms(days_at_war: u64, actor_strength: u32, target_strength: u32) -> PeaceTerms {
+        PeaceTerms { days_at_war, target_provinces: 4, actor_strength, target_strength }
+    }
+
+    #[test]
+    fn nothing_is_ceded_right_after_declaration() {
+        assert_eq!(terms(0, 100_000, 0).acceptable_demands(), 0);
+    }
+
+    #[test]
+    fn whole_country_needs_long_war_and_double_strength() {
+        assert_eq!(terms(WAR_EXHAUSTION_DAYS, 20_000, 10_000).acceptable_demands(), 4);
+        assert!(terms(WAR_EXHAUSTION_DAYS, 15_000, 10_000).acceptable_demands() < 4);
+        assert!(terms(WAR_EXHAUSTION_DAYS / 2, 20_000, 10_000).acceptable_demands() < 4);
+    }
+
+    fn world() -> World {
+        let mut world = World::new();
+        let mut countries = CountryRegistry::default();
+        for tag in ["AVR", "COR", "EAS"] {
+            countries.insert(tag.to_string(), tag.to_string(), [0, 0, 0], None);
+        }
+        world.insert_resource(countries);
+        world.init_resource::<Diplomacy>();
+        world.init_resource::<Treasuries>();
+        world.init_resource::<Events<DiplomaticActionRejected>>();
+        world.init_resource::<Events<WarDeclared>>();
+        world.init_resource::<Events<PeaceSigned>>();
+        world.init_resource::<Events<AllianceChanged>>();
+        world.init_resource::<Events<ChangeProvinceOwner>>();
+        world.init_resource::<Events<ChangeProvinceController>>();
+        world
+    }
+
+    fn execute(world: &mut World, today: u64, actor: CountryId, target: CountryId, action: DiplomaticAction) -> Result<(), DiplomacyError> {
+        let request = DiplomaticRequest { actor, target, action };
+        world.run_system_once(move |mut diplomacy: ResMut<Diplomacy>,
+                                    mut treasuries: ResMut<Treasuries>,
+                                    countries: Res<CountryRegistry>,
+                                    mut outcomes: DiplomacyOutcomes| {
+            let mut context = DiplomacyContext {
+                diplomacy: &mut diplomacy,
+                countries: &countries,
+                treasuries: &mut treasuries,
+                strengths: BTreeMap::new(),
+                today,
+            };
+            context.execute(&request, &mut outcomes)
+        }).expect("система дипломатии не запустилась")
+    }
+
+    fn peace() -> DiplomaticAction {
+        DiplomaticAction::OfferPeace(PeaceDeal { demanded: Vec::new(), ceded: Vec::new() })
+    }
+
+    fn last_peace(world: &World) -> PeaceSigned {
+        world.resource::<Events<PeaceSigned>>().iter_current_update_events().last().cloned().expect("мир не подписан")
+    }
+
+    #[test]
+    fn war_is_rejected_during_truce() {
+        let mut world = world();
+        world.resource_mut::<Diplomacy>().insert_truce(AVR, COR, 100);
+
+        assert_eq!(execute(&mut world, 10, AVR, COR, DiplomaticAction::DeclareWar), Err(DiplomacyError::Truce { until: 100 }));
+        assert!(world.resource::<Diplomacy>().wars().is_empty());
+
+        assert_eq!(execute(&mut world, 100, AVR, COR, DiplomaticAction::DeclareWar), Ok(()));
+    }
+
+    #[test]
+    fn allies_join_the_defender() {
+        let mut world = world();
+        world.resource_mut::<Diplomacy>().insert_alliance(COR, EAS);
+
+        assert_eq!(execute(&mut world, 0, AVR, COR, DiplomaticAction::DeclareWar), Ok(()));
+
+        let diplomacy = world.resource::<Diplomacy>();
+        assert!(diplomacy.at_war(AVR, COR));
+        assert!(diplomacy.at_war(AVR, EAS));
+        assert!(!diplomacy.at_war(COR, EAS));
+
+        let declared = world.resource::<Events<WarDeclared>>().iter_current_update_events().last().cloned().expect("война не объявлена");
+        assert_eq!(declared.joined, vec![EAS]);
+    }
+
+    #[test]
+    fn peace_between_leaders_ends_the_war() {
+        let mut world = world();
+        world.resource_mut::<Diplomacy>().insert_alliance(COR, EAS);
+        execute(&mut world, 0, AVR, COR, DiplomaticAction::DeclareWar).unwrap();
+
+        assert_eq!(execute(&mut world, 50, AVR, COR, peace()), Ok(()));
+        assert!(last_peace(&world).war_ended);
+
+        let diplomacy = world.resource::<Diplomacy>();
+        assert!(diplomacy.wars().is_empty());
+        assert_eq!(diplomacy.truce_until(AVR, COR, 50), Some(50 + TRUCE_DAYS));
+        assert_eq!(diplomacy.truce_until(AVR, EAS, 50), Some(50 + TRUCE_DAYS));
+    }
+
+    #[test]
+    fn separate_peace_removes_the_leaving_country() {
+        let mut world = world();
+        world.resource_mut::<Diplomacy>().insert_alliance(COR, EAS);
+        execute(&mut world, 0, AVR, COR, DiplomaticAction::DeclareWar).unwrap();
+
+        assert_eq!(execute(&mut world, 50, AVR, EAS, peace()), Ok(()));
+        assert!(!last_peace(&world).war_ended);
+
+        let diplomacy = world.resource::<Diplomacy>();
+        assert!(diplomacy.at_war(AVR, COR));
+        assert!(!diplomacy.at_war(AVR, EAS));
+        assert_eq!(diplomacy.wars()[0].side_of(EAS), None);
+        assert_eq!(diplomacy.truce_until(AVR, EAS, 50), Some(50 + TRUCE_DAYS));
+        assert_eq!(diplomacy.truce_until(AVR, COR, 50), None);
+    }
+}
@@ -0,0 +1,249 @@
+use crate::core::country::{CountryId, CountryRegistry, PlayerCountry};
+use crate::core::diplomacy::{Diplomacy, DiplomaticAction, DiplomaticActionRejected, DiplomaticRequest, PeaceDeal, PeaceTerms, GIFT_GOLD};
+use crate::core::map::province::selection::SelectedProvince;
+use crate::core::map::province::ProvinceId;
+use crate::core::simulation::calendar::GameCalendar;
+use crate::core::simulation::run_simulation;
+use crate::core::unit::Unit;
+use bevy::color::Color;
+use bevy::prelude::{default, on_event, resource_changed, AlignItems, Alpha, App, BackgroundColor, BuildChildren, Button, ChildBuild, Changed, Commands, Component, Condition, EventReader, EventWriter, FlexDirection, Interaction, IntoSystemConfigs, Node, PositionType, Query, Res, ResMut, Resource, Single, Startup, Text, TextFont, UiRect, Update, Val, Visibility, With};
+use chrono::Days;
+use std::fmt::Write;
+
+#[derive(Component)]
+struct DiplomacyPanel;
+
+#[derive(Component)]
+struct DiplomacyText;
+
+#[derive(Component, Clone, Copy)]
+enum DiplomacyButton {
+    DeclareWar,
+    OfferPeace,
+    ProposeAlliance,
+    BreakAlliance,
+    SendGift,
+}
+
+// Всё, что нужно для мира на условиях, которые цель примет: те же правила, что и при исполнении запроса
+#[derive(bevy::ecs::system::SystemParam)]
+struct PeaceOffer<'w, 's> {
+    diplomacy: Res<'w, Diplomacy>,
+    countries: Res<'w, CountryRegistry>,
+    calendar: Res<'w, GameCalendar>,
+    units: Query<'w, 's, &'static Unit>,
+}
+
+impl PeaceOffer<'_, '_> {
+    fn terms(&self, actor: CountryId, target: CountryId) -> Option<PeaceTerms> {
+        let provinces = self.countries.get(target).map_or(0, |country| country.owned.len());
+        let strength = |country: CountryId| self.units.iter().filter(|unit| unit.owner == country).map(|unit| unit.strength).sum();
+        self.diplomacy.peace_terms(actor, target, self.calendar.days_elapsed(), provinces, strength)
+    }
+
+    // Мир на условиях текущего положения: каждый забирает занятые им земли противника,
+    // но не больше, чем цель готова отдать
+    fn deal(&self, actor: CountryId, target: CountryId) -> PeaceDeal {
+        let mut demanded = held_by(&self.countries, target, actor);
+        demanded.truncate(self.terms(actor, target).map_or(0, |terms| terms.acceptable_demands()));
+
+        PeaceDeal {
+            demanded,
+            ceded: held_by(&self.countries, actor, target),
+        }
+    }
+}
+
+// Страна, с которой сейчас ведутся переговоры, и причина последнего отказа
+#[derive(Resource, Default)]
+struct DiplomacyPanelState {
+    target: Option<CountryId>,
+    rejection: Option<String>,
+}
+
+pub fn build(app: &mut App) {
+    app.init_resource::<DiplomacyPanelState>();
+    app.add_systems(Startup, init);
+    app.add_systems(Update, (
+        update_target,
+        press_buttons,
+        read_rejections,
+        update_panel.run_if(
+            resource_changed::<DiplomacyPanelState>
+                .or(resource_changed::<Diplomacy>)
+                .or(resource_changed::<GameCalendar>)
+                .or(on_event::<DiplomaticActionRejected>),
+        ),
+    ).chain().after(run_simulation));
+}
+
+fn init(mut commands: Commands) {
+    let buttons = [
+        (DiplomacyButton::DeclareWar, "Объявить войну".to_string()),
+        (DiplomacyButton::OfferPeace, "Предложить мир".to_string()),
+        (DiplomacyButton::ProposeAlliance, "Предложить союз".to_string()),
+        (DiplomacyButton::BreakAlliance, "Разорвать союз".to_string()),
+        (DiplomacyButton::SendGift, format!("Подарок ({:.0})", GIFT_GOLD)),
+    ];
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(8.0),
+                bottom: Val::Px(8.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                ..default()
+            },
+            BackgroundColor(Color::BLACK.with_alpha(0.55)),
+            Interaction::default(),
+            Visibility::Hidden,
+            DiplomacyPanel,
+        ))
+        .with_children(|p| {
+            p.spawn((
+                Text::default(),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                DiplomacyText,
+            ));
+
+            for (button, label) in buttons {
+                p.spawn((
+                    Button,
+                    Node {
+                        padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(Color::WHITE.with_alpha(0.15)),
+                    button,
+                ))
+                .with_children(|b| {
+                    b.spawn((
+                        Text::new(label),
+                        TextFont {
+                            font_size: 14.0,
+                            ..default()
+                        },
+                    ));
+                });
+            }
+        });
+}
+
+// Переговоры ведутся с владельцем выбранной чужой провинции
+fn update_target(
+    selected: Res<SelectedProvince>,
+    player: Res<PlayerCountry>,
+    countries: Res<CountryRegistry>,
+    mut state: ResMut<DiplomacyPanelState>,
+) {
+    let target = selected.0
+        .and_then(|province| countries.owner_of(province))
+        .filter(|owner| player.0.is_some_and(|player| player != *owner));
+
+    if state.target != target {
+        state.target = target;
+        state.rejection = None;
+    }
+}
+
+fn press_buttons(
+    player: Res<PlayerCountry>,
+    peace: PeaceOffer,
+    state: Res<DiplomacyPanelState>,
+    buttons: Query<(&Interaction, &DiplomacyButton), Changed<Interaction>>,
+    mut requests: EventWriter<DiplomaticRequest>,
+) {
+    let (Some(actor), Some(target)) = (player.0, state.target) else {
+        return;
+    };
+
+    for (interaction, button) in buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        let action = match button {
+            DiplomacyButton::DeclareWar => DiplomaticAction::DeclareWar,
+            DiplomacyButton::OfferPeace => DiplomaticAction::OfferPeace(peace.deal(actor, target)),
+            DiplomacyButton::ProposeAlliance => DiplomaticAction::ProposeAlliance,
+            DiplomacyButton::BreakAlliance => DiplomaticAction::BreakAlliance,
+            DiplomacyButton::SendGift => DiplomaticAction::SendGift,
+        };
+
+        requests.send(DiplomaticRequest { actor, target, action });
+    }
+}
+
+fn held_by(countries: &CountryRegistry, owner: CountryId, controller: CountryId) -> Vec<ProvinceId> {
+    countries.get(owner)
+        .map(|country| {
+            country.owned.iter()
+                .filter(|province| countries.controller_of(**province) == Some(controller))
+                .copied()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn read_rejections(
+    player: Res<PlayerCountry>,
+    mut state: ResMut<DiplomacyPanelState>,
+    mut rejections: EventReader<DiplomaticActionRejected>,
+) {
+    for rejection in rejections.read() {
+        if player.0 == Some(rejection.request.actor) {
+            state.rejection = Some(rejection.error.to_string());
+        }
+    }
+}
+
+fn update_panel(
+    state: Res<DiplomacyPanelState>,
+    player: Res<PlayerCountry>,
+    peace: PeaceOffer,
+    mut panel: Single<&mut Visibility, With<DiplomacyPanel>>,
+    mut text: Single<&mut Text, With<DiplomacyText>>,
+) {
+    let (Some(actor), Some(target)) = (player.0, state.target) else {
+        **panel = Visibility::Hidden;
+        return;
+    };
+    let PeaceOffer { diplomacy, countries, calendar, .. } = &peace;
+    let Some(country) = countries.get(target) else {
+        **panel = Visibility::Hidden;
+        return;
+    };
+    **panel = Visibility::Inherited;
+
+    let today = calendar.days_elapsed();
+    let status = if diplomacy.at_war(actor, target) {
+        "война".to_string()
+    } else if diplomacy.are_allied(actor, target) {
+        "союз".to_string()
+    } else if let Some(until) = diplomacy.truce_until(actor, target, today) {
+        let end = calendar.date() + Days::new(until - today);
+        format!("перемирие до {}", end.format("%d.%m.%Y"))
+    } else {
+        "мир".to_string()
+    };
+
+    let mut content = format!("{} ({})", country.name, country.tag);
+    let _ = write!(content, "\nОтношения: {}", status);
+    let _ = write!(content, "\nМнение о нас: {}, наше мнение: {}", diplomacy.opinion(target, actor), diplomacy.opinion(actor, target));
+    if let Some(terms) = peace.terms(actor, target) {
+        let occupied = held_by(countries, target, actor).len();
+        let _ = write!(content, "\nГотовы уступить провинций: {} из занятых {}", terms.acceptable_demands().min(occupied), occupied);
+    }
+    if let Some(rejection) = state.rejection.as_ref() {
+        let _ = write!(content, "\nОтказ: {}", rejection);
+    }
+
+    text.0 = content;
+}
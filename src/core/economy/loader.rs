@@ -0,0 +1,118 @@
+use crate::core::economy::{BuildingDefinition, Economy, EconomyModifier, ProvinceEconomy, ProvinceOutput};
+use crate::core::map::province::{ProvinceId, ProvinceRegistry, TerrainKind};
+use bevy::prelude::{Commands, Res};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+
+#[derive(Deserialize, Default)]
+struct EconomyDefinitions {
+    #[serde(default)]
+    terrain: HashMap<TerrainKind, EconomyModifier>,
+    #[serde(default)]
+    buildings: BTreeMap<String, BuildingEntry>,
+    #[serde(default)]
+    provinces: BTreeMap<u16, ProvinceEntry>,
+}
+
+#[derive(Deserialize)]
+struct BuildingEntry {
+    name: String,
+    #[serde(default)]
+    modifier: EconomyModifier,
+}
+
+#[derive(Deserialize)]
+struct ProvinceEntry {
+    #[serde(default)]
+    tax: f32,
+    #[serde(default)]
+    production: f32,
+    #[serde(default)]
+    manpower: u32,
+    #[serde(default)]
+    buildings: Vec<String>,
+}
+
+pub fn load_economy(
+    mut commands: Commands,
+    provinces: Res<ProvinceRegistry>,
+) {
+    let definitions = load_definitions("common/data/economy.ron");
+    let economy = build_economy(definitions, &provinces);
+
+    #[cfg(debug_assertions)]
+    println!("Загружена экономика {} провинций", economy.provinces.len());
+
+    commands.insert_resource(economy);
+}
+
+fn build_economy(definitions: EconomyDefinitions, provinces: &ProvinceRegistry) -> Economy {
+    let mut economy = Economy {
+        terrain: definitions.terrain,
+        ..Economy::default()
+    };
+
+    for (id, entry) in definitions.buildings {
+        economy.buildings.insert(id, BuildingDefinition { name: entry.name, modifier: entry.modifier });
+    }
+
+    for id in definitions.provinces.keys() {
+        if provinces.get(ProvinceId(*id)).is_none_or(|province| province.is_sea) {
+            eprintln!("Экономика задана для неизвестной или морской провинции {}", id);
+        }
+    }
+
+    for province in provinces.iter().filter(|province| !province.is_sea) {
+        let Some(entry) = definitions.provinces.get(&province.id.0) else {
+            eprintln!("Для провинции {} не задана экономика", province.id.0);
+            continue;
+        };
+
+        let terrain = province.terrain.kind();
+        let mut modifier = economy.terrain_modifier(terrain);
+
+        let mut buildings = Vec::new();
+        for building in entry.buildings.iter() {
+            match economy.buildings.get(building) {
+                Some(definition) => {
+                    modifier.add(&definition.modifier);
+                    buildings.push(building.clone());
+                }
+                None => eprintln!("В провинции {} указана неизвестная постройка {}", province.id.0, building),
+            }
+        }
+
+        let mut province_economy = ProvinceEconomy {
+            base: ProvinceOutput {
+                tax: entry.tax,
+                production: entry.production,
+                manpower: entry.manpower,
+            },
+            terrain,
+            buildings,
+            modifier,
+            output: ProvinceOutput::default(),
+        };
+        province_economy.recalculate();
+
+        economy.provinces.insert(province.id, province_economy);
+    }
+
+    economy
+}
+
+fn load_definitions(path: &str) -> EconomyDefinitions {
+    let Ok(content) = fs::read_to_string(path) else {
+        eprintln!("Файл экономики {} не найден, провинции не приносят дохода", path);
+        return EconomyDefinitions::default();
+    };
+
+    match ron::from_str(&content) {
+        Ok(definitions) => definitions,
+        Err(e) => {
+            eprintln!("Не удалось разобрать {}: {}", path, e);
+            EconomyDefinitions::default()
+        }
+    }
+}
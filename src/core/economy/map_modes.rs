@@ -0,0 +1,54 @@
+use crate::core::economy::{Economy, ProvinceOutput};
+use crate::core::map::map_mode::{Gradient, MapMode, MapModeColoring, RegisterMapMode};
+use crate::core::map::province::Province;
+use bevy::color::Color;
+use bevy::prelude::{App, World};
+
+pub const TAX: &str = "tax";
+pub const PRODUCTION: &str = "production";
+pub const MANPOWER: &str = "manpower";
+
+fn output_value(world: &World, province: &Province, value: fn(&ProvinceOutput) -> f32) -> Option<f32> {
+    world.get_resource::<Economy>()?.province(province.id).map(|economy| value(&economy.output))
+}
+
+pub fn register(app: &mut App) {
+    app.register_map_mode(MapMode {
+        id: TAX.to_string(),
+        name: "Налоги".to_string(),
+        hotkey: Some(4),
+        coloring: MapModeColoring::numeric(
+            |world, province| output_value(world, province, |output| output.tax),
+            Gradient {
+                low: Color::srgb(0.35, 0.3, 0.2),
+                high: Color::srgb(0.95, 0.8, 0.2),
+            },
+        ),
+    });
+
+    app.register_map_mode(MapMode {
+        id: PRODUCTION.to_string(),
+        name: "Производство".to_string(),
+        hotkey: Some(5),
+        coloring: MapModeColoring::numeric(
+            |world, province| output_value(world, province, |output| output.production),
+            Gradient {
+                low: Color::srgb(0.25, 0.25, 0.3),
+                high: Color::srgb(0.85, 0.45, 0.15),
+            },
+        ),
+    });
+
+    app.register_map_mode(MapMode {
+        id: MANPOWER.to_string(),
+        name: "Рекруты".to_string(),
+        hotkey: Some(6),
+        coloring: MapModeColoring::numeric(
+            |world, province| output_value(world, province, |output| output.manpower as f32),
+            Gradient {
+                low: Color::srgb(0.25, 0.25, 0.3),
+                high: Color::srgb(0.8, 0.2, 0.2),
+            },
+        ),
+    });
+}
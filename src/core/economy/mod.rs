@@ -0,0 +1,183 @@
+pub(crate) mod loader;
+mod map_modes;
+
+use crate::core::country::{CountryId, CountryRegistry};
+use crate::core::economy::loader::load_economy;
+use crate::core::map::province::loader::load_provinces;
+use crate::core::map::province::tooltip::RegisterTooltipSection;
+use crate::core::map::province::{Province, ProvinceId, TerrainKind};
+use crate::core::simulation::MonthlyTick;
+use bevy::prelude::{App, IntoSystemConfigs, Res, ResMut, Resource, Startup, World};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fmt::Write;
+
+// Цена найма одного солдата в дукатах
+pub const RECRUIT_COST: f64 = 0.01;
+
+// Запас рекрутов копится не дольше этого числа месяцев прироста
+const MANPOWER_CAP_MONTHS: u32 = 120;
+
+// Надбавки в процентах к базовым значениям провинции
+#[derive(Deserialize, Clone, Copy, Debug, Default)]
+#[serde(default)]
+pub struct EconomyModifier {
+    pub tax: i32,
+    pub production: i32,
+    pub manpower: i32,
+}
+
+impl fmt::Display for EconomyModifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "налоги {:+}%, производство {:+}%, рекруты {:+}%", self.tax, self.production, self.manpower)
+    }
+}
+
+impl EconomyModifier {
+    fn add(&mut self, other: &EconomyModifier) {
+        self.tax += other.tax;
+        self.production += other.production;
+        self.manpower += other.manpower;
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BuildingDefinition {
+    pub name: String,
+    pub modifier: EconomyModifier,
+}
+
+// Месячный доход провинции в дукатах и прирост рекрутов
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProvinceOutput {
+    pub tax: f32,
+    pub production: f32,
+    pub manpower: u32,
+}
+
+impl ProvinceOutput {
+    pub fn income(&self) -> f32 {
+        self.tax + self.production
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ProvinceEconomy {
+    pub base: ProvinceOutput,
+    pub terrain: TerrainKind,
+    pub buildings: Vec<String>,
+    // Сумма модификаторов рельефа и построек
+    pub modifier: EconomyModifier,
+    pub output: ProvinceOutput,
+}
+
+impl ProvinceEconomy {
+    fn recalculate(&mut self) {
+        let apply = |value: f32, percent: i32| (value * (100 + percent) as f32 / 100.0).max(0.0);
+
+        self.output = ProvinceOutput {
+            tax: apply(self.base.tax, self.modifier.tax),
+            production: apply(self.base.production, self.modifier.production),
+            manpower: apply(self.base.manpower as f32, self.modifier.manpower).round() as u32,
+        };
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Economy {
+    terrain: HashMap<TerrainKind, EconomyModifier>,
+    buildings: BTreeMap<String, BuildingDefinition>,
+    provinces: HashMap<ProvinceId, ProvinceEconomy>,
+}
+
+impl Economy {
+    pub fn province(&self, id: ProvinceId) -> Option<&ProvinceEconomy> {
+        self.provinces.get(&id)
+    }
+
+    pub fn terrain_modifier(&self, terrain: TerrainKind) -> EconomyModifier {
+        self.terrain.get(&terrain).copied().unwrap_or_default()
+    }
+
+    pub fn building(&self, id: &str) -> Option<&BuildingDefinition> {
+        self.buildings.get(id)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Treasury {
+    pub gold: f64,
+    pub manpower: u32,
+}
+
+#[derive(Resource, Default)]
+pub struct Treasuries {
+    pub countries: BTreeMap<CountryId, Treasury>,
+}
+
+impl Treasuries {
+    pub fn get(&self, country: CountryId) -> Treasury {
+        self.countries.get(&country).copied().unwrap_or_default()
+    }
+}
+
+pub fn build(app: &mut App) {
+    app.init_resource::<Economy>();
+    app.init_resource::<Treasuries>();
+    app.add_systems(Startup, load_economy.after(load_provinces));
+    app.add_systems(MonthlyTick, collect_income);
+
+    map_modes::register(app);
+    app.register_tooltip_section(economy_tooltip);
+}
+
+// Разбор дохода провинции в подсказке: что дала база, а что — рельеф и каждая постройка
+fn economy_tooltip(world: &World, province: &Province) -> Option<String> {
+    let economy = world.resource::<Economy>();
+    let province = economy.province(province.id)?;
+    let (base, output) = (&province.base, &province.output);
+
+    let mut content = format!(
+        "Доход: налоги {:.1}, производство {:.1}, рекруты {}",
+        output.tax, output.production, output.manpower,
+    );
+    let _ = write!(content, "\n  база: налоги {:.1}, производство {:.1}, рекруты {}", base.tax, base.production, base.manpower);
+    let _ = write!(content, "\n  {}: {}", province.terrain.name(), economy.terrain_modifier(province.terrain));
+    for id in province.buildings.iter() {
+        if let Some(building) = economy.building(id) {
+            let _ = write!(content, "\n  {}: {}", building.name, building.modifier);
+        }
+    }
+
+    Some(content)
+}
+
+// Оккупированная провинция не платит ни владельцу, ни оккупанту
+fn collect_income(
+    economy: Res<Economy>,
+    countries: Res<CountryRegistry>,
+    mut treasuries: ResMut<Treasuries>,
+) {
+    for country in countries.iter() {
+        let mut income = 0.0;
+        let mut manpower = 0;
+        let mut manpower_cap = 0;
+
+        for province in country.owned.iter() {
+            let Some(output) = economy.province(*province).map(|p| p.output) else {
+                continue;
+            };
+
+            manpower_cap += output.manpower * MANPOWER_CAP_MONTHS;
+            if countries.controller_of(*province) == Some(country.id) {
+                income += output.income() as f64;
+                manpower += output.manpower;
+            }
+        }
+
+        let treasury = treasuries.countries.entry(country.id).or_default();
+        treasury.gold += income;
+        treasury.manpower = (treasury.manpower + manpower).min(manpower_cap.max(treasury.manpower));
+    }
+}
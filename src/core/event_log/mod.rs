@@ -0,0 +1,302 @@
+use crate::core::country::{CountryId, CountryRegistry, PlayerCountry, ProvinceControllerChanged, ProvinceOwnerChanged};
+use crate::core::diplomacy::{AllianceChanged, PeaceSigned, WarDeclared};
+use crate::core::map::province::{ProvinceId, ProvinceMap, ProvinceRegistry};
+use crate::core::save::{process_save_requests, GameLoaded, GameSaved};
+use crate::core::simulation::calendar::GameCalendar;
+use crate::core::simulation::run_simulation;
+use crate::core::unit::battle::{BattleEnded, BattleStarted};
+use crate::core::unit::{Unit, UnitArrived};
+use bevy::color::Color;
+use bevy::prelude::{default, resource_changed, Alpha, App, BackgroundColor, BuildChildren, ChildBuild, Commands, Component, EventReader, IntoSystemConfigs, Node, PositionType, Query, Res, ResMut, Resource, Single, Startup, Text, TextFont, UiRect, Update, Val, Visibility, With};
+use std::collections::{HashSet, VecDeque};
+use std::fmt::Write;
+use std::path::Path;
+
+// Сколько последних записей показывается в журнале
+const LOG_CAPACITY: usize = 8;
+
+// Журнал над миникартой
+const LOG_BOTTOM: f32 = 148.0;
+
+// Журнал событий, касающихся игрока; без игрока в журнал попадает всё
+#[derive(Resource, Default)]
+pub struct EventLog {
+    entries: VecDeque<String>,
+}
+
+impl EventLog {
+    pub fn push(&mut self, calendar: &GameCalendar, text: String) {
+        if self.entries.len() == LOG_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(format!("{}  {}", calendar.format(), text));
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &String> {
+        self.entries.iter()
+    }
+}
+
+#[derive(Component)]
+struct EventLogPanel;
+
+#[derive(Component)]
+struct EventLogText;
+
+pub fn build(app: &mut App) {
+    app.init_resource::<EventLog>();
+    app.add_systems(Startup, init);
+    app.add_systems(Update, (
+        log_saves,
+        log_diplomacy.after(run_simulation),
+        log_ownership_changes.after(run_simulation),
+        log_unit_arrivals,
+        log_battles,
+        update_log_text.run_if(resource_changed::<EventLog>),
+    ).chain().after(process_save_requests));
+}
+
+fn init(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(8.0),
+                bottom: Val::Px(LOG_BOTTOM),
+                padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                ..default()
+            },
+            BackgroundColor(Color::BLACK.with_alpha(0.55)),
+            Visibility::Hidden,
+            EventLogPanel,
+        ))
+        .with_children(|p| {
+            p.spawn((
+                Text::default(),
+                TextFont {
+                    font_size: 13.0,
+                    ..default()
+                },
+                EventLogText,
+            ));
+        });
+}
+
+// Всё, что нужно для записи в журнал: имена стран и провинций, игрок и текущая дата
+#[derive(bevy::ecs::system::SystemParam)]
+struct LogWriter<'w> {
+    calendar: Res<'w, GameCalendar>,
+    player: Res<'w, PlayerCountry>,
+    countries: Res<'w, CountryRegistry>,
+    provinces: Res<'w, ProvinceRegistry>,
+    log: ResMut<'w, EventLog>,
+}
+
+impl LogWriter<'_> {
+    fn country(&self, id: Option<CountryId>) -> String {
+        id.and_then(|id| self.countries.get(id)).map(|c| c.name.clone()).unwrap_or_else(|| "никто".to_string())
+    }
+
+    fn province(&self, id: ProvinceId) -> String {
+        self.provinces.get(id).map(|p| p.name.clone()).unwrap_or_else(|| "?".to_string())
+    }
+
+    fn involves_player(&self, countries: &[Option<CountryId>]) -> bool {
+        self.player.0.is_none_or(|player| countries.contains(&Some(player)))
+    }
+
+    fn push(&mut self, text: String) {
+        self.log.push(&self.calendar, text);
+    }
+}
+
+fn log_ownership_changes(
+    mut loaded: EventReader<GameLoaded>,
+    mut owner_changes: EventReader<ProvinceOwnerChanged>,
+    mut controller_changes: EventReader<ProvinceControllerChanged>,
+    mut writer: LogWriter,
+) {
+    // После загрузки изменения владельцев описывают разницу с прежней партией, а не события игры
+    if let Some(loaded) = loaded.read().last() {
+        owner_changes.clear();
+        controller_changes.clear();
+        writer.log.clear();
+        writer.push(format!("Загружена игра {}", save_name(&loaded.path)));
+        return;
+    }
+
+    let mut transferred = HashSet::new();
+    for change in owner_changes.read() {
+        transferred.insert(change.province);
+        if writer.involves_player(&[change.previous, change.owner]) {
+            let text = format!(
+                "{} переходит от {} к {}",
+                writer.province(change.province),
+                writer.country(change.previous),
+                writer.country(change.owner),
+            );
+            writer.push(text);
+        }
+    }
+
+    // Контроль переданной провинции меняется вместе с владельцем, отдельная запись не нужна
+    for change in controller_changes.read().filter(|change| !transferred.contains(&change.province)) {
+        let owner = writer.countries.owner_of(change.province);
+        if !writer.involves_player(&[change.previous, change.controller, owner]) {
+            continue;
+        }
+
+        let text = if change.controller == owner {
+            format!("{} освобождена от {}", writer.province(change.province), writer.country(change.previous))
+        } else {
+            format!("{} занята: {}", writer.province(change.province), writer.country(change.controller))
+        };
+        writer.push(text);
+    }
+}
+
+fn log_diplomacy(
+    mut wars: EventReader<WarDeclared>,
+    mut peace: EventReader<PeaceSigned>,
+    mut alliances: EventReader<AllianceChanged>,
+    mut writer: LogWriter,
+) {
+    for war in wars.read() {
+        let mut involved = vec![Some(war.attacker), Some(war.defender)];
+        involved.extend(war.joined.iter().map(|ally| Some(*ally)));
+        if !writer.involves_player(&involved) {
+            continue;
+        }
+
+        let mut text = format!("{} объявляет войну {}", writer.country(Some(war.attacker)), writer.country(Some(war.defender)));
+        if !war.joined.is_empty() {
+            let joined: Vec<String> = war.joined.iter().map(|ally| writer.country(Some(*ally))).collect();
+            let _ = write!(text, ", на её стороне: {}", joined.join(", "));
+        }
+        writer.push(text);
+    }
+
+    for peace in peace.read() {
+        if !writer.involves_player(&[Some(peace.actor), Some(peace.target)]) {
+            continue;
+        }
+
+        let (actor, target) = (writer.country(Some(peace.actor)), writer.country(Some(peace.target)));
+        let mut text = if peace.war_ended {
+            format!("Мир между {} и {}, война окончена", actor, target)
+        } else {
+            format!("Сепаратный мир между {} и {}", actor, target)
+        };
+        for (receiver, provinces) in [(&actor, &peace.deal.demanded), (&target, &peace.deal.ceded)] {
+            if !provinces.is_empty() {
+                let names: Vec<String> = provinces.iter().map(|province| writer.province(*province)).collect();
+                let _ = write!(text, "; {} получает {}", receiver, names.join(", "));
+            }
+        }
+        writer.push(text);
+    }
+
+    for change in alliances.read() {
+        if !writer.involves_player(&[Some(change.a), Some(change.b)]) {
+            continue;
+        }
+
+        let (a, b) = (writer.country(Some(change.a)), writer.country(Some(change.b)));
+        let text = if change.allied {
+            format!("Союз: {} и {}", a, b)
+        } else {
+            format!("{} разрывает союз с {}", a, b)
+        };
+        writer.push(text);
+    }
+}
+
+fn log_saves(
+    mut saved: EventReader<GameSaved>,
+    mut writer: LogWriter,
+) {
+    for saved in saved.read() {
+        writer.push(format!("Игра сохранена: {}", save_name(&saved.path)));
+    }
+}
+
+fn save_name(path: &Path) -> String {
+    path.file_stem().map(|name| name.to_string_lossy().into_owned()).unwrap_or_else(|| path.display().to_string())
+}
+
+// Прибытие отмечается только для армий игрока, чужие передвижения в журнал не попадают
+fn log_unit_arrivals(
+    map: Option<Res<ProvinceMap>>,
+    units: Query<&Unit>,
+    mut arrivals: EventReader<UnitArrived>,
+    mut writer: LogWriter,
+) {
+    let Some(player) = writer.player.0 else {
+        arrivals.clear();
+        return;
+    };
+
+    for arrival in arrivals.read() {
+        let Ok(unit) = units.get(arrival.unit) else {
+            continue;
+        };
+        if unit.owner != player {
+            continue;
+        }
+
+        let province = map.as_ref().and_then(|map| map.province_at(arrival.position.x, arrival.position.y));
+        let place = province.map(|province| writer.province(province)).unwrap_or_else(|| "?".to_string());
+        writer.push(format!("Армия ({}) прибыла: {}", unit.strength, place));
+    }
+}
+
+fn log_battles(
+    mut started: EventReader<BattleStarted>,
+    mut ended: EventReader<BattleEnded>,
+    mut writer: LogWriter,
+) {
+    for battle in started.read() {
+        if writer.involves_player(&[Some(battle.attacker), Some(battle.defender)]) {
+            let text = format!(
+                "Битва: {} атакует {} ({})",
+                writer.country(Some(battle.attacker)),
+                writer.country(Some(battle.defender)),
+                writer.province(battle.province),
+            );
+            writer.push(text);
+        }
+    }
+
+    for battle in ended.read() {
+        let report = &battle.report;
+        if !writer.involves_player(&[Some(report.attacker), Some(report.defender)]) {
+            continue;
+        }
+
+        let place = writer.province(report.province);
+        let text = match report.winner {
+            Some(winner) => format!(
+                "Битва ({}) окончена: победа {}, потери {} / {}",
+                place,
+                writer.country(Some(winner)),
+                report.attacker_losses(),
+                report.defender_losses(),
+            ),
+            None => format!("Битва ({}) прервана заключением мира", place),
+        };
+        writer.push(text);
+    }
+}
+
+fn update_log_text(
+    log: Res<EventLog>,
+    mut panel: Single<&mut Visibility, With<EventLogPanel>>,
+    mut text: Single<&mut Text, With<EventLogText>>,
+) {
+    **panel = if log.entries.is_empty() { Visibility::Hidden } else { Visibility::Inherited };
+    text.0 = log.entries().cloned().collect::<Vec<_>>().join("\n");
+}
@@ -0,0 +1,186 @@
+use crate::core::country::loader::load_countries;
+use crate::core::country::{apply_ownership_changes, CountryId, CountryRegistry, PlayerCountry};
+use crate::core::map::map_mode::LOOKUP_WIDTH;
+use crate::core::map::province::adjacency::{build_province_graph, ProvinceGraph};
+use crate::core::map::province::{ProvinceId, ProvinceRegistry};
+use crate::core::map::terrain::material::{province_visibility_image, TerrainMaterial, TerrainMaterialHandle};
+use crate::core::simulation::calendar::GameCalendar;
+use crate::core::simulation::DailyTick;
+use crate::core::unit::{Selected, Unit, UnitPosition};
+use bevy::prelude::{resource_changed, Added, App, Assets, Commands, Condition, DetectChangesMut, Entity, Has, Image, IntoSystemConfigs, Query, Res, ResMut, Resource, Startup, Update, Visibility};
+use std::collections::{BTreeMap, BTreeSet};
+
+// Значения в текстуре тумана, см. terrain.wgsl
+const FOG_UNEXPLORED: u8 = 0;
+const FOG_EXPLORED: u8 = 128;
+const FOG_VISIBLE: u8 = u8::MAX;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProvinceVisibility {
+    Unexplored,
+    Explored,
+    Visible,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CountryVisibility {
+    // Провинции, которые страна когда-либо видела; видимые сейчас входят сюда же
+    pub explored: BTreeSet<ProvinceId>,
+    pub visible: BTreeSet<ProvinceId>,
+}
+
+impl CountryVisibility {
+    pub fn get(&self, province: ProvinceId) -> ProvinceVisibility {
+        if self.visible.contains(&province) {
+            ProvinceVisibility::Visible
+        } else if self.explored.contains(&province) {
+            ProvinceVisibility::Explored
+        } else {
+            ProvinceVisibility::Unexplored
+        }
+    }
+}
+
+// Пересчитывается только на тике симуляции, отрисовка лишь читает готовый результат
+#[derive(Resource, Default)]
+pub struct FogOfWar {
+    pub countries: BTreeMap<CountryId, CountryVisibility>,
+}
+
+impl FogOfWar {
+    pub fn visibility(&self, country: CountryId, province: ProvinceId) -> ProvinceVisibility {
+        self.countries.get(&country)
+            .map(|visibility| visibility.get(province))
+            .unwrap_or(ProvinceVisibility::Unexplored)
+    }
+
+    pub fn is_visible(&self, country: CountryId, province: ProvinceId) -> bool {
+        self.visibility(country, province) == ProvinceVisibility::Visible
+    }
+}
+
+pub fn build(app: &mut App) {
+    app.init_resource::<FogOfWar>();
+    app.add_systems(Startup, update_fog_of_war.after(load_countries).after(build_province_graph));
+    app.add_systems(DailyTick, update_fog_of_war.after(apply_ownership_changes));
+    app.add_systems(Update, (
+        update_fog_texture.run_if(resource_changed::<FogOfWar>.or(resource_changed::<PlayerCountry>)),
+        // Армии переходят между провинциями только на дневном тике
+        hide_fogged_units.run_if(
+            resource_changed::<FogOfWar>
+                .or(resource_changed::<PlayerCountry>)
+                .or(resource_changed::<GameCalendar>)
+                .or(units_added),
+        ),
+    ));
+}
+
+// Страна видит свои и контролируемые провинции, провинции со своими армиями и всех их соседей
+pub fn update_fog_of_war(
+    countries: Res<CountryRegistry>,
+    graph: Res<ProvinceGraph>,
+    units: Query<(&Unit, &UnitPosition)>,
+    mut fog: ResMut<FogOfWar>,
+) {
+    let mut sources: BTreeMap<CountryId, BTreeSet<ProvinceId>> = BTreeMap::new();
+    for country in countries.iter() {
+        let provinces = sources.entry(country.id).or_default();
+        provinces.extend(country.owned.iter().copied());
+        provinces.extend(country.controlled.iter().copied());
+    }
+    for (unit, position) in units.iter() {
+        if let Some(province) = position.province {
+            sources.entry(unit.owner).or_default().insert(province);
+        }
+    }
+
+    let mut changed = false;
+    for (country, provinces) in sources {
+        let mut visible = provinces.clone();
+        for province in provinces {
+            visible.extend(graph.neighbours(province).iter().map(|adjacency| adjacency.neighbour));
+        }
+
+        let entry = fog.bypass_change_detection().countries.entry(country).or_default();
+        if entry.visible != visible || !visible.is_subset(&entry.explored) {
+            entry.explored.extend(visible.iter().copied());
+            entry.visible = visible;
+            changed = true;
+        }
+    }
+
+    if changed {
+        fog.set_changed();
+    }
+}
+
+fn update_fog_texture(
+    fog: Res<FogOfWar>,
+    player: Res<PlayerCountry>,
+    registry: Res<ProvinceRegistry>,
+    handle: Option<Res<TerrainMaterialHandle>>,
+    mut materials: ResMut<Assets<TerrainMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let Some(material) = handle.and_then(|handle| materials.get_mut(&handle.0)) else {
+        return;
+    };
+
+    // Без игрока туман не показывается: таблица из одного видимого значения
+    let Some(player) = player.0 else {
+        material.extension.province_visibility = images.add(province_visibility_image(1, 1, vec![FOG_VISIBLE]));
+        return;
+    };
+
+    let max_id = registry.iter().map(|p| p.id.0 as usize).max().unwrap_or(0);
+    let rows = max_id / LOOKUP_WIDTH + 1;
+
+    // Пиксели без провинции (id 0) остаются открытыми
+    let mut table = vec![FOG_VISIBLE; rows * LOOKUP_WIDTH];
+    for province in registry.iter() {
+        table[province.id.0 as usize] = match fog.visibility(player, province.id) {
+            ProvinceVisibility::Unexplored => FOG_UNEXPLORED,
+            ProvinceVisibility::Explored => FOG_EXPLORED,
+            ProvinceVisibility::Visible => FOG_VISIBLE,
+        };
+    }
+
+    material.extension.province_visibility = images.add(province_visibility_image(LOOKUP_WIDTH as u32, rows as u32, table));
+}
+
+// Видит ли игрок армию: свои видны всегда, чужие — только в видимых провинциях
+#[derive(bevy::ecs::system::SystemParam)]
+pub struct UnitVisibility<'w> {
+    fog: Res<'w, FogOfWar>,
+    player: Res<'w, PlayerCountry>,
+}
+
+impl UnitVisibility<'_> {
+    pub fn shows(&self, unit: &Unit, position: &UnitPosition) -> bool {
+        match self.player.0 {
+            None => true,
+            Some(player) => unit.owner == player || position.province.is_some_and(|province| self.fog.is_visible(player, province)),
+        }
+    }
+}
+
+fn units_added(units: Query<(), Added<Unit>>) -> bool {
+    !units.is_empty()
+}
+
+// Чужие армии вне видимых провинций не рисуются и снимаются с выделения
+fn hide_fogged_units(
+    mut commands: Commands,
+    visibility: UnitVisibility,
+    mut units: Query<(Entity, &Unit, &UnitPosition, &mut Visibility, Has<Selected>)>,
+) {
+    for (entity, unit, position, mut shown_as, selected) in units.iter_mut() {
+        let shown = visibility.shows(unit, position);
+
+        let target = if shown { Visibility::Inherited } else { Visibility::Hidden };
+        shown_as.set_if_neq(target);
+        if !shown && selected {
+            commands.entity(entity).remove::<Selected>();
+        }
+    }
+}
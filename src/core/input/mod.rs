@@ -0,0 +1,275 @@
+use crate::core::settings::UserSettings;
+use bevy::input::InputSystem;
+use bevy::prelude::{App, ButtonInput, Gamepad, GamepadAxis, GamepadButton, IntoSystemConfigs, KeyCode, MouseButton, PreUpdate, Query, Res, ResMut, Resource};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// Смещение курсора, после которого нажатие кнопки мыши считается перетаскиванием, а не кликом.
+// Общее для выбора, приказов и панорамирования: одна кнопка может значить и клик, и перетаскивание
+pub const CLICK_DRAG_THRESHOLD: f32 = 6.0;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum InputAction {
+    PanForward,
+    PanBackward,
+    PanLeft,
+    PanRight,
+    RotateLeft,
+    RotateRight,
+    ZoomIn,
+    ZoomOut,
+    DragPan,
+    Select,
+    Order,
+    // Модификатор: добавить к выделению, поставить точку маршрута в очередь
+    Additive,
+    DebugPanic,
+    SaveBookmark(u8),
+    RecallBookmark(u8),
+    MapMode(u8),
+    TogglePause,
+    SpeedUp,
+    SpeedDown,
+    QuickSave,
+    QuickLoad,
+    ToggleAiGoals,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum InputBinding {
+    Key(KeyCode),
+    KeyWithModifier { key: KeyCode, modifier: KeyModifier },
+    Mouse(MouseButton),
+    GamepadButton(GamepadButton),
+    GamepadAxis { axis: GamepadAxis, positive: bool },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum KeyModifier {
+    Control,
+    Shift,
+    Alt,
+}
+
+impl KeyModifier {
+    pub fn pressed(&self, keys: &ButtonInput<KeyCode>) -> bool {
+        match self {
+            KeyModifier::Control => keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]),
+            KeyModifier::Shift => keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]),
+            KeyModifier::Alt => keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct EdgeScrollSettings {
+    pub enabled: bool,
+    pub margin: f32,
+    pub speed: f32,
+}
+
+impl Default for EdgeScrollSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            margin: 8.0,
+            speed: 440.0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct InputSettings {
+    pub bindings: BTreeMap<InputAction, Vec<InputBinding>>,
+    pub edge_scroll: EdgeScrollSettings,
+    pub gamepad_deadzone: f32,
+}
+
+impl Default for InputSettings {
+    fn default() -> Self {
+        let mut bindings = BTreeMap::new();
+        bindings.insert(InputAction::PanForward, vec![
+            InputBinding::Key(KeyCode::KeyW),
+            InputBinding::GamepadAxis { axis: GamepadAxis::LeftStickY, positive: true },
+        ]);
+        bindings.insert(InputAction::PanBackward, vec![
+            InputBinding::Key(KeyCode::KeyS),
+            InputBinding::GamepadAxis { axis: GamepadAxis::LeftStickY, positive: false },
+        ]);
+        bindings.insert(InputAction::PanLeft, vec![
+            InputBinding::Key(KeyCode::KeyA),
+            InputBinding::GamepadAxis { axis: GamepadAxis::LeftStickX, positive: false },
+        ]);
+        bindings.insert(InputAction::PanRight, vec![
+            InputBinding::Key(KeyCode::KeyD),
+            InputBinding::GamepadAxis { axis: GamepadAxis::LeftStickX, positive: true },
+        ]);
+        bindings.insert(InputAction::RotateLeft, vec![
+            InputBinding::Key(KeyCode::KeyQ),
+            InputBinding::GamepadAxis { axis: GamepadAxis::RightStickX, positive: false },
+        ]);
+        bindings.insert(InputAction::RotateRight, vec![
+            InputBinding::Key(KeyCode::KeyE),
+            InputBinding::GamepadAxis { axis: GamepadAxis::RightStickX, positive: true },
+        ]);
+        bindings.insert(InputAction::ZoomIn, vec![
+            InputBinding::Key(KeyCode::Equal),
+            InputBinding::GamepadAxis { axis: GamepadAxis::RightStickY, positive: true },
+        ]);
+        bindings.insert(InputAction::ZoomOut, vec![
+            InputBinding::Key(KeyCode::Minus),
+            InputBinding::GamepadAxis { axis: GamepadAxis::RightStickY, positive: false },
+        ]);
+        bindings.insert(InputAction::DragPan, vec![
+            InputBinding::Mouse(MouseButton::Right),
+            InputBinding::Mouse(MouseButton::Middle),
+        ]);
+        bindings.insert(InputAction::Select, vec![InputBinding::Mouse(MouseButton::Left)]);
+        bindings.insert(InputAction::Order, vec![InputBinding::Mouse(MouseButton::Right)]);
+        bindings.insert(InputAction::Additive, vec![
+            InputBinding::Key(KeyCode::ShiftLeft),
+            InputBinding::Key(KeyCode::ShiftRight),
+        ]);
+        bindings.insert(InputAction::DebugPanic, vec![InputBinding::Key(KeyCode::KeyP)]);
+
+        let digits = [
+            KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3,
+            KeyCode::Digit4, KeyCode::Digit5, KeyCode::Digit6,
+            KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9,
+        ];
+        for (i, key) in digits.into_iter().enumerate() {
+            let slot = i as u8 + 1;
+            bindings.insert(InputAction::SaveBookmark(slot), vec![
+                InputBinding::KeyWithModifier { key, modifier: KeyModifier::Control },
+            ]);
+            bindings.insert(InputAction::RecallBookmark(slot), vec![InputBinding::Key(key)]);
+        }
+
+        bindings.insert(InputAction::TogglePause, vec![InputBinding::Key(KeyCode::Space)]);
+        bindings.insert(InputAction::SpeedUp, vec![
+            InputBinding::Key(KeyCode::NumpadAdd),
+            InputBinding::Key(KeyCode::Period),
+        ]);
+        bindings.insert(InputAction::SpeedDown, vec![
+            InputBinding::Key(KeyCode::NumpadSubtract),
+            InputBinding::Key(KeyCode::Comma),
+        ]);
+
+        bindings.insert(InputAction::QuickSave, vec![
+            InputBinding::KeyWithModifier { key: KeyCode::KeyS, modifier: KeyModifier::Control },
+        ]);
+        bindings.insert(InputAction::QuickLoad, vec![
+            InputBinding::KeyWithModifier { key: KeyCode::KeyL, modifier: KeyModifier::Control },
+        ]);
+
+        bindings.insert(InputAction::ToggleAiGoals, vec![InputBinding::Key(KeyCode::F10)]);
+
+        let function_keys = [
+            KeyCode::F1, KeyCode::F2, KeyCode::F3,
+            KeyCode::F4, KeyCode::F5, KeyCode::F6,
+            KeyCode::F7, KeyCode::F8, KeyCode::F9,
+        ];
+        for (i, key) in function_keys.into_iter().enumerate() {
+            bindings.insert(InputAction::MapMode(i as u8 + 1), vec![InputBinding::Key(key)]);
+        }
+
+        Self {
+            bindings,
+            edge_scroll: EdgeScrollSettings::default(),
+            gamepad_deadzone: 0.15,
+        }
+    }
+}
+
+impl InputSettings {
+    // Действия, которых нет в файле пользователя (например, добавленные в новой версии), берутся по умолчанию
+    pub fn fill_missing_bindings(&mut self) {
+        for (action, bindings) in InputSettings::default().bindings {
+            self.bindings.entry(action).or_insert(bindings);
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ActionState {
+    values: HashMap<InputAction, f32>,
+    previous: HashMap<InputAction, f32>,
+}
+
+impl ActionState {
+    pub fn value(&self, action: InputAction) -> f32 {
+        self.values.get(&action).copied().unwrap_or(0.0)
+    }
+
+    pub fn pressed(&self, action: InputAction) -> bool {
+        self.value(action) > 0.0
+    }
+
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        self.pressed(action) && self.previous.get(&action).copied().unwrap_or(0.0) <= 0.0
+    }
+
+    pub fn just_released(&self, action: InputAction) -> bool {
+        !self.pressed(action) && self.previous.get(&action).copied().unwrap_or(0.0) > 0.0
+    }
+}
+
+pub fn build(app: &mut App) {
+    app.init_resource::<ActionState>();
+    app.add_systems(PreUpdate, update_action_state.after(InputSystem));
+}
+
+fn update_action_state(
+    settings: Res<UserSettings>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    mut state: ResMut<ActionState>,
+) {
+    let state = &mut *state;
+    std::mem::swap(&mut state.previous, &mut state.values);
+    state.values.clear();
+
+    let deadzone = settings.input.gamepad_deadzone;
+
+    // Клавиши, зажатые вместе с модификатором своего сочетания: их простые привязки не срабатывают,
+    // иначе Ctrl+S одновременно сохраняет игру и двигает камеру
+    let chorded: HashSet<KeyCode> = settings.input.bindings.values()
+        .flatten()
+        .filter_map(|binding| match binding {
+            InputBinding::KeyWithModifier { key, modifier } if keys.pressed(*key) && modifier.pressed(&keys) => Some(*key),
+            _ => None,
+        })
+        .collect();
+
+    for (action, bindings) in settings.input.bindings.iter() {
+        let mut value: f32 = 0.0;
+
+        for binding in bindings {
+            let binding_value = match binding {
+                InputBinding::Key(key) => if keys.pressed(*key) && !chorded.contains(key) { 1.0 } else { 0.0 },
+                InputBinding::KeyWithModifier { key, modifier } => {
+                    if keys.pressed(*key) && modifier.pressed(&keys) { 1.0 } else { 0.0 }
+                },
+                InputBinding::Mouse(button) => if mouse.pressed(*button) { 1.0 } else { 0.0 },
+                InputBinding::GamepadButton(button) => {
+                    if gamepads.iter().any(|gamepad| gamepad.pressed(*button)) { 1.0 } else { 0.0 }
+                },
+                InputBinding::GamepadAxis { axis, positive } => {
+                    gamepads.iter()
+                        .filter_map(|gamepad| gamepad.get(*axis))
+                        .map(|raw| if *positive { raw } else { -raw })
+                        .filter(|v| *v > deadzone)
+                        .fold(0.0, f32::max)
+                },
+            };
+            value = value.max(binding_value);
+        }
+
+        if value > 0.0 {
+            state.values.insert(*action, value.min(1.0));
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use crate::core::map::province::{ProvinceId, ProvinceMap};
+use bevy::math::Vec2;
+use std::collections::HashMap;
+
+const PIXEL_SIMPLIFY_TOLERANCE: f32 = 0.75;
+
+#[derive(Clone, Debug)]
+pub struct BorderLine {
+    pub provinces: (ProvinceId, ProvinceId),
+    pub points: Vec<Vec2>,
+}
+
+// Границы проходят по рёбрам пикселей: вершина решётки (x, y) кодируется как y * (width + 1) + x
+pub fn extract_border_lines(map: &ProvinceMap, wrap: bool) -> Vec<BorderLine> {
+    let width = map.width();
+    let height = map.height();
+    let ids = map.raw_ids();
+    let stride = width as u64 + 1;
+    let vertex = |x: u32, y: u32| y as u64 * stride + x as u64;
+
+    let mut edges: HashMap<(u16, u16), Vec<(u64, u64)>> = HashMap::new();
+    let mut add_edge = |a: u16, b: u16, from: u64, to: u64| {
+        if a != b && a != 0 && b != 0 {
+            edges.entry((a.min(b), a.max(b))).or_default().push((from, to));
+        }
+    };
+
+    for y in 0..height {
+        let row = (y * width) as usize;
+        for x in 0..width {
+            let id = ids[row + x as usize];
+
+            if x + 1 < width {
+                add_edge(id, ids[row + x as usize + 1], vertex(x + 1, y), vertex(x + 1, y + 1));
+            } else if wrap {
+                add_edge(id, ids[row], vertex(width, y), vertex(width, y + 1));
+            }
+
+            if y + 1 < height {
+                add_edge(id, ids[row + (width + x) as usize], vertex(x, y + 1), vertex(x + 1, y + 1));
+            }
+        }
+    }
+
+    let mut pairs: Vec<(u16, u16)> = edges.keys().copied().collect();
+    pairs.sort();
+
+    let mut lines = Vec::new();
+    for pair in pairs {
+        let pair_edges = edges.remove(&pair).unwrap_or_default();
+
+        for chain in chain_edges(&pair_edges) {
+            let points: Vec<Vec2> = chain.iter()
+                .map(|v| Vec2::new((v % stride) as f32, (v / stride) as f32))
+                .collect();
+
+            let points = simplify_polyline(&points, PIXEL_SIMPLIFY_TOLERANCE)
+                .into_iter()
+                .map(|p| map.pixel_to_world(p.x, p.y))
+                .collect();
+
+            lines.push(BorderLine {
+                provinces: (ProvinceId(pair.0), ProvinceId(pair.1)),
+                points,
+            });
+        }
+    }
+
+    lines
+}
+
+fn chain_edges(edges: &[(u64, u64)]) -> Vec<Vec<u64>> {
+    let mut adjacency: HashMap<u64, Vec<u64>> = HashMap::new();
+    for (a, b) in edges {
+        adjacency.entry(*a).or_default().push(*b);
+        adjacency.entry(*b).or_default().push(*a);
+    }
+
+    let degree: HashMap<u64, usize> = adjacency.iter().map(|(v, n)| (*v, n.len())).collect();
+
+    let mut nodes: Vec<u64> = adjacency.keys().copied().collect();
+    nodes.sort();
+
+    let mut chains = Vec::new();
+
+    // Сначала цепочки от концов и развилок, затем оставшиеся замкнутые контуры
+    for pass_endpoints in [true, false] {
+        for start in nodes.iter().copied() {
+            if pass_endpoints && degree[&start] == 2 {
+                continue;
+            }
+
+            while let Some(next) = take_neighbour(&mut adjacency, start) {
+                let mut chain = vec![start, next];
+                let mut current = next;
+
+                while degree[&current] == 2 {
+                    match take_neighbour(&mut adjacency, current) {
+                        Some(n) => {
+                            chain.push(n);
+                            current = n;
+                        }
+                        None => break,
+                    }
+                }
+
+                chains.push(chain);
+            }
+        }
+    }
+
+    chains
+}
+
+fn take_neighbour(adjacency: &mut HashMap<u64, Vec<u64>>, from: u64) -> Option<u64> {
+    let next = adjacency.get_mut(&from)?.pop()?;
+    if let Some(back) = adjacency.get_mut(&next) {
+        if let Some(i) = back.iter().position(|v| *v == from) {
+            back.swap_remove(i);
+        }
+    }
+    Some(next)
+}
+
+pub fn simplify_polyline(points: &[Vec2], tolerance: f32) -> Vec<Vec2> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    let mut stack = vec![(0, points.len() - 1)];
+    while let Some((start, end)) = stack.pop() {
+        let mut max_distance = 0.0;
+        let mut index = start;
+
+        for i in start + 1..end {
+            let distance = point_segment_distance(points[i], points[start], points[end]);
+            if distance > max_distance {
+                max_distance = distance;
+                index = i;
+            }
+        }
+
+        if max_distance > tolerance {
+            keep[index] = true;
+            stack.push((start, index));
+            stack.push((index, end));
+        }
+    }
+
+    points.iter().zip(keep).filter(|(_, k)| *k).map(|(p, _)| *p).collect()
+}
+
+fn point_segment_distance(point: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    let length_squared = ab.length_squared();
+    if length_squared <= f32::EPSILON {
+        return point.distance(a);
+    }
+
+    let t = ((point - a).dot(ab) / length_squared).clamp(0.0, 1.0);
+    point.distance(a + ab * t)
+}
@@ -0,0 +1,296 @@
+pub(crate) mod extraction;
+pub(crate) mod ribbon;
+
+use crate::core::async_tasks::{BackgroundTaskResult, BackgroundTaskSystem};
+use crate::core::country::{CountryRegistry, ProvinceOwnerChanged};
+use crate::core::map::borders::extraction::{extract_border_lines, BorderLine};
+use crate::core::map::borders::ribbon::RibbonBuilder;
+use crate::core::map::components::{WorldChunk, WorldMap};
+use crate::core::map::province::adjacency::build_province_graph;
+use crate::core::map::province::selection::SelectedProvince;
+use crate::core::map::province::{ProvinceId, ProvinceMap};
+use crate::core::map::terrain::cache::LodLevel;
+use crate::core::map::terrain::heightfield::Heightfield;
+use crate::core::map::wrap::{MapWrap, Wrapped};
+use crate::core::simulation::run_simulation;
+use bevy::color::Color;
+use bevy::math::Vec2;
+use bevy::pbr::{MeshMaterial3d, StandardMaterial};
+use bevy::prelude::{default, resource_added, resource_changed, App, Assets, BuildChildren, Commands, Component, Entity, EventReader, Handle, IntoSystemConfigs, Local, Mesh, Mesh3d, Parent, Query, Res, ResMut, Resource, Startup, Transform, Update, Visibility, Without};
+use bevy::render::view::RenderLayers;
+use std::collections::{HashMap, HashSet};
+use std::thread;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BorderStyle {
+    Province,
+    Country,
+    Selected,
+}
+
+impl BorderStyle {
+    pub fn width(&self) -> f32 {
+        match self {
+            BorderStyle::Province => 0.6,
+            BorderStyle::Country => 1.6,
+            BorderStyle::Selected => 1.2,
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            BorderStyle::Province => Color::srgb(0.15, 0.15, 0.15),
+            BorderStyle::Country => Color::srgb(0.05, 0.05, 0.05),
+            BorderStyle::Selected => Color::srgb(1.0, 0.85, 0.2),
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct ProvinceBorders {
+    pub lines: Vec<BorderLine>,
+}
+
+#[derive(Resource)]
+pub struct BorderAssets {
+    pub material: Handle<StandardMaterial>,
+}
+
+#[derive(Component)]
+pub struct BorderChunk {
+    origin: Vec2,
+    pieces: Vec<BorderLine>,
+    meshes: HashMap<LodLevel, Handle<Mesh>>,
+    current_lod: Option<LodLevel>,
+}
+
+impl BorderChunk {
+    pub fn invalidate(&mut self) {
+        self.meshes.clear();
+        self.current_lod = None;
+    }
+
+    fn touches(&self, provinces: &HashSet<ProvinceId>) -> bool {
+        self.pieces.iter().any(|piece| provinces.contains(&piece.provinces.0) || provinces.contains(&piece.provinces.1))
+    }
+}
+
+// Граница выделенной провинции важнее остальных, между провинциями разных владельцев — государственная
+fn border_style(line: &BorderLine, countries: &CountryRegistry, selected: Option<ProvinceId>) -> BorderStyle {
+    if selected.is_some_and(|id| line.provinces.0 == id || line.provinces.1 == id) {
+        return BorderStyle::Selected;
+    }
+
+    let owners = (countries.owner_of(line.provinces.0), countries.owner_of(line.provinces.1));
+    match owners {
+        (Some(a), Some(b)) if a != b => BorderStyle::Country,
+        _ => BorderStyle::Province,
+    }
+}
+
+pub fn build(app: &mut App) {
+    app.add_systems(Startup, (init_border_assets, start_border_extraction.after(build_province_graph)));
+    app.add_systems(Update, spawn_border_chunks.run_if(resource_added::<ProvinceBorders>));
+    app.add_systems(Update, (
+        invalidate_owner_borders.after(run_simulation),
+        invalidate_selected_borders.run_if(resource_changed::<SelectedProvince>),
+        update_border_chunks,
+    ).chain());
+}
+
+fn init_border_assets(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let material = materials.add(StandardMaterial {
+        base_color: Color::WHITE,
+        unlit: true,
+        ..default()
+    });
+
+    commands.insert_resource(BorderAssets { material });
+}
+
+fn start_border_extraction(
+    map: Res<ProvinceMap>,
+    wrap: Res<MapWrap>,
+    task_system: Res<BackgroundTaskSystem>,
+) {
+    let map = map.clone();
+    let wrap = wrap.enabled;
+    let sender = task_system.sender.clone();
+
+    thread::spawn(move || {
+        let lines = extract_border_lines(&map, wrap);
+
+        #[cfg(debug_assertions)]
+        println!("Извлечено {} линий границ провинций", lines.len());
+
+        if let Err(e) = sender.send(BackgroundTaskResult::BordersExtracted(lines)) {
+            println!("Не удалось отправить границы провинций в основной поток: {:?}", e);
+        }
+    });
+}
+
+fn spawn_border_chunks(
+    mut commands: Commands,
+    borders: Res<ProvinceBorders>,
+    assets: Res<BorderAssets>,
+    map: Query<&WorldMap>,
+    chunks: Query<(Entity, &Wrapped, &Transform), Without<BorderChunk>>,
+) {
+    let Ok(map) = map.get_single() else {
+        return;
+    };
+    let chunk_size = map.chunk_size as f32;
+
+    let mut chunk_entities: HashMap<(i32, i32), (Entity, Vec2)> = HashMap::new();
+    for (entity, wrapped, transform) in chunks.iter() {
+        let origin = Vec2::new(wrapped.canonical_x, transform.translation.z);
+        let key = ((origin.x / chunk_size).floor() as i32, (origin.y / chunk_size).floor() as i32);
+        chunk_entities.insert(key, (entity, origin));
+    }
+
+    let mut pieces: HashMap<(i32, i32), Vec<BorderLine>> = HashMap::new();
+    for line in borders.lines.iter() {
+        for (key, points) in split_by_chunks(&line.points, chunk_size) {
+            pieces.entry(key).or_default().push(BorderLine {
+                provinces: line.provinces,
+                points,
+            });
+        }
+    }
+
+    for (key, chunk_pieces) in pieces {
+        // Участки на самом краю карты относятся к последнему чанку
+        let key = (key.0.min(map.chunks_with as i32 - 1), key.1.min(map.chunks_height as i32 - 1));
+        let Some((chunk_entity, origin)) = chunk_entities.get(&key).copied() else {
+            continue;
+        };
+
+        let border = commands.spawn((
+            Mesh3d::from(Handle::default()),
+            MeshMaterial3d::from(assets.material.clone()),
+            Transform::default(),
+            Visibility::default(),
+            RenderLayers::layer(1),
+            BorderChunk {
+                origin,
+                pieces: chunk_pieces,
+                meshes: HashMap::new(),
+                current_lod: None,
+            },
+        )).id();
+
+        commands.entity(chunk_entity).add_child(border);
+    }
+}
+
+fn split_by_chunks(points: &[Vec2], chunk_size: f32) -> Vec<((i32, i32), Vec<Vec2>)> {
+    let mut result: Vec<((i32, i32), Vec<Vec2>)> = Vec::new();
+
+    for window in points.windows(2) {
+        let middle = (window[0] + window[1]) / 2.0;
+        let key = ((middle.x / chunk_size).floor() as i32, (middle.y / chunk_size).floor() as i32);
+
+        match result.last_mut() {
+            Some((last_key, piece)) if *last_key == key => piece.push(window[1]),
+            _ => result.push((key, vec![window[0], window[1]])),
+        }
+    }
+
+    result
+}
+
+fn invalidate_owner_borders(
+    mut changes: EventReader<ProvinceOwnerChanged>,
+    mut borders: Query<&mut BorderChunk>,
+) {
+    let changed: HashSet<ProvinceId> = changes.read().map(|change| change.province).collect();
+    if changed.is_empty() {
+        return;
+    }
+
+    for mut border in borders.iter_mut() {
+        if border.touches(&changed) {
+            border.invalidate();
+        }
+    }
+}
+
+// Перестраиваются только чанки вокруг прежней и новой выделенной провинции
+fn invalidate_selected_borders(
+    selected: Res<SelectedProvince>,
+    mut previous: Local<Option<ProvinceId>>,
+    mut borders: Query<&mut BorderChunk>,
+) {
+    let changed: HashSet<ProvinceId> = previous.iter().chain(selected.0.iter()).copied().collect();
+    *previous = selected.0;
+    if changed.is_empty() {
+        return;
+    }
+
+    for mut border in borders.iter_mut() {
+        if border.touches(&changed) {
+            border.invalidate();
+        }
+    }
+}
+
+fn update_border_chunks(
+    heightfield: Option<Res<Heightfield>>,
+    countries: Res<CountryRegistry>,
+    selected: Res<SelectedProvince>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    chunks: Query<(&WorldChunk, &RenderLayers), Without<BorderChunk>>,
+    mut borders: Query<(&Parent, &mut BorderChunk, &mut Mesh3d, &mut RenderLayers)>,
+) {
+    let Some(heightfield) = heightfield else {
+        return;
+    };
+
+    let max_builds_per_frame = 4;
+    let mut builds = 0;
+
+    for (parent, mut border, mut mesh3d, mut layers) in borders.iter_mut() {
+        let Ok((chunk, chunk_layers)) = chunks.get(parent.get()) else {
+            continue;
+        };
+
+        if *layers != *chunk_layers {
+            *layers = chunk_layers.clone();
+        }
+
+        if border.current_lod == chunk.current_lod {
+            continue;
+        }
+
+        let Some(lod) = chunk.current_lod else {
+            mesh3d.0 = Handle::default();
+            border.current_lod = None;
+            continue;
+        };
+
+        if !border.meshes.contains_key(&lod) {
+            if builds >= max_builds_per_frame {
+                continue;
+            }
+            builds += 1;
+
+            let mut builder = RibbonBuilder::default();
+            for piece in border.pieces.iter() {
+                builder.add_line(&piece.points, border.origin, border_style(piece, &countries, selected.0), lod, &heightfield);
+            }
+
+            let handle = if builder.is_empty() {
+                Handle::default()
+            } else {
+                meshes.add(builder.build())
+            };
+            border.meshes.insert(lod, handle);
+        }
+
+        mesh3d.0 = border.meshes[&lod].clone();
+        border.current_lod = Some(lod);
+    }
+}
@@ -0,0 +1,111 @@
+use crate::core::map::borders::extraction::simplify_polyline;
+use crate::core::map::borders::BorderStyle;
+use crate::core::map::terrain::cache::LodLevel;
+use crate::core::map::terrain::heightfield::Heightfield;
+use bevy::asset::RenderAssetUsages;
+use bevy::color::ColorToComponents;
+use bevy::math::{Vec2, Vec3};
+use bevy::prelude::Mesh;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+
+struct RibbonLod {
+    simplify_tolerance: f32,
+    max_segment_length: f32,
+    lift: f32,
+    width_factor: f32,
+}
+
+fn ribbon_lod(lod: LodLevel) -> RibbonLod {
+    match lod {
+        LodLevel::High => RibbonLod { simplify_tolerance: 0.0, max_segment_length: 2.0, lift: 0.4, width_factor: 1.0 },
+        LodLevel::Medium => RibbonLod { simplify_tolerance: 1.5, max_segment_length: 4.0, lift: 1.0, width_factor: 1.8 },
+        LodLevel::Low => RibbonLod { simplify_tolerance: 3.0, max_segment_length: 8.0, lift: 2.0, width_factor: 3.0 },
+    }
+}
+
+#[derive(Default)]
+pub struct RibbonBuilder {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    colors: Vec<[f32; 4]>,
+    indices: Vec<u32>,
+}
+
+impl RibbonBuilder {
+    // points в мировых координатах, origin — мировая позиция родителя меша
+    pub fn add_line(
+        &mut self,
+        points: &[Vec2],
+        origin: Vec2,
+        style: BorderStyle,
+        lod: LodLevel,
+        heightfield: &Heightfield,
+    ) {
+        let params = ribbon_lod(lod);
+        let simplified = if params.simplify_tolerance > 0.0 {
+            simplify_polyline(points, params.simplify_tolerance)
+        } else {
+            points.to_vec()
+        };
+
+        let dense = densify(&simplified, params.max_segment_length);
+        if dense.len() < 2 {
+            return;
+        }
+
+        let half_width = style.width() * params.width_factor / 2.0;
+        let color = style.color().to_linear().to_f32_array();
+        let base = self.positions.len() as u32;
+
+        for i in 0..dense.len() {
+            let prev = dense[i.saturating_sub(1)];
+            let next = dense[(i + 1).min(dense.len() - 1)];
+            let direction = (next - prev).normalize_or_zero();
+            let side = Vec2::new(-direction.y, direction.x) * half_width;
+
+            for offset in [side, -side] {
+                let world = dense[i] + offset;
+                let y = heightfield.height_at(world.x, world.y) + params.lift;
+                let local = world - origin;
+                self.positions.push([local.x, y, local.y]);
+                self.normals.push(Vec3::Y.to_array());
+                self.colors.push(color);
+            }
+        }
+
+        for i in 0..(dense.len() as u32 - 1) {
+            let a = base + i * 2;
+            self.indices.extend_from_slice(&[a, a + 2, a + 1, a + 1, a + 2, a + 3]);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    pub fn build(self) -> Mesh {
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default());
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, self.positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, self.normals);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, self.colors);
+        mesh.insert_indices(Indices::U32(self.indices));
+        mesh
+    }
+}
+
+fn densify(points: &[Vec2], max_segment_length: f32) -> Vec<Vec2> {
+    let mut result = Vec::with_capacity(points.len());
+    for window in points.windows(2) {
+        let (a, b) = (window[0], window[1]);
+        let steps = (a.distance(b) / max_segment_length).ceil().max(1.0) as usize;
+        for step in 0..steps {
+            result.push(a.lerp(b, step as f32 / steps as f32));
+        }
+    }
+
+    if let Some(last) = points.last() {
+        result.push(*last);
+    }
+
+    result
+}
@@ -0,0 +1,85 @@
+use crate::core::input::{ActionState, InputAction};
+use crate::core::map::camera::fly_to::CameraFlyTo;
+use crate::core::map::camera::zoom::focus_point;
+use crate::core::map::camera::CameraController;
+use crate::core::settings::UserSettings;
+use bevy::math::Vec2;
+use bevy::prelude::{EulerRot, EventWriter, Query, Res, ResMut, Resource, Transform, World};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub const BOOKMARK_SLOTS: u8 = 9;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct CameraBookmark {
+    pub focus: Vec2,
+    pub height: f32,
+    pub yaw: f32,
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, Default, Debug)]
+pub struct CameraBookmarks {
+    pub slots: BTreeMap<u8, CameraBookmark>,
+}
+
+fn camera_view(transform: &Transform, controller: &CameraController) -> CameraBookmark {
+    let focus = focus_point(transform, controller.zoom.ground_height);
+    let (yaw, _, _) = transform.rotation.to_euler(EulerRot::YXZ);
+
+    CameraBookmark {
+        focus: Vec2::new(focus.x, focus.z),
+        height: controller.zoom.target_height,
+        yaw,
+    }
+}
+
+pub(crate) fn current_camera_view(world: &mut World) -> Option<CameraBookmark> {
+    let mut query = world.query::<(&Transform, &CameraController)>();
+    query.get_single(world).ok().map(|(transform, controller)| camera_view(transform, controller))
+}
+
+pub(super) fn load_bookmarks(
+    settings: Res<UserSettings>,
+    mut bookmarks: ResMut<CameraBookmarks>,
+) {
+    *bookmarks = settings.camera_bookmarks.clone();
+}
+
+pub(super) fn bookmark_input(
+    actions: Res<ActionState>,
+    query: Query<(&Transform, &CameraController)>,
+    mut bookmarks: ResMut<CameraBookmarks>,
+    mut settings: ResMut<UserSettings>,
+    mut fly_to: EventWriter<CameraFlyTo>,
+) {
+    let Ok((transform, controller)) = query.get_single() else {
+        return;
+    };
+
+    for slot in 1..=BOOKMARK_SLOTS {
+        if actions.just_pressed(InputAction::SaveBookmark(slot)) {
+            bookmarks.slots.insert(slot, camera_view(transform, controller));
+
+            settings.camera_bookmarks = bookmarks.clone();
+            if let Err(e) = settings.save() {
+                eprintln!("Не удалось сохранить закладку камеры {}: {}", slot, e);
+            }
+            continue;
+        }
+
+        // Ctrl+цифра одновременно активирует и привязку без модификатора
+        if actions.pressed(InputAction::SaveBookmark(slot)) {
+            continue;
+        }
+
+        if actions.just_pressed(InputAction::RecallBookmark(slot)) {
+            if let Some(bookmark) = bookmarks.slots.get(&slot) {
+                fly_to.send(
+                    CameraFlyTo::new(bookmark.focus)
+                        .with_height(bookmark.height)
+                        .with_yaw(bookmark.yaw)
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use crate::core::input::{ActionState, InputAction};
+use crate::core::map::camera::zoom::{height_to_tilt, MAX_HEIGHT, MIN_HEIGHT};
+use crate::core::map::camera::{clamp_camera_position, CameraController};
+use crate::core::map::wrap::MapWrap;
+use bevy::math::{Vec2, Vec3};
+use bevy::prelude::{Commands, Component, Entity, EulerRot, Event, EventReader, Quat, Query, Res, Time, Transform};
+use std::f32::consts::PI;
+
+const DEFAULT_FLIGHT_DURATION: f32 = 1.5;
+const FLIGHT_ARC_FACTOR: f32 = 0.15;
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct CameraFlyTo {
+    pub focus: Vec2,
+    pub height: Option<f32>,
+    pub yaw: Option<f32>,
+    pub duration: f32,
+}
+
+impl CameraFlyTo {
+    pub fn new(focus: Vec2) -> Self {
+        Self {
+            focus,
+            height: None,
+            yaw: None,
+            duration: DEFAULT_FLIGHT_DURATION,
+        }
+    }
+
+    pub fn with_height(mut self, height: f32) -> Self {
+        self.height = Some(height);
+        self
+    }
+
+    pub fn with_yaw(mut self, yaw: f32) -> Self {
+        self.yaw = Some(yaw);
+        self
+    }
+
+    pub fn with_duration(mut self, duration: f32) -> Self {
+        self.duration = duration;
+        self
+    }
+}
+
+#[derive(Component)]
+pub(super) struct CameraFlight {
+    from_position: Vec2,
+    to_position: Vec2,
+    from_height: f32,
+    to_height: f32,
+    from_yaw: f32,
+    to_yaw: f32,
+    arc: f32,
+    elapsed: f32,
+    duration: f32,
+}
+
+impl CameraFlight {
+    pub(super) fn shift_x(&mut self, shift: f32) {
+        self.from_position.x += shift;
+        self.to_position.x += shift;
+    }
+}
+
+pub(super) fn start_camera_flight(
+    mut commands: Commands,
+    mut events: EventReader<CameraFlyTo>,
+    wrap: Res<MapWrap>,
+    query: Query<(Entity, &Transform, &CameraController)>,
+) {
+    let Some(event) = events.read().last() else {
+        return;
+    };
+
+    let Ok((entity, transform, controller)) = query.get_single() else {
+        return;
+    };
+
+    let (from_yaw, _, _) = transform.rotation.to_euler(EulerRot::YXZ);
+    let to_yaw = event.yaw.unwrap_or(from_yaw);
+    let to_height = event.height.unwrap_or(controller.zoom.target_height).clamp(MIN_HEIGHT, MAX_HEIGHT);
+
+    let from_position = Vec2::new(transform.translation.x, transform.translation.z);
+    let mut to_position = camera_position_for_focus(event.focus, to_height, to_yaw);
+    to_position.x = wrap.nearest_to(to_position.x, from_position.x);
+    let arc = (from_position.distance(to_position) * FLIGHT_ARC_FACTOR)
+        .min(MAX_HEIGHT - controller.zoom.current_height.max(to_height))
+        .max(0.0);
+
+    commands.entity(entity).insert(CameraFlight {
+        from_position,
+        to_position,
+        from_height: controller.zoom.current_height,
+        to_height,
+        from_yaw,
+        to_yaw: from_yaw + shortest_angle(from_yaw, to_yaw),
+        arc,
+        elapsed: 0.0,
+        duration: event.duration.max(0.01),
+    });
+}
+
+pub(super) fn animate_camera_flight(
+    mut commands: Commands,
+    time: Res<Time>,
+    actions: Res<ActionState>,
+    wrap: Res<MapWrap>,
+    mut query: Query<(Entity, &mut Transform, &mut CameraController, &mut CameraFlight)>,
+) {
+    for (entity, mut transform, mut controller, mut flight) in query.iter_mut() {
+        // Любое ручное управление прерывает перелёт
+        let interrupted = [
+            InputAction::PanForward,
+            InputAction::PanBackward,
+            InputAction::PanLeft,
+            InputAction::PanRight,
+            InputAction::DragPan,
+        ].iter().any(|action| actions.pressed(*action));
+
+        if interrupted {
+            commands.entity(entity).remove::<CameraFlight>();
+            continue;
+        }
+
+        flight.elapsed += time.delta_secs();
+        let t = (flight.elapsed / flight.duration).min(1.0);
+        let eased = ease_in_out_cubic(t);
+
+        let position = flight.from_position.lerp(flight.to_position, eased);
+        let clamped = clamp_camera_position(Vec3::new(position.x, transform.translation.y, position.y), &wrap);
+        transform.translation.x = clamped.x;
+        transform.translation.z = clamped.z;
+
+        let height = (lerp(flight.from_height, flight.to_height, eased) + flight.arc * (PI * t).sin())
+            .clamp(MIN_HEIGHT, MAX_HEIGHT);
+        controller.zoom.current_height = height;
+        controller.zoom.target_height = height;
+
+        let yaw = lerp(flight.from_yaw, flight.to_yaw, eased);
+        transform.rotation = Quat::from_euler(EulerRot::YXZ, yaw, height_to_tilt(height), 0.0);
+
+        if t >= 1.0 {
+            controller.zoom.target_height = flight.to_height;
+            commands.entity(entity).remove::<CameraFlight>();
+        }
+    }
+}
+
+// Камера смотрит под углом, поэтому сама она стоит позади точки фокуса
+pub(super) fn camera_position_for_focus(focus: Vec2, height: f32, yaw: f32) -> Vec2 {
+    let tilt = height_to_tilt(height);
+    let horizontal_distance = height / (-tilt).tan();
+    let forward = Quat::from_rotation_y(yaw) * Vec3::NEG_Z;
+    focus - Vec2::new(forward.x, forward.z) * horizontal_distance
+}
+
+fn shortest_angle(from: f32, to: f32) -> f32 {
+    let delta = (to - from).rem_euclid(2.0 * PI);
+    if delta > PI {
+        delta - 2.0 * PI
+    } else {
+        delta
+    }
+}
+
+fn ease_in_out_cubic(t: f32) -> f32 {
+    if t < 0.5 {
+        4.0 * t * t * t
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+    }
+}
+
+fn lerp(start: f32, end: f32, t: f32) -> f32 {
+    start + (end - start) * t
+}
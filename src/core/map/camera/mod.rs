@@ -1,21 +1,30 @@
 mod view_world;
 mod zoom;
+pub(crate) mod bookmarks;
+pub(crate) mod fly_to;
 
+use crate::core::input::{ActionState, InputAction, CLICK_DRAG_THRESHOLD};
+use crate::core::map::camera::bookmarks::{bookmark_input, load_bookmarks, CameraBookmarks};
+use crate::core::map::camera::fly_to::{animate_camera_flight, start_camera_flight, CameraFlight, CameraFlyTo};
+use crate::core::map::wrap::{apply_wrap_offsets, MapWrap};
 use crate::core::map::camera::view_world::{process_lod_changes, process_pending_mesh_deletions, view_world, PendingLodChanges, PendingMeshDeletions};
-use crate::core::map::camera::zoom::zoom_handler;
+use crate::core::map::camera::zoom::{focus_point, zoom_handler};
+use crate::core::settings::UserSettings;
 use bevy::app::{Startup, Update};
 use bevy::math::Vec3;
-use bevy::prelude::{ButtonInput, Camera, Camera3d, Commands, Component, FixedUpdate, GlobalTransform, KeyCode, MouseButton, Query, Ray3d, Res, ResMut, Resource, Time, Transform, Vec2, Window};
+use bevy::prelude::{Camera, Camera3d, IntoSystemConfigs, Commands, Component, FixedUpdate, GlobalTransform, Quat, Query, Ray3d, Res, ResMut, Resource, Time, Transform, Vec2, Window};
 use crate::core::map::terrain::cache::LodLevel;
 
 const MAP_MIN_X: f32 = -256.0;
 const MAP_MAX_X: f32 = 8192.0 + 256.0;
 const MAP_MIN_Z: f32 = -256.0;
 const MAP_MAX_Z: f32 = 4096.0 + 256.0;
+const EDGE_SCROLL_REFERENCE_HEIGHT: f32 = 120.0;
 
 #[derive(Component)]
 struct CameraController {
     speed: f32,
+    rotation_speed: f32,
     zoom: CameraZoom
 }
 
@@ -24,20 +33,24 @@ struct CameraZoom {
     target_height: f32,
     current_height: f32,
     smooth_factor: f32,
+    ground_height: f32,
+    ground_smooth_rate: f32,
+    effective_height: f32,
 }
 
 #[derive(Resource, Default)]
 struct CameraDragState {
     is_dragging: bool,
+    press_screen_position: Option<Vec2>,
     drag_start_world_position: Option<Vec3>,
 }
 
 #[derive(Component)]
-struct CameraCorners {
-    min_x: f32,
-    max_x: f32,
-    min_z: f32,
-    max_z: f32,
+pub(crate) struct CameraCorners {
+    pub(crate) min_x: f32,
+    pub(crate) max_x: f32,
+    pub(crate) min_z: f32,
+    pub(crate) max_z: f32,
 }
 
 #[derive(Resource)]
@@ -79,6 +92,18 @@ pub fn build(app: &mut bevy::prelude::App) {
     app.init_resource::<CameraLodState>(); // Ресурс состояния LOD
     app.init_resource::<PendingLodChanges>(); // Инициализируем ресурс для изменений LOD
     app.add_systems(Update, process_pending_mesh_deletions);
+
+    app.add_event::<CameraFlyTo>();
+    app.init_resource::<CameraBookmarks>();
+    app.add_systems(Startup, load_bookmarks);
+    app.add_systems(Update, (bookmark_input, start_camera_flight, animate_camera_flight).chain());
+
+    app.add_systems(Update, (wrap_camera_position, update_wrap_view, apply_wrap_offsets)
+        .chain()
+        .after(camera_movement)
+        .after(camera_drag_movement)
+        .after(animate_camera_flight)
+        .before(view_world));
 }
 
 fn update_lod_state(
@@ -86,7 +111,7 @@ fn update_lod_state(
     mut lod_state: ResMut<CameraLodState>,
 ) {
     if let Ok(controller) = query.get_single() {
-        lod_state.current_height = controller.zoom.current_height;
+        lod_state.current_height = controller.zoom.effective_height;
     }
 }
 
@@ -98,11 +123,15 @@ fn init(mut commands: Commands) {
         Transform::from_xyz(1100.0, initial_height, 720.0),
         CameraController {
             speed: 440.0,
+            rotation_speed: 1.5,
             zoom: CameraZoom {
                 speed: 1200.0,
                 target_height: initial_height,
                 current_height: initial_height,
                 smooth_factor: 0.1,
+                ground_height: 0.0,
+                ground_smooth_rate: 3.0,
+                effective_height: initial_height,
             }
         },
         CameraCorners {
@@ -125,9 +154,10 @@ fn update_camera_corners(
         Vec2::new(0.0, 0.0),
         Vec2::new(window.width(), 0.0),
         Vec2::new(0.0, window.height()),
+        Vec2::new(window.width(), window.height()),
     ];
 
-    let mut world_corners = [Vec3::ZERO; 4];
+    let mut world_corners = [camera_transform.translation(); 4];
 
     for (i, screen_corner) in screen_corners.iter().enumerate() {
         match camera.viewport_to_world(camera_transform, *screen_corner) {
@@ -142,10 +172,11 @@ fn update_camera_corners(
         }
     }
 
-    corners.min_x = world_corners[0].x;
-    corners.min_z = world_corners[0].z;
-    corners.max_x = world_corners[1].x;
-    corners.max_z = world_corners[2].z;
+    // Камера может быть повёрнута, поэтому берём охватывающий прямоугольник всех углов
+    corners.min_x = world_corners.iter().map(|c| c.x).fold(f32::INFINITY, f32::min);
+    corners.max_x = world_corners.iter().map(|c| c.x).fold(f32::NEG_INFINITY, f32::max);
+    corners.min_z = world_corners.iter().map(|c| c.z).fold(f32::INFINITY, f32::min);
+    corners.max_z = world_corners.iter().map(|c| c.z).fold(f32::NEG_INFINITY, f32::max);
 }
 
 fn ray_intersect_plane(ray: Ray3d, plane_normal: Vec3, plane_d: f32) -> Option<Vec3> {
@@ -161,58 +192,109 @@ fn ray_intersect_plane(ray: Ray3d, plane_normal: Vec3, plane_d: f32) -> Option<V
 
 fn camera_movement(
     time: Res<Time>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
+    settings: Res<UserSettings>,
+    wrap: Res<MapWrap>,
+    window: Query<&Window>,
     mut query: Query<(&CameraController, &mut Transform)>,
 ) {
-    for (controller, mut transform) in query.iter_mut() {
-        let mut direction = Vec3::ZERO;
+    let edge_scroll = &settings.input.edge_scroll;
+    let edge_direction = if edge_scroll.enabled {
+        window.get_single().ok()
+            .and_then(|window| window.cursor_position().map(|cursor| (window, cursor)))
+            .map(|(window, cursor)| {
+                let mut direction = Vec2::ZERO;
+                if cursor.x <= edge_scroll.margin {
+                    direction.x -= 1.0;
+                }
+                if cursor.x >= window.width() - edge_scroll.margin {
+                    direction.x += 1.0;
+                }
+                if cursor.y <= edge_scroll.margin {
+                    direction.y += 1.0;
+                }
+                if cursor.y >= window.height() - edge_scroll.margin {
+                    direction.y -= 1.0;
+                }
+                direction
+            })
+            .unwrap_or(Vec2::ZERO)
+    } else {
+        Vec2::ZERO
+    };
 
-        if keyboard_input.pressed(KeyCode::KeyW) {
-            direction.z -= 1.0;
-        }
-        if keyboard_input.pressed(KeyCode::KeyS) {
-            direction.z += 1.0;
-        }
-        if keyboard_input.pressed(KeyCode::KeyA) {
-            direction.x -= 1.0;
+    for (controller, mut transform) in query.iter_mut() {
+        let rotation = actions.value(InputAction::RotateRight) - actions.value(InputAction::RotateLeft);
+        if rotation != 0.0 {
+            let focus = focus_point(&transform, controller.zoom.ground_height);
+            let angle = -rotation * controller.rotation_speed * time.delta_secs();
+            transform.rotate_around(focus, Quat::from_rotation_y(angle));
         }
-        if keyboard_input.pressed(KeyCode::KeyD) {
-            direction.x += 1.0;
+
+        let input = Vec2::new(
+            actions.value(InputAction::PanRight) - actions.value(InputAction::PanLeft),
+            actions.value(InputAction::PanForward) - actions.value(InputAction::PanBackward),
+        );
+
+        let mut offset = pan_direction(&transform, input.clamp_length_max(1.0)) * controller.speed;
+
+        if edge_direction != Vec2::ZERO {
+            let height_factor = controller.zoom.effective_height / EDGE_SCROLL_REFERENCE_HEIGHT;
+            offset += pan_direction(&transform, edge_direction.normalize()) * edge_scroll.speed * height_factor;
         }
 
-        if direction != Vec3::ZERO {
-            direction = direction.normalize();
-            let new_position = transform.translation + direction * controller.speed * time.delta_secs();
-            transform.translation = clamp_camera_position(new_position);
+        if offset != Vec3::ZERO {
+            let new_position = transform.translation + offset * time.delta_secs();
+            transform.translation = clamp_camera_position(new_position, &wrap);
         }
     }
 }
 
+fn pan_direction(transform: &Transform, input: Vec2) -> Vec3 {
+    let right = transform.right();
+    let forward = transform.forward();
+
+    let right_xz = Vec3::new(right.x, 0.0, right.z).normalize_or_zero();
+    let forward_xz = Vec3::new(forward.x, 0.0, forward.z).normalize_or_zero();
+
+    right_xz * input.x + forward_xz * input.y
+}
+
 fn camera_drag_movement(
     window: Query<&Window>,
-    mouse_input: Res<ButtonInput<MouseButton>>,
+    actions: Res<ActionState>,
+    wrap: Res<MapWrap>,
     mut drag_state: ResMut<CameraDragState>,
     mut query: Query<(&mut Transform, &GlobalTransform, &Camera)>,
 ) {
     let window = window.single();
     let (mut transform, global_transform, camera) = query.single_mut();
 
-    if mouse_input.just_pressed(MouseButton::Right) {
+    // Панорамирование начинается только после порога: короткий клик правой кнопкой — это приказ армиям
+    if actions.just_pressed(InputAction::DragPan) {
         if let Some(cursor_position) = window.cursor_position() {
             if let Ok(ray) = camera.viewport_to_world(global_transform, cursor_position) {
                 if let Some(world_position) = ray_intersect_plane(ray, Vec3::Y, 0.0) {
-                    drag_state.is_dragging = true;
+                    drag_state.press_screen_position = Some(cursor_position);
                     drag_state.drag_start_world_position = Some(world_position);
                 }
             }
         }
     }
 
-    if mouse_input.just_released(MouseButton::Right) {
+    if actions.just_released(InputAction::DragPan) {
         drag_state.is_dragging = false;
+        drag_state.press_screen_position = None;
         drag_state.drag_start_world_position = None;
     }
 
+    if !drag_state.is_dragging {
+        let moved = drag_state.press_screen_position
+            .zip(window.cursor_position())
+            .is_some_and(|(press, cursor)| press.distance(cursor) > CLICK_DRAG_THRESHOLD);
+        drag_state.is_dragging = moved;
+    }
+
     if drag_state.is_dragging {
         if let Some(cursor_position) = window.cursor_position() {
             if let Some(start_world_pos) = drag_state.drag_start_world_position {
@@ -222,7 +304,7 @@ fn camera_drag_movement(
 
                         let movement = Vec3::new(world_delta.x, 0.0, world_delta.z);
                         let new_position = transform.translation + movement;
-                        transform.translation = clamp_camera_position(new_position);
+                        transform.translation = clamp_camera_position(new_position, &wrap);
                     }
                 }
             }
@@ -245,10 +327,56 @@ fn get_cursor_world_delta(
     right_xz * delta.x + forward_xz * delta.y
 }
 
-fn clamp_camera_position(position: Vec3) -> Vec3 {
+pub(crate) fn clamp_camera_position(position: Vec3, wrap: &MapWrap) -> Vec3 {
+    let x = if wrap.enabled {
+        position.x
+    } else {
+        position.x.clamp(MAP_MIN_X, MAP_MAX_X)
+    };
+
     Vec3::new(
-        position.x.clamp(MAP_MIN_X, MAP_MAX_X),
+        x,
         position.y,
         position.z.clamp(MAP_MIN_Z, MAP_MAX_Z),
     )
 }
+
+// Камера всегда остаётся в каноническом диапазоне x, всё, что завязано на её позицию, сдвигается вместе с ней
+fn wrap_camera_position(
+    wrap: Res<MapWrap>,
+    mut drag_state: ResMut<CameraDragState>,
+    mut query: Query<(&mut Transform, &mut CameraCorners, Option<&mut CameraFlight>)>,
+) {
+    if !wrap.enabled {
+        return;
+    }
+
+    for (mut transform, mut corners, flight) in query.iter_mut() {
+        let canonical = wrap.canonical_position(transform.translation);
+        let shift = canonical.x - transform.translation.x;
+        if shift == 0.0 {
+            continue;
+        }
+
+        transform.translation = canonical;
+        corners.min_x += shift;
+        corners.max_x += shift;
+
+        if let Some(start) = drag_state.drag_start_world_position.as_mut() {
+            start.x += shift;
+        }
+
+        if let Some(mut flight) = flight {
+            flight.shift_x(shift);
+        }
+    }
+}
+
+fn update_wrap_view(
+    mut wrap: ResMut<MapWrap>,
+    query: Query<&CameraCorners>,
+) {
+    if let Ok(corners) = query.get_single() {
+        wrap.set_view_center_x((corners.min_x + corners.max_x) / 2.0);
+    }
+}
@@ -1,14 +1,20 @@
+use crate::core::input::{ActionState, InputAction};
 use crate::core::map::camera::{CameraController, CameraLodState};
+use crate::core::map::terrain::heightfield::Heightfield;
 use bevy::input::mouse::MouseWheel;
-use bevy::prelude::{EulerRot, EventReader, Quat, Query, Res, ResMut, Time, Transform};
+use bevy::prelude::{EulerRot, EventReader, Quat, Query, Res, ResMut, Time, Transform, Vec3};
 
-const MIN_HEIGHT: f32 = 60.0;
-const MAX_HEIGHT: f32 = 1300.0;
+pub(super) const MIN_HEIGHT: f32 = 60.0;
+pub(super) const MAX_HEIGHT: f32 = 1300.0;
 const MIN_TILT: f32 = -0.6;
 const MAX_TILT: f32 = -1.35;
+// Наименьший зазор между камерой и рельефом прямо под ней
+const MIN_CLEARANCE: f32 = 25.0;
 
 pub fn zoom_handler(
     time: Res<Time>,
+    actions: Res<ActionState>,
+    heightfield: Option<Res<Heightfield>>,
     mut mouse_wheel_events: EventReader<MouseWheel>,
     mut query: Query<(&mut CameraController, &mut Transform)>,
     mut lod_state: ResMut<CameraLodState>,
@@ -17,31 +23,58 @@ pub fn zoom_handler(
     for event in mouse_wheel_events.read() {
         scroll -= event.y;
     }
+    scroll += actions.value(InputAction::ZoomOut) - actions.value(InputAction::ZoomIn);
 
     for (mut controller, mut transform) in query.iter_mut() {
         controller.zoom.target_height -= scroll * controller.zoom.speed * time.delta_secs();
         controller.zoom.target_height = controller.zoom.target_height.clamp(MIN_HEIGHT, MAX_HEIGHT);
 
-        if controller.zoom.target_height == controller.zoom.current_height {
-            continue;
-        }
-
         controller.zoom.current_height = lerp(
             controller.zoom.current_height,
             controller.zoom.target_height,
             controller.zoom.smooth_factor
         );
 
-        transform.translation.y = controller.zoom.current_height;
+        // Высота камеры задаётся относительно рельефа под точкой фокуса
+        let mut focus_ground = controller.zoom.ground_height;
+        let mut under_camera = f32::MIN;
+        if let Some(heightfield) = heightfield.as_ref() {
+            let focus = focus_point(&transform, controller.zoom.ground_height);
+            focus_ground = heightfield.height_at(focus.x, focus.z);
+            under_camera = heightfield.height_at(transform.translation.x, transform.translation.z);
+
+            // Сглаживание по времени кадра, чтобы подъём над горами не зависел от FPS
+            let t = 1.0 - (-controller.zoom.ground_smooth_rate * time.delta_secs()).exp();
+            controller.zoom.ground_height = lerp(
+                controller.zoom.ground_height,
+                focus_ground.max(under_camera),
+                t
+            );
+        }
+
+        // Пока высота догоняет рельеф, камера не должна уходить под склон
+        transform.translation.y = (controller.zoom.ground_height + controller.zoom.current_height)
+            .max(under_camera + MIN_CLEARANCE);
+        controller.zoom.effective_height = transform.translation.y - focus_ground;
+
         let pitch_angle = height_to_tilt(controller.zoom.current_height);
         let (yaw, _, roll) = transform.rotation.to_euler(EulerRot::YXZ);
         transform.rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch_angle, roll);
-        lod_state.current_height = controller.zoom.current_height;
+        lod_state.current_height = controller.zoom.effective_height;
     }
 }
 
+pub(super) fn focus_point(transform: &Transform, ground_height: f32) -> Vec3 {
+    let forward = transform.forward();
+    if forward.y >= -f32::EPSILON {
+        return transform.translation;
+    }
+
+    let distance = (transform.translation.y - ground_height) / -forward.y;
+    transform.translation + forward * distance
+}
 
-fn height_to_tilt(height: f32) -> f32 {
+pub(super) fn height_to_tilt(height: f32) -> f32 {
     let clamped_height = height.clamp(MIN_HEIGHT, MAX_HEIGHT);
     let t = (clamped_height - MIN_HEIGHT) / (MAX_HEIGHT - MIN_HEIGHT);
     let transformed_t = 2.0 * t - t * t;
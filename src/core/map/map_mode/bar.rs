@@ -0,0 +1,88 @@
+use crate::core::map::map_mode::{ActiveMapMode, MapModes, SetMapMode};
+use bevy::color::Color;
+use bevy::prelude::{default, resource_changed, Alpha, App, BackgroundColor, BuildChildren, Button, Changed, ChildBuild, Commands, Component, EventWriter, FlexDirection, Interaction, IntoSystemConfigs, JustifyContent, Node, PositionType, Query, Res, Startup, Text, TextFont, UiRect, Update, Val};
+
+const BUTTON_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.15);
+const ACTIVE_BUTTON_COLOR: Color = Color::srgba(1.0, 0.85, 0.4, 0.45);
+
+#[derive(Component)]
+struct MapModeButton(String);
+
+pub fn build(app: &mut App) {
+    app.add_systems(Startup, init);
+    app.add_systems(Update, (
+        press_buttons.before(super::switch_map_mode),
+        highlight_active.after(super::switch_map_mode).run_if(resource_changed::<ActiveMapMode>),
+    ));
+}
+
+// Панель режимов по центру сверху: кнопка на каждый зарегистрированный режим в порядке регистрации
+fn init(mut commands: Commands, modes: Res<MapModes>) {
+    commands
+        .spawn(Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        })
+        .with_children(|p| {
+            p.spawn((
+                Node {
+                    flex_direction: FlexDirection::Row,
+                    column_gap: Val::Px(4.0),
+                    padding: UiRect::axes(Val::Px(6.0), Val::Px(4.0)),
+                    ..default()
+                },
+                BackgroundColor(Color::BLACK.with_alpha(0.55)),
+                Interaction::default(),
+            ))
+            .with_children(|bar| {
+                for mode in modes.iter() {
+                    let label = match mode.hotkey {
+                        Some(slot) => format!("{} {}", slot, mode.name),
+                        None => mode.name.clone(),
+                    };
+
+                    bar.spawn((
+                        Button,
+                        Node {
+                            padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+                            ..default()
+                        },
+                        BackgroundColor(BUTTON_COLOR),
+                        MapModeButton(mode.id.clone()),
+                    ))
+                    .with_children(|b| {
+                        b.spawn((
+                            Text::new(label),
+                            TextFont {
+                                font_size: 14.0,
+                                ..default()
+                            },
+                        ));
+                    });
+                }
+            });
+        });
+}
+
+fn press_buttons(
+    buttons: Query<(&Interaction, &MapModeButton), Changed<Interaction>>,
+    mut events: EventWriter<SetMapMode>,
+) {
+    for (interaction, button) in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            events.send(SetMapMode(button.0.clone()));
+        }
+    }
+}
+
+fn highlight_active(
+    active: Res<ActiveMapMode>,
+    mut buttons: Query<(&MapModeButton, &mut BackgroundColor)>,
+) {
+    for (button, mut color) in buttons.iter_mut() {
+        color.0 = if button.0 == active.id { ACTIVE_BUTTON_COLOR } else { BUTTON_COLOR };
+    }
+}
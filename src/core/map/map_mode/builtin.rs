@@ -0,0 +1,54 @@
+use crate::core::country::CountryRegistry;
+use crate::core::map::map_mode::{Gradient, MapMode, MapModeColoring, RegisterMapMode};
+use bevy::color::Color;
+use bevy::prelude::App;
+
+pub const TERRAIN: &str = "terrain";
+pub const POLITICAL: &str = "political";
+pub const HEIGHT: &str = "height";
+pub const PROVINCES: &str = "provinces";
+
+pub fn register(app: &mut App) {
+    app.register_map_mode(MapMode {
+        id: TERRAIN.to_string(),
+        name: "Рельеф".to_string(),
+        hotkey: Some(1),
+        coloring: MapModeColoring::Terrain,
+    });
+
+    app.register_map_mode(MapMode {
+        id: POLITICAL.to_string(),
+        name: "Политическая".to_string(),
+        hotkey: Some(2),
+        coloring: MapModeColoring::province(|world, province| {
+            let countries = world.get_resource::<CountryRegistry>()?;
+            let owner = countries.owner_of(province.id).and_then(|id| countries.get(id))?;
+            let [r, g, b] = owner.color;
+            Some(Color::srgb_u8(r, g, b))
+        }),
+    });
+
+    app.register_map_mode(MapMode {
+        id: HEIGHT.to_string(),
+        name: "Высоты".to_string(),
+        hotkey: Some(3),
+        coloring: MapModeColoring::numeric(
+            |_, province| (!province.is_sea).then_some(province.terrain.mean_height),
+            Gradient {
+                low: Color::srgb(0.2, 0.45, 0.2),
+                high: Color::srgb(0.95, 0.95, 0.9),
+            },
+        ),
+    });
+
+    // Цвета-ключи из provinces.png: помогает сверять карту провинций с её описанием
+    app.register_map_mode(MapMode {
+        id: PROVINCES.to_string(),
+        name: "Провинции".to_string(),
+        hotkey: Some(7),
+        coloring: MapModeColoring::province(|_, province| {
+            let [r, g, b] = province.color;
+            Some(Color::srgb_u8(r, g, b))
+        }),
+    });
+}
@@ -0,0 +1,240 @@
+mod bar;
+mod builtin;
+
+use crate::core::country::ProvinceOwnerChanged;
+use crate::core::input::{ActionState, InputAction};
+use crate::core::map::province::{Province, ProvinceRegistry};
+use crate::core::map::terrain::material::{province_color_image, TerrainMaterial, TerrainMaterialHandle};
+use crate::core::simulation::run_simulation;
+use bevy::color::{Alpha, Color, ColorToPacked, Mix};
+use bevy::prelude::{on_event, resource_changed, App, Assets, Condition, Event, EventReader, EventWriter, Image, IntoSystemConfigs, Mut, Res, ResMut, Resource, Update, World};
+use std::collections::HashMap;
+
+pub const MAP_MODE_HOTKEYS: u8 = 9;
+
+// Ширина таблицы цветов: id провинции раскладывается по строкам, чтобы не упираться в лимит ширины текстуры
+pub const LOOKUP_WIDTH: usize = 256;
+const OVERLAY_OPACITY: f32 = 0.85;
+
+pub type ProvinceColorFn = Box<dyn Fn(&World, &Province) -> Option<Color> + Send + Sync>;
+pub type ProvinceValueFn = Box<dyn Fn(&World, &Province) -> Option<f32> + Send + Sync>;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Gradient {
+    pub low: Color,
+    pub high: Color,
+}
+
+impl Gradient {
+    pub fn sample(&self, t: f32) -> Color {
+        self.low.mix(&self.high, t.clamp(0.0, 1.0))
+    }
+}
+
+pub enum MapModeColoring {
+    // Чистый рельеф без наложения
+    Terrain,
+    Province(ProvinceColorFn),
+    // Числовое поле провинции, растянутое градиентом между минимумом и максимумом по карте
+    Numeric { value: ProvinceValueFn, gradient: Gradient },
+}
+
+impl MapModeColoring {
+    pub fn province(color: impl Fn(&World, &Province) -> Option<Color> + Send + Sync + 'static) -> Self {
+        MapModeColoring::Province(Box::new(color))
+    }
+
+    pub fn numeric(value: impl Fn(&World, &Province) -> Option<f32> + Send + Sync + 'static, gradient: Gradient) -> Self {
+        MapModeColoring::Numeric { value: Box::new(value), gradient }
+    }
+
+    fn province_colors(&self, world: &World, registry: &ProvinceRegistry) -> HashMap<u16, Color> {
+        match self {
+            MapModeColoring::Terrain => HashMap::new(),
+            MapModeColoring::Province(color) => registry.iter()
+                .filter_map(|province| color(world, province).map(|c| (province.id.0, c)))
+                .collect(),
+            MapModeColoring::Numeric { value, gradient } => {
+                let values: Vec<(u16, f32)> = registry.iter()
+                    .filter_map(|province| value(world, province).map(|v| (province.id.0, v)))
+                    .collect();
+
+                let min = values.iter().map(|(_, v)| *v).fold(f32::INFINITY, f32::min);
+                let max = values.iter().map(|(_, v)| *v).fold(f32::NEG_INFINITY, f32::max);
+                let range = (max - min).max(f32::EPSILON);
+
+                values.into_iter()
+                    .map(|(id, v)| (id, gradient.sample((v - min) / range)))
+                    .collect()
+            }
+        }
+    }
+}
+
+pub struct MapMode {
+    pub id: String,
+    pub name: String,
+    // Номер слота горячей клавиши (1..=9), см. InputAction::MapMode
+    pub hotkey: Option<u8>,
+    pub coloring: MapModeColoring,
+}
+
+#[derive(Resource, Default)]
+pub struct MapModes {
+    modes: Vec<MapMode>,
+    index: HashMap<String, usize>,
+}
+
+impl MapModes {
+    // Режим с уже существующим id заменяется, так моды могут переопределять встроенные режимы
+    pub fn register(&mut self, mode: MapMode) {
+        match self.index.get(&mode.id) {
+            Some(&i) => self.modes[i] = mode,
+            None => {
+                self.index.insert(mode.id.clone(), self.modes.len());
+                self.modes.push(mode);
+            }
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&MapMode> {
+        self.index.get(id).map(|&i| &self.modes[i])
+    }
+
+    pub fn by_hotkey(&self, slot: u8) -> Option<&MapMode> {
+        self.modes.iter().find(|mode| mode.hotkey == Some(slot))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &MapMode> {
+        self.modes.iter()
+    }
+}
+
+pub trait RegisterMapMode {
+    fn register_map_mode(&mut self, mode: MapMode) -> &mut Self;
+}
+
+impl RegisterMapMode for App {
+    fn register_map_mode(&mut self, mode: MapMode) -> &mut Self {
+        self.init_resource::<MapModes>();
+        self.world_mut().resource_mut::<MapModes>().register(mode);
+        self
+    }
+}
+
+#[derive(Resource)]
+pub struct ActiveMapMode {
+    pub id: String,
+}
+
+// Итоговые цвета провинций в текущем режиме (sRGBA), по ним же перерисовывается миникарта
+#[derive(Resource, Default)]
+pub struct MapModeLookup {
+    pub colors: Vec<[u8; 4]>,
+}
+
+impl MapModeLookup {
+    pub fn color(&self, id: u16) -> [u8; 4] {
+        self.colors.get(id as usize).copied().unwrap_or([0; 4])
+    }
+}
+
+#[derive(Event)]
+pub struct SetMapMode(pub String);
+
+// Данные, от которых зависит текущий режим, изменились и таблицу нужно пересчитать
+#[derive(Event, Default)]
+pub struct RefreshMapMode;
+
+pub fn build(app: &mut App) {
+    app.init_resource::<MapModes>();
+    app.init_resource::<MapModeLookup>();
+    app.insert_resource(ActiveMapMode { id: builtin::TERRAIN.to_string() });
+    app.add_event::<SetMapMode>();
+    app.add_event::<RefreshMapMode>();
+
+    builtin::register(app);
+    bar::build(app);
+
+    app.add_systems(Update, (
+        refresh_on_ownership_change.after(run_simulation),
+        map_mode_hotkeys,
+        switch_map_mode,
+        rebuild_map_mode_lookup.run_if(resource_changed::<ActiveMapMode>.or(on_event::<RefreshMapMode>)),
+    ).chain());
+}
+
+fn refresh_on_ownership_change(
+    mut changes: EventReader<ProvinceOwnerChanged>,
+    mut refresh: EventWriter<RefreshMapMode>,
+) {
+    if changes.read().count() > 0 {
+        refresh.send(RefreshMapMode);
+    }
+}
+
+fn map_mode_hotkeys(
+    actions: Res<ActionState>,
+    modes: Res<MapModes>,
+    mut events: EventWriter<SetMapMode>,
+) {
+    for slot in 1..=MAP_MODE_HOTKEYS {
+        if actions.just_pressed(InputAction::MapMode(slot)) {
+            if let Some(mode) = modes.by_hotkey(slot) {
+                events.send(SetMapMode(mode.id.clone()));
+            }
+        }
+    }
+}
+
+fn switch_map_mode(
+    modes: Res<MapModes>,
+    mut events: EventReader<SetMapMode>,
+    mut active: ResMut<ActiveMapMode>,
+) {
+    for SetMapMode(id) in events.read() {
+        if modes.get(id).is_none() {
+            eprintln!("Неизвестный режим карты: {}", id);
+            continue;
+        }
+
+        if active.id != *id {
+            #[cfg(debug_assertions)]
+            println!("Режим карты: {}", id);
+
+            active.id = id.clone();
+        }
+    }
+}
+
+// Эксклюзивная система: функции раскраски режимов получают доступ ко всему миру
+fn rebuild_map_mode_lookup(world: &mut World) {
+    let colors = world.resource_scope(|world, modes: Mut<MapModes>| {
+        let active = world.resource::<ActiveMapMode>();
+        let registry = world.resource::<ProvinceRegistry>();
+        modes.get(&active.id).map(|mode| mode.coloring.province_colors(world, registry))
+    });
+
+    let Some(colors) = colors else {
+        return;
+    };
+
+    let max_id = world.resource::<ProvinceRegistry>().iter().map(|p| p.id.0 as usize).max().unwrap_or(0);
+    let rows = max_id / LOOKUP_WIDTH + 1;
+
+    let mut table = vec![[0u8; 4]; rows * LOOKUP_WIDTH];
+    for (id, color) in colors {
+        table[id as usize] = color.with_alpha(OVERLAY_OPACITY).to_srgba().to_u8_array();
+    }
+
+    let image = province_color_image(LOOKUP_WIDTH as u32, rows as u32, table.concat());
+    let image = world.resource_mut::<Assets<Image>>().add(image);
+
+    if let Some(handle) = world.get_resource::<TerrainMaterialHandle>().map(|h| h.0.clone()) {
+        if let Some(material) = world.resource_mut::<Assets<TerrainMaterial>>().get_mut(&handle) {
+            material.extension.province_colors = image;
+        }
+    }
+
+    world.resource_mut::<MapModeLookup>().colors = table;
+}
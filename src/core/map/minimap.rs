@@ -0,0 +1,196 @@
+use crate::core::map::camera::fly_to::CameraFlyTo;
+use crate::core::map::camera::CameraCorners;
+use crate::core::map::map_mode::MapModeLookup;
+use crate::core::map::province::ProvinceMap;
+use crate::core::map::sea::SEA_LEVEL;
+use crate::core::map::terrain::generate_terrain;
+use crate::core::map::terrain::heightfield::Heightfield;
+use crate::core::map::wrap::MapWrap;
+use crate::core::map::{MAP_DEPTH, MAP_WIDTH};
+use bevy::asset::RenderAssetUsages;
+use bevy::color::{Alpha, Color, ColorToPacked, Mix, Srgba};
+use bevy::math::Vec2;
+use bevy::prelude::{default, resource_changed, App, Assets, BackgroundColor, BorderColor, BuildChildren, ButtonInput, ChildBuild, Commands, Component, EventWriter, Image, ImageNode, Interaction, IntoSystemConfigs, MouseButton, Node, PositionType, Query, Res, ResMut, Resource, Startup, Update, UiRect, Val, With, Without};
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::ui::RelativeCursorPosition;
+
+const MINIMAP_WIDTH: u32 = 256;
+const MINIMAP_HEIGHT: u32 = 128;
+
+#[derive(Component)]
+pub(crate) struct Minimap;
+
+#[derive(Component)]
+struct MinimapViewport;
+
+#[derive(Resource, Default)]
+struct MinimapDragState {
+    is_dragging: bool,
+}
+
+pub fn build(app: &mut App) {
+    app.init_resource::<MinimapDragState>();
+    app.add_systems(Startup, init.after(generate_terrain));
+    app.add_systems(Update, (minimap_input, update_viewport_frame));
+    app.add_systems(Update, update_minimap_overlay.run_if(resource_changed::<MapModeLookup>));
+}
+
+fn init(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    heightfield: Res<Heightfield>,
+) {
+    let image = images.add(render_minimap(&heightfield, None));
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(8.0),
+                bottom: Val::Px(8.0),
+                width: Val::Px(MINIMAP_WIDTH as f32),
+                height: Val::Px(MINIMAP_HEIGHT as f32),
+                border: UiRect::all(Val::Px(2.0)),
+                overflow: bevy::ui::Overflow::clip(),
+                ..default()
+            },
+            BorderColor(Color::BLACK),
+            ImageNode::new(image),
+            Interaction::default(),
+            RelativeCursorPosition::default(),
+            Minimap,
+        ))
+        .with_children(|p| {
+            p.spawn((
+                Node {
+                    position_type: PositionType::Absolute,
+                    border: UiRect::all(Val::Px(1.0)),
+                    ..default()
+                },
+                BorderColor(Color::WHITE),
+                BackgroundColor(Color::NONE),
+                MinimapViewport,
+            ));
+        });
+}
+
+fn update_minimap_overlay(
+    heightfield: Res<Heightfield>,
+    map: Option<Res<ProvinceMap>>,
+    lookup: Res<MapModeLookup>,
+    minimap: Query<&ImageNode, With<Minimap>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let Ok(node) = minimap.get_single() else {
+        return;
+    };
+
+    let overlay = map.as_deref().map(|map| (map, &*lookup));
+    images.insert(&node.image, render_minimap(&heightfield, overlay));
+}
+
+fn render_minimap(heightfield: &Heightfield, overlay: Option<(&ProvinceMap, &MapModeLookup)>) -> Image {
+    let mut data = Vec::with_capacity((MINIMAP_WIDTH * MINIMAP_HEIGHT * 4) as usize);
+    let step_x = MAP_WIDTH / MINIMAP_WIDTH as f32;
+    let step_z = MAP_DEPTH / MINIMAP_HEIGHT as f32;
+
+    for y in 0..MINIMAP_HEIGHT {
+        for x in 0..MINIMAP_WIDTH {
+            let (world_x, world_z) = ((x as f32 + 0.5) * step_x, (y as f32 + 0.5) * step_z);
+            let mut color = height_color(heightfield.height_at(world_x, world_z));
+
+            if let Some((map, lookup)) = overlay {
+                if let Some(province) = map.province_at(world_x, world_z) {
+                    let overlay = Srgba::from_u8_array(lookup.color(province.0));
+                    color = color.mix(&Color::from(overlay.with_alpha(1.0)), overlay.alpha);
+                }
+            }
+
+            data.extend_from_slice(&color.to_srgba().to_u8_array());
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: MINIMAP_WIDTH,
+            height: MINIMAP_HEIGHT,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+fn height_color(height: f32) -> Color {
+    if height < SEA_LEVEL {
+        let depth = (height / SEA_LEVEL).clamp(0.0, 1.0);
+        return Color::srgb(0.05 + 0.1 * depth, 0.15 + 0.2 * depth, 0.45 + 0.25 * depth);
+    }
+
+    let t = ((height - SEA_LEVEL) / 80.0).clamp(0.0, 1.0);
+    if t < 0.5 {
+        let k = t * 2.0;
+        Color::srgb(0.3 + 0.3 * k, 0.5 - 0.05 * k, 0.3 - 0.05 * k)
+    } else {
+        let k = (t - 0.5) * 2.0;
+        Color::srgb(0.6 + 0.35 * k, 0.45 + 0.5 * k, 0.25 + 0.7 * k)
+    }
+}
+
+fn minimap_input(
+    mouse: Res<ButtonInput<MouseButton>>,
+    minimap: Query<&RelativeCursorPosition, With<Minimap>>,
+    mut drag_state: ResMut<MinimapDragState>,
+    mut fly_to: EventWriter<CameraFlyTo>,
+) {
+    let Ok(cursor) = minimap.get_single() else {
+        return;
+    };
+
+    if mouse.just_pressed(MouseButton::Left) && cursor.mouse_over() {
+        drag_state.is_dragging = true;
+        if let Some(position) = cursor.normalized {
+            fly_to.send(CameraFlyTo::new(minimap_to_world(position)).with_duration(0.4));
+        }
+        return;
+    }
+
+    if !mouse.pressed(MouseButton::Left) {
+        drag_state.is_dragging = false;
+        return;
+    }
+
+    if drag_state.is_dragging {
+        if let Some(position) = cursor.normalized {
+            let position = position.clamp(Vec2::ZERO, Vec2::ONE);
+            fly_to.send(CameraFlyTo::new(minimap_to_world(position)).with_duration(0.0));
+        }
+    }
+}
+
+fn minimap_to_world(position: Vec2) -> Vec2 {
+    Vec2::new(position.x * MAP_WIDTH, position.y * MAP_DEPTH)
+}
+
+fn update_viewport_frame(
+    wrap: Res<MapWrap>,
+    corners: Query<&CameraCorners>,
+    mut frame: Query<&mut Node, (With<MinimapViewport>, Without<Minimap>)>,
+) {
+    let (Ok(corners), Ok(mut node)) = (corners.get_single(), frame.get_single_mut()) else {
+        return;
+    };
+
+    let scale_x = MINIMAP_WIDTH as f32 / MAP_WIDTH;
+    let scale_z = MINIMAP_HEIGHT as f32 / MAP_DEPTH;
+
+    let min_x = wrap.canonical_x(corners.min_x);
+    let width = corners.max_x - corners.min_x;
+
+    node.left = Val::Px(min_x * scale_x);
+    node.top = Val::Px(corners.min_z * scale_z);
+    node.width = Val::Px(width * scale_x);
+    node.height = Val::Px((corners.max_z - corners.min_z) * scale_z);
+}
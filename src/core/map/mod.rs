@@ -1,19 +1,37 @@
-mod camera;
-mod sea;
+pub(crate) mod camera;
+pub(crate) mod sea;
 mod light;
 pub(crate) mod terrain;
 pub(crate) mod components;
+pub(crate) mod wrap;
+mod minimap;
+pub(crate) mod province;
+pub(crate) mod borders;
+pub(crate) mod picking;
+pub(crate) mod map_mode;
 
 use crate::core::map::terrain::generate_terrain;
+use crate::core::map::wrap::MapWrap;
+use crate::core::settings::UserSettings;
 use bevy::app::{App, Plugin, Startup};
 
+pub const MAP_WIDTH: f32 = 8192.0;
+pub const MAP_DEPTH: f32 = 4096.0;
+
 pub struct MapPlugin;
 impl Plugin for MapPlugin {
     fn build(&self, app: &mut App) {
+        let wrap = app.world().get_resource::<UserSettings>().is_none_or(|settings| settings.map.wrap);
+        app.insert_resource(MapWrap::new(wrap));
         terrain::build(app);
         camera::build(app);
         sea::build(app);
         light::build(app);
+        minimap::build(app);
+        picking::build(app);
+        map_mode::build(app);
+        province::build(app);
+        borders::build(app);
         terrain::mesh_pool::build(app);
 
         app.add_systems(Startup, generate_terrain);
@@ -0,0 +1,93 @@
+use crate::core::map::terrain::heightfield::Heightfield;
+use crate::core::map::wrap::MapWrap;
+use crate::core::map::{MAP_DEPTH, MAP_WIDTH};
+use bevy::math::{Ray3d, Vec2, Vec3};
+use bevy::prelude::{App, Camera, GlobalTransform, Interaction, Query, Res, ResMut, Resource, Update, Window};
+
+const RAYCAST_STEP: f32 = 1.0;
+const RAYCAST_REFINE_STEPS: usize = 8;
+
+// Точка рельефа под курсором в канонических координатах (x уже приведён к диапазону карты)
+#[derive(Resource, Default)]
+pub struct TerrainCursor {
+    pub screen_position: Option<Vec2>,
+    pub world_position: Option<Vec3>,
+    pub over_ui: bool,
+}
+
+pub fn build(app: &mut App) {
+    app.init_resource::<TerrainCursor>();
+    app.add_systems(Update, update_terrain_cursor);
+}
+
+pub fn update_terrain_cursor(
+    heightfield: Option<Res<Heightfield>>,
+    wrap: Res<MapWrap>,
+    window: Query<&Window>,
+    camera: Query<(&Camera, &GlobalTransform)>,
+    interactions: Query<&Interaction>,
+    mut cursor: ResMut<TerrainCursor>,
+) {
+    cursor.screen_position = window.get_single().ok().and_then(|window| window.cursor_position());
+    cursor.over_ui = interactions.iter().any(|interaction| *interaction != Interaction::None);
+    cursor.world_position = None;
+
+    let (Some(heightfield), Some(screen_position)) = (heightfield, cursor.screen_position) else {
+        return;
+    };
+
+    if cursor.over_ui {
+        return;
+    }
+
+    let Ok((camera, camera_transform)) = camera.get_single() else {
+        return;
+    };
+
+    if let Ok(ray) = camera.viewport_to_world(camera_transform, screen_position) {
+        cursor.world_position = raycast_terrain(ray, &heightfield, &wrap);
+    }
+}
+
+// Шагаем по лучу от уровня самой высокой точки рельефа до нулевой высоты, затем уточняем бисекцией
+pub fn raycast_terrain(ray: Ray3d, heightfield: &Heightfield, wrap: &MapWrap) -> Option<Vec3> {
+    let direction_y = ray.direction.y;
+    if direction_y >= 0.0 {
+        return None;
+    }
+
+    let above_ground = |t: f32| {
+        let point = ray.get_point(t);
+        point.y > heightfield.height_at(wrap.canonical_x(point.x), point.z)
+    };
+
+    let start = ((heightfield.max_height() - ray.origin.y) / direction_y).max(0.0);
+    let end = (-ray.origin.y / direction_y).max(start);
+
+    let mut previous = start;
+    let mut t = start;
+    loop {
+        if !above_ground(t) {
+            break;
+        }
+        if t >= end {
+            return None;
+        }
+        previous = t;
+        t = (t + RAYCAST_STEP).min(end);
+    }
+
+    let (mut low, mut high) = (previous, t);
+    for _ in 0..RAYCAST_REFINE_STEPS {
+        let middle = (low + high) / 2.0;
+        if above_ground(middle) {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    let hit = wrap.canonical_position(ray.get_point(high));
+    let inside = (0.0..MAP_WIDTH).contains(&hit.x) && (0.0..MAP_DEPTH).contains(&hit.z);
+    inside.then_some(hit)
+}
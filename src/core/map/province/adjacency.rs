@@ -0,0 +1,216 @@
+use crate::core::map::province::{ProvinceId, ProvinceMap, ProvinceRegistry};
+use crate::core::map::wrap::MapWrap;
+use crate::pkg::dir::{cache_directory, init_dir};
+use bevy::prelude::{Commands, Res, Resource};
+use bincode::{Decode, Encode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdjacencyKind {
+    Land,
+    Sea,
+    Coastal,
+    Strait,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Adjacency {
+    pub neighbour: ProvinceId,
+    pub kind: AdjacencyKind,
+    pub border_length: f32,
+    // Граница проходит по реке: атакующий через неё получает штраф в бою
+    pub river: bool,
+}
+
+#[derive(Resource, Default)]
+pub struct ProvinceGraph {
+    adjacency: HashMap<ProvinceId, Vec<Adjacency>>,
+}
+
+impl ProvinceGraph {
+    pub fn neighbours(&self, id: ProvinceId) -> &[Adjacency] {
+        self.adjacency.get(&id).map(|list| list.as_slice()).unwrap_or(&[])
+    }
+
+    pub fn edge(&self, from: ProvinceId, to: ProvinceId) -> Option<&Adjacency> {
+        self.neighbours(from).iter().find(|a| a.neighbour == to)
+    }
+
+    pub fn is_river_crossing(&self, from: ProvinceId, to: ProvinceId) -> bool {
+        self.edge(from, to).is_some_and(|a| a.river)
+    }
+
+    fn connect(&mut self, a: ProvinceId, b: ProvinceId, kind: AdjacencyKind, border_length: f32) {
+        self.adjacency.entry(a).or_default().push(Adjacency { neighbour: b, kind, border_length, river: false });
+        self.adjacency.entry(b).or_default().push(Adjacency { neighbour: a, kind, border_length, river: false });
+    }
+
+    fn mark_river(&mut self, a: ProvinceId, b: ProvinceId) -> bool {
+        let mut found = false;
+        for (from, to) in [(a, b), (b, a)] {
+            if let Some(edge) = self.adjacency.get_mut(&from).and_then(|list| list.iter_mut().find(|e| e.neighbour == to)) {
+                edge.river = true;
+                found = true;
+            }
+        }
+        found
+    }
+}
+
+#[derive(Deserialize)]
+struct StraitDefinitions {
+    straits: Vec<ProvincePair>,
+}
+
+#[derive(Deserialize)]
+struct RiverDefinitions {
+    rivers: Vec<ProvincePair>,
+}
+
+#[derive(Deserialize)]
+struct ProvincePair {
+    from: u16,
+    to: u16,
+}
+
+// Кэшируются только сырые длины границ в пикселях, классификация зависит от реестра и считается заново
+#[derive(Serialize, Deserialize, Encode, Decode)]
+struct BorderCounts {
+    pairs: Vec<(u16, u16, u32)>,
+}
+
+pub fn build_province_graph(
+    mut commands: Commands,
+    map: Res<ProvinceMap>,
+    registry: Res<ProvinceRegistry>,
+    wrap: Res<MapWrap>,
+) {
+    let cache_path = adjacency_cache(&map.source_hash, wrap.enabled);
+
+    let counts = match load_border_counts(&cache_path) {
+        Ok(counts) => counts,
+        Err(_) => {
+            let counts = extract_border_counts(&map, wrap.enabled);
+            if let Err(e) = save_border_counts(&counts, &cache_path) {
+                println!("Не удалось сохранить граф смежности провинций в кэш: {}", e);
+            }
+            counts
+        }
+    };
+
+    let pixel_size = map.pixel_to_world(1.0, 1.0);
+    let border_unit = (pixel_size.x + pixel_size.y) / 2.0;
+
+    let mut graph = ProvinceGraph::default();
+    for (a, b, count) in counts.pairs {
+        let (a, b) = (ProvinceId(a), ProvinceId(b));
+        let a_sea = registry.get(a).is_some_and(|p| p.is_sea);
+        let b_sea = registry.get(b).is_some_and(|p| p.is_sea);
+
+        let kind = match (a_sea, b_sea) {
+            (false, false) => AdjacencyKind::Land,
+            (true, true) => AdjacencyKind::Sea,
+            _ => AdjacencyKind::Coastal,
+        };
+
+        graph.connect(a, b, kind, count as f32 * border_unit);
+    }
+
+    let straits = load_pairs::<StraitDefinitions>("common/map/straits.ron").map(|d| d.straits);
+    for strait in straits.unwrap_or_default() {
+        let (a, b) = (ProvinceId(strait.from), ProvinceId(strait.to));
+        if registry.get(a).is_none() || registry.get(b).is_none() {
+            eprintln!("Пролив между неизвестными провинциями {} и {}", strait.from, strait.to);
+            continue;
+        }
+        graph.connect(a, b, AdjacencyKind::Strait, 0.0);
+    }
+
+    let rivers = load_pairs::<RiverDefinitions>("common/map/rivers.ron").map(|d| d.rivers);
+    for river in rivers.unwrap_or_default() {
+        if !graph.mark_river(ProvinceId(river.from), ProvinceId(river.to)) {
+            eprintln!("Река между несмежными провинциями {} и {}", river.from, river.to);
+        }
+    }
+
+    commands.insert_resource(graph);
+}
+
+fn extract_border_counts(map: &ProvinceMap, wrap: bool) -> BorderCounts {
+    let mut counts: HashMap<(u16, u16), u32> = HashMap::new();
+    let width = map.width();
+    let height = map.height();
+    let ids = map.raw_ids();
+
+    let mut count_pair = |a: u16, b: u16| {
+        if a != b && a != 0 && b != 0 {
+            *counts.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+        }
+    };
+
+    for y in 0..height {
+        let row = (y * width) as usize;
+        for x in 0..width {
+            let id = ids[row + x as usize];
+
+            if x + 1 < width {
+                count_pair(id, ids[row + x as usize + 1]);
+            } else if wrap {
+                count_pair(id, ids[row]);
+            }
+
+            if y + 1 < height {
+                count_pair(id, ids[row + (width + x) as usize]);
+            }
+        }
+    }
+
+    let mut pairs: Vec<(u16, u16, u32)> = counts.into_iter().map(|((a, b), c)| (a, b, c)).collect();
+    pairs.sort();
+
+    BorderCounts { pairs }
+}
+
+fn load_pairs<T: DeserializeOwned>(path: &str) -> Option<T> {
+    let content = fs::read_to_string(path).ok()?;
+
+    match ron::from_str::<T>(&content) {
+        Ok(definitions) => Some(definitions),
+        Err(e) => {
+            eprintln!("Не удалось разобрать {}: {}", path, e);
+            None
+        }
+    }
+}
+
+pub fn province_cache_dir() -> PathBuf {
+    cache_directory().join("provinces")
+}
+
+fn adjacency_cache(source_hash: &str, wrap: bool) -> PathBuf {
+    let suffix = if wrap { "wrap" } else { "flat" };
+    province_cache_dir().join(format!("adjacency_{}_{}.bin", source_hash, suffix))
+}
+
+fn load_border_counts(path: &PathBuf) -> std::io::Result<BorderCounts> {
+    let buffer = fs::read(path)?;
+
+    let config = bincode::config::standard();
+    let (counts, _) = bincode::decode_from_slice::<BorderCounts, _>(&buffer, config)
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+
+    Ok(counts)
+}
+
+fn save_border_counts(counts: &BorderCounts, path: &PathBuf) -> std::io::Result<()> {
+    init_dir(province_cache_dir())?;
+
+    let config = bincode::config::standard();
+    let encoded = bincode::encode_to_vec(counts, config)
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+    fs::write(path, encoded)
+}
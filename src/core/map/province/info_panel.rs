@@ -0,0 +1,174 @@
+use crate::core::map::camera::fly_to::CameraFlyTo;
+use crate::core::map::province::adjacency::{AdjacencyKind, ProvinceGraph};
+use crate::core::map::province::selection::{ProvinceClicked, SelectedProvince};
+use crate::core::map::province::{ProvinceId, ProvinceRegistry};
+use bevy::color::Color;
+use bevy::prelude::{default, resource_changed, Alpha, App, BackgroundColor, BuildChildren, Button, Changed, ChildBuild, Commands, Component, Condition, DetectChanges, EventReader, EventWriter, FlexDirection, Interaction, IntoSystemConfigs, Node, PositionType, Query, Res, ResMut, Resource, Single, Startup, Text, TextFont, UiRect, Update, Val, Visibility, With};
+use std::fmt::Write;
+
+// Высота камеры при перелёте — во столько раз больше наибольшей стороны провинции
+const FLY_HEIGHT_PER_EXTENT: f32 = 1.5;
+
+#[derive(Component)]
+struct ProvinceInfoPanel;
+
+#[derive(Component)]
+struct ProvinceInfoText;
+
+#[derive(Component, Clone, Copy)]
+enum ProvinceInfoButton {
+    Close,
+    FlyTo,
+}
+
+// Панель открывается щелчком по провинции и может быть закрыта, не снимая выделения
+#[derive(Resource, Default)]
+pub struct ProvinceInfo {
+    pub province: Option<ProvinceId>,
+}
+
+pub fn build(app: &mut App) {
+    app.init_resource::<ProvinceInfo>();
+    app.add_systems(Startup, init);
+    app.add_systems(Update, (
+        open_on_click,
+        press_buttons,
+        update_panel.run_if(resource_changed::<ProvinceInfo>.or(resource_changed::<ProvinceGraph>)),
+    ).chain());
+}
+
+fn init(mut commands: Commands) {
+    let buttons = [
+        (ProvinceInfoButton::FlyTo, "Показать на карте"),
+        (ProvinceInfoButton::Close, "Закрыть"),
+    ];
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(8.0),
+                left: Val::Px(8.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                ..default()
+            },
+            BackgroundColor(Color::BLACK.with_alpha(0.55)),
+            Interaction::default(),
+            Visibility::Hidden,
+            ProvinceInfoPanel,
+        ))
+        .with_children(|p| {
+            p.spawn((
+                Text::default(),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                ProvinceInfoText,
+            ));
+
+            for (button, label) in buttons {
+                p.spawn((
+                    Button,
+                    Node {
+                        padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+                        ..default()
+                    },
+                    BackgroundColor(Color::WHITE.with_alpha(0.15)),
+                    button,
+                ))
+                .with_children(|b| {
+                    b.spawn((
+                        Text::new(label),
+                        TextFont {
+                            font_size: 14.0,
+                            ..default()
+                        },
+                    ));
+                });
+            }
+        });
+}
+
+fn open_on_click(
+    selected: Res<SelectedProvince>,
+    mut clicks: EventReader<ProvinceClicked>,
+    mut info: ResMut<ProvinceInfo>,
+) {
+    if let Some(click) = clicks.read().last() {
+        info.province = Some(click.province);
+    } else if selected.is_changed() && selected.0.is_none() && info.province.is_some() {
+        info.province = None;
+    }
+}
+
+fn press_buttons(
+    registry: Res<ProvinceRegistry>,
+    buttons: Query<(&Interaction, &ProvinceInfoButton), Changed<Interaction>>,
+    mut info: ResMut<ProvinceInfo>,
+    mut fly: EventWriter<CameraFlyTo>,
+) {
+    for (interaction, button) in buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            ProvinceInfoButton::Close => info.province = None,
+            ProvinceInfoButton::FlyTo => {
+                let Some(province) = info.province.and_then(|id| registry.get(id)) else {
+                    continue;
+                };
+                let extent = province.bounds.size().max_element();
+                fly.send(CameraFlyTo::new(province.bounds.center()).with_height(extent * FLY_HEIGHT_PER_EXTENT));
+            }
+        }
+    }
+}
+
+fn update_panel(
+    info: Res<ProvinceInfo>,
+    registry: Res<ProvinceRegistry>,
+    graph: Res<ProvinceGraph>,
+    mut panel: Single<&mut Visibility, With<ProvinceInfoPanel>>,
+    mut text: Single<&mut Text, With<ProvinceInfoText>>,
+) {
+    let Some(province) = info.province.and_then(|id| registry.get(id)) else {
+        **panel = Visibility::Hidden;
+        return;
+    };
+    **panel = Visibility::Inherited;
+
+    let terrain = &province.terrain;
+
+    let mut content = format!("{} (#{})", province.name, province.id.0);
+    if province.is_sea {
+        content.push_str("\nМоре");
+    } else {
+        let _ = write!(content, "\nМестность: {}", terrain.kind().name());
+    }
+    let _ = write!(content, "\nПлощадь: {} пикс.", province.pixel_area);
+    let _ = write!(content, "\nВысота: средняя {:.1}, наибольшая {:.1}", terrain.mean_height, terrain.max_height);
+    let _ = write!(content, "\nУклон: средний {:.2}, наибольший {:.2}", terrain.mean_slope, terrain.max_slope);
+    let _ = write!(content, "\nВода: {:.0}%", terrain.water_ratio * 100.0);
+    if terrain.coastal {
+        content.push_str(", побережье");
+    }
+
+    // Соседи с типом связи и длиной общей границы; у проливов длина нулевая
+    for adjacency in graph.neighbours(province.id) {
+        let kind = match adjacency.kind {
+            AdjacencyKind::Land => "суша",
+            AdjacencyKind::Sea => "море",
+            AdjacencyKind::Coastal => "побережье",
+            AdjacencyKind::Strait => "пролив",
+        };
+        let name = registry.get(adjacency.neighbour).map(|p| p.name.as_str()).unwrap_or("?");
+        let river = if adjacency.river { ", река" } else { "" };
+        let _ = write!(content, "\n  {} — {}, граница {:.0}{}", name, kind, adjacency.border_length, river);
+    }
+
+    text.0 = content;
+}
@@ -0,0 +1,222 @@
+use crate::core::map::province::{Province, ProvinceId, ProvinceMap, ProvinceRegistry, ProvinceTerrain};
+use crate::core::map::sea::SEA_LEVEL;
+use crate::core::map::terrain::heightfield::Heightfield;
+use crate::core::map::{MAP_DEPTH, MAP_WIDTH};
+use bevy::math::{Rect, Vec2};
+use bevy::prelude::{Commands, Res};
+use crate::pkg::str::generate_bytes_hash;
+use image::RgbImage;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+
+#[derive(Deserialize)]
+struct ProvinceDefinitions {
+    provinces: Vec<ProvinceDefinition>,
+}
+
+#[derive(Deserialize)]
+struct ProvinceDefinition {
+    id: u16,
+    name: String,
+    color: (u8, u8, u8),
+    #[serde(default)]
+    sea: Option<bool>,
+}
+
+#[derive(Default)]
+struct ProvinceAccumulator {
+    area: u32,
+    sum_x: f64,
+    sum_y: f64,
+    min: Vec2,
+    max: Vec2,
+    sum_height: f64,
+    max_height: f32,
+    sum_slope: f64,
+    max_slope: f32,
+    water_pixels: u32,
+    coastal: bool,
+}
+
+pub fn load_provinces(
+    mut commands: Commands,
+    heightfield: Res<Heightfield>,
+) {
+    let definitions = load_definitions("common/map/provinces.ron");
+
+    // Без карты провинций игра запускается с пустым реестром: один пиксель без провинции
+    let (registry, map) = match load_province_image("common/map/provinces.png") {
+        Ok((image, source_hash)) => build_provinces(definitions, &image, source_hash, &heightfield),
+        Err(e) => {
+            eprintln!("Не удалось загрузить карту провинций: {}", e);
+            let map = ProvinceMap::new(1, 1, Vec2::new(MAP_WIDTH, MAP_DEPTH), vec![0], String::new());
+            (ProvinceRegistry::default(), map)
+        }
+    };
+
+    if registry.is_empty() {
+        eprintln!("На карте провинций не найдено ни одной провинции из provinces.ron");
+    }
+
+    #[cfg(debug_assertions)]
+    println!("Загружено {} провинций", registry.len());
+
+    commands.insert_resource(registry);
+    commands.insert_resource(map);
+}
+
+fn build_provinces(
+    definitions: ProvinceDefinitions,
+    image: &RgbImage,
+    source_hash: String,
+    heightfield: &Heightfield,
+) -> (ProvinceRegistry, ProvinceMap) {
+    let width = image.width();
+    let height = image.height();
+
+    let mut by_color: HashMap<[u8; 3], u16> = HashMap::new();
+    let mut names: HashMap<u16, (String, Option<bool>)> = HashMap::new();
+    let mut next_id = 1;
+
+    for definition in definitions.provinces {
+        let color = [definition.color.0, definition.color.1, definition.color.2];
+        by_color.insert(color, definition.id);
+        names.insert(definition.id, (definition.name, definition.sea));
+        next_id = next_id.max(definition.id + 1);
+    }
+
+    let mut ids = vec![0u16; (width * height) as usize];
+    let mut undefined_colors = 0;
+    let mut last_color = None;
+    let mut last_id = 0;
+
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let color = pixel.0;
+        if last_color != Some(color) {
+            last_id = *by_color.entry(color).or_insert_with(|| {
+                let id = next_id;
+                next_id += 1;
+                undefined_colors += 1;
+                id
+            });
+            last_color = Some(color);
+        }
+        ids[(y * width + x) as usize] = last_id;
+    }
+
+    if undefined_colors > 0 {
+        eprintln!("В provinces.png найдено {} цветов без описания в provinces.ron", undefined_colors);
+    }
+
+    let map = ProvinceMap::new(width, height, Vec2::new(MAP_WIDTH, MAP_DEPTH), ids, source_hash);
+
+    // Высоты считаются построчно, чтобы каждый пиксель сэмплировался один раз
+    let sample_row = |y: u32| -> Vec<f32> {
+        (0..width)
+            .map(|x| {
+                let world = map.pixel_to_world(x as f32 + 0.5, y as f32 + 0.5);
+                heightfield.height_at(world.x, world.y)
+            })
+            .collect()
+    };
+
+    let world_per_pixel = map.pixel_to_world(1.0, 1.0);
+    let mut accumulators: HashMap<u16, ProvinceAccumulator> = HashMap::new();
+    let mut previous_row: Option<Vec<f32>> = None;
+    let mut current_row = sample_row(0);
+
+    for y in 0..height {
+        let next_row = if y + 1 < height { Some(sample_row(y + 1)) } else { None };
+
+        for x in 0..width {
+            let id = map.ids[(y * width + x) as usize];
+            if id == 0 {
+                continue;
+            }
+
+            let h = current_row[x as usize];
+            let left = current_row[x.saturating_sub(1) as usize];
+            let right = current_row[(x + 1).min(width - 1) as usize];
+            let up = previous_row.as_ref().map_or(h, |row| row[x as usize]);
+            let down = next_row.as_ref().map_or(h, |row| row[x as usize]);
+
+            let slope_x = (right - left) / (2.0 * world_per_pixel.x);
+            let slope_z = (down - up) / (2.0 * world_per_pixel.y);
+            let slope = (slope_x * slope_x + slope_z * slope_z).sqrt();
+
+            let is_water = h < SEA_LEVEL;
+            let near_water = [left, right, up, down].iter().any(|n| *n < SEA_LEVEL);
+
+            let acc = accumulators.entry(id).or_insert_with(|| ProvinceAccumulator {
+                min: Vec2::splat(f32::MAX),
+                max: Vec2::splat(f32::MIN),
+                ..Default::default()
+            });
+
+            acc.area += 1;
+            acc.sum_x += x as f64 + 0.5;
+            acc.sum_y += y as f64 + 0.5;
+            acc.min = acc.min.min(Vec2::new(x as f32, y as f32));
+            acc.max = acc.max.max(Vec2::new(x as f32 + 1.0, y as f32 + 1.0));
+            acc.sum_height += h as f64;
+            acc.max_height = acc.max_height.max(h);
+            acc.sum_slope += slope as f64;
+            acc.max_slope = acc.max_slope.max(slope);
+            if is_water {
+                acc.water_pixels += 1;
+            } else if near_water {
+                acc.coastal = true;
+            }
+        }
+
+        previous_row = Some(current_row);
+        current_row = next_row.unwrap_or_default();
+    }
+
+    let mut sorted: Vec<(u16, ProvinceAccumulator)> = accumulators.into_iter().collect();
+    sorted.sort_by_key(|(id, _)| *id);
+
+    let mut registry = ProvinceRegistry::default();
+    for (id, acc) in sorted {
+        let area = acc.area as f64;
+        let water_ratio = acc.water_pixels as f32 / acc.area as f32;
+        let (name, sea) = names.remove(&id).unwrap_or_else(|| (format!("Province {}", id), None));
+        let color = by_color.iter().find(|(_, v)| **v == id).map(|(c, _)| *c).unwrap_or([0, 0, 0]);
+
+        registry.insert(Province {
+            id: ProvinceId(id),
+            name,
+            color,
+            pixel_area: acc.area,
+            centroid: map.pixel_to_world((acc.sum_x / area) as f32, (acc.sum_y / area) as f32),
+            bounds: Rect::from_corners(
+                map.pixel_to_world(acc.min.x, acc.min.y),
+                map.pixel_to_world(acc.max.x, acc.max.y),
+            ),
+            terrain: ProvinceTerrain {
+                mean_height: (acc.sum_height / area) as f32,
+                max_height: acc.max_height,
+                mean_slope: (acc.sum_slope / area) as f32,
+                max_slope: acc.max_slope,
+                water_ratio,
+                coastal: acc.coastal,
+            },
+            is_sea: sea.unwrap_or(water_ratio > 0.5),
+        });
+    }
+
+    (registry, map)
+}
+
+fn load_definitions(path: &str) -> ProvinceDefinitions {
+    let content = fs::read_to_string(path).expect("Failed to read province definitions");
+    ron::from_str(&content).expect("Failed to parse province definitions")
+}
+
+fn load_province_image(path: &str) -> Result<(RgbImage, String), String> {
+    let bytes = fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+    let img = image::load_from_memory(&bytes).map_err(|e| format!("{}: {}", path, e))?;
+
+    Ok((img.into_rgb8(), generate_bytes_hash(&bytes)))
+}
@@ -0,0 +1,188 @@
+pub(crate) mod loader;
+pub(crate) mod adjacency;
+pub(crate) mod selection;
+mod info_panel;
+pub(crate) mod tooltip;
+
+use crate::core::map::province::adjacency::{build_province_graph, ProvinceGraph};
+use crate::core::map::province::loader::load_provinces;
+use crate::core::map::picking::update_terrain_cursor;
+use crate::core::map::province::tooltip::RegisterTooltipSection;
+use crate::core::map::province::selection::{init_province_texture, select_province, update_hovered_province, update_province_highlight, HoveredProvince, ProvinceClicked, ProvinceHovered, SelectClickState, SelectedProvince};
+use crate::core::map::terrain::generate_terrain;
+use bevy::math::{Rect, Vec2};
+use bevy::prelude::{App, IntoSystemConfigs, Resource, Startup, Update};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ProvinceId(pub u16);
+
+// Пороги рельефа по статистике провинции из карты высот
+const HILLS_SLOPE: f32 = 0.3;
+const MOUNTAINS_SLOPE: f32 = 0.7;
+const MOUNTAINS_HEIGHT: f32 = 50.0;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TerrainKind {
+    Plains,
+    Hills,
+    Mountains,
+}
+
+impl TerrainKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TerrainKind::Plains => "равнина",
+            TerrainKind::Hills => "холмы",
+            TerrainKind::Mountains => "горы",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ProvinceTerrain {
+    pub mean_height: f32,
+    pub max_height: f32,
+    pub mean_slope: f32,
+    pub max_slope: f32,
+    pub water_ratio: f32,
+    pub coastal: bool,
+}
+
+impl ProvinceTerrain {
+    pub fn kind(&self) -> TerrainKind {
+        if self.mean_height >= MOUNTAINS_HEIGHT || self.mean_slope >= MOUNTAINS_SLOPE {
+            TerrainKind::Mountains
+        } else if self.mean_slope >= HILLS_SLOPE {
+            TerrainKind::Hills
+        } else {
+            TerrainKind::Plains
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Province {
+    pub id: ProvinceId,
+    pub name: String,
+    pub color: [u8; 3],
+    pub pixel_area: u32,
+    pub centroid: Vec2,
+    pub bounds: Rect,
+    pub terrain: ProvinceTerrain,
+    pub is_sea: bool,
+}
+
+#[derive(Resource, Default)]
+pub struct ProvinceRegistry {
+    provinces: Vec<Province>,
+    index: HashMap<ProvinceId, usize>,
+}
+
+impl ProvinceRegistry {
+    pub fn insert(&mut self, province: Province) {
+        self.index.insert(province.id, self.provinces.len());
+        self.provinces.push(province);
+    }
+
+    pub fn get(&self, id: ProvinceId) -> Option<&Province> {
+        self.index.get(&id).map(|&i| &self.provinces[i])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Province> {
+        self.provinces.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.provinces.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.provinces.is_empty()
+    }
+}
+
+// Попиксельная карта провинций: 0 означает пиксель без провинции
+#[derive(Resource, Clone)]
+pub struct ProvinceMap {
+    width: u32,
+    height: u32,
+    pixels_per_unit: Vec2,
+    ids: Arc<[u16]>,
+    pub source_hash: String,
+}
+
+impl ProvinceMap {
+    pub fn new(width: u32, height: u32, world_size: Vec2, ids: Vec<u16>, source_hash: String) -> Self {
+        Self {
+            width,
+            height,
+            pixels_per_unit: Vec2::new(width as f32 / world_size.x, height as f32 / world_size.y),
+            ids: ids.into(),
+            source_hash,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> Option<ProvinceId> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        match self.ids[(y * self.width + x) as usize] {
+            0 => None,
+            id => Some(ProvinceId(id)),
+        }
+    }
+
+    pub fn province_at(&self, x: f32, z: f32) -> Option<ProvinceId> {
+        if x < 0.0 || z < 0.0 {
+            return None;
+        }
+
+        self.pixel((x * self.pixels_per_unit.x) as u32, (z * self.pixels_per_unit.y) as u32)
+    }
+
+    pub fn pixel_to_world(&self, x: f32, y: f32) -> Vec2 {
+        Vec2::new(x, y) / self.pixels_per_unit
+    }
+
+    pub fn raw_ids(&self) -> &[u16] {
+        &self.ids
+    }
+}
+
+pub fn build(app: &mut App) {
+    app.init_resource::<ProvinceRegistry>();
+    app.init_resource::<ProvinceGraph>();
+    app.add_systems(Startup, (load_provinces.after(generate_terrain), build_province_graph).chain());
+
+    app.init_resource::<SelectedProvince>();
+    app.init_resource::<HoveredProvince>();
+    app.init_resource::<SelectClickState>();
+    app.add_event::<ProvinceClicked>();
+    app.add_event::<ProvinceHovered>();
+    app.add_systems(Startup, init_province_texture.after(load_provinces));
+    app.add_systems(Update, (update_hovered_province, select_province, update_province_highlight)
+        .chain()
+        .after(update_terrain_cursor));
+
+    info_panel::build(app);
+    tooltip::build(app);
+    app.register_tooltip_section(|_, province| {
+        if province.is_sea {
+            Some("Море".to_string())
+        } else {
+            Some(format!("Местность: {}", province.terrain.kind().name()))
+        }
+    });
+}
@@ -0,0 +1,120 @@
+use crate::core::input::{ActionState, InputAction, CLICK_DRAG_THRESHOLD};
+use crate::core::map::picking::TerrainCursor;
+use crate::core::map::province::{ProvinceId, ProvinceMap};
+use crate::core::map::terrain::material::{province_id_image, TerrainMaterial, TerrainMaterialHandle};
+use bevy::math::Vec2;
+use bevy::prelude::{Assets, DetectChanges, Event, EventWriter, Image, Res, ResMut, Resource};
+
+#[derive(Resource, Default)]
+pub struct SelectedProvince(pub Option<ProvinceId>);
+
+#[derive(Resource, Default)]
+pub struct HoveredProvince(pub Option<ProvinceId>);
+
+#[derive(Event)]
+pub struct ProvinceClicked {
+    pub province: ProvinceId,
+}
+
+#[derive(Event)]
+pub struct ProvinceHovered {
+    pub province: Option<ProvinceId>,
+}
+
+#[derive(Resource, Default)]
+pub(crate) struct SelectClickState {
+    press_position: Option<Vec2>,
+}
+
+pub fn init_province_texture(
+    map: Res<ProvinceMap>,
+    handle: Res<TerrainMaterialHandle>,
+    mut materials: ResMut<Assets<TerrainMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let Some(material) = materials.get_mut(&handle.0) else {
+        return;
+    };
+
+    let image = province_id_image(map.width(), map.height(), map.raw_ids());
+    material.extension.province_ids = images.add(image);
+}
+
+pub fn update_hovered_province(
+    cursor: Res<TerrainCursor>,
+    map: Option<Res<ProvinceMap>>,
+    mut hovered: ResMut<HoveredProvince>,
+    mut events: EventWriter<ProvinceHovered>,
+) {
+    let province = match (map, cursor.world_position) {
+        (Some(map), Some(position)) => map.province_at(position.x, position.z),
+        _ => None,
+    };
+
+    if hovered.0 != province {
+        hovered.0 = province;
+        events.send(ProvinceHovered { province });
+    }
+}
+
+pub fn select_province(
+    actions: Res<ActionState>,
+    cursor: Res<TerrainCursor>,
+    map: Option<Res<ProvinceMap>>,
+    mut click_state: ResMut<SelectClickState>,
+    mut selected: ResMut<SelectedProvince>,
+    mut events: EventWriter<ProvinceClicked>,
+) {
+    if actions.just_pressed(InputAction::Select) {
+        click_state.press_position = if cursor.over_ui { None } else { cursor.screen_position };
+    }
+
+    if !actions.just_released(InputAction::Select) {
+        return;
+    }
+
+    let Some(press_position) = click_state.press_position.take() else {
+        return;
+    };
+
+    let is_click = cursor.screen_position
+        .is_some_and(|position| position.distance(press_position) <= CLICK_DRAG_THRESHOLD);
+    if !is_click {
+        return;
+    }
+
+    let Some(map) = map else {
+        return;
+    };
+
+    let hit = cursor.world_position.and_then(|position| map.province_at(position.x, position.z));
+
+    match hit {
+        Some(province) => {
+            selected.0 = Some(province);
+            events.send(ProvinceClicked { province });
+        }
+        None => selected.0 = None,
+    }
+}
+
+pub fn update_province_highlight(
+    selected: Res<SelectedProvince>,
+    hovered: Res<HoveredProvince>,
+    handle: Option<Res<TerrainMaterialHandle>>,
+    mut materials: ResMut<Assets<TerrainMaterial>>,
+) {
+    if !selected.is_changed() && !hovered.is_changed() {
+        return;
+    }
+
+    let Some(handle) = handle else {
+        return;
+    };
+
+    if let Some(material) = materials.get_mut(&handle.0) {
+        let highlight = &mut material.extension.highlight;
+        highlight.selected = selected.0.map_or(0, |id| id.0 as u32);
+        highlight.hovered = hovered.0.map_or(0, |id| id.0 as u32);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::core::map::map_mode::{ActiveMapMode, RefreshMapMode};
+use crate::core::map::picking::TerrainCursor;
+use crate::core::map::province::selection::ProvinceHovered;
+use crate::core::map::province::{Province, ProvinceId, ProvinceRegistry};
+use crate::core::simulation::calendar::GameCalendar;
+use bevy::color::Color;
+use bevy::prelude::{default, on_event, resource_changed, Alpha, App, BackgroundColor, BuildChildren, ChildBuild, Commands, Component, Condition, DetectChangesMut, EventReader, IntoSystemConfigs, Mut, Node, PositionType, Res, ResMut, Resource, Single, Startup, Text, TextFont, UiRect, Update, Val, Visibility, With, World};
+
+// Смещение подсказки от курсора, чтобы она не закрывала саму провинцию
+const CURSOR_OFFSET: f32 = 16.0;
+
+pub type TooltipSectionFn = Box<dyn Fn(&World, &Province) -> Option<String> + Send + Sync>;
+
+// Строки подсказки под названием провинции, в порядке регистрации
+#[derive(Resource, Default)]
+pub struct TooltipSections {
+    sections: Vec<TooltipSectionFn>,
+}
+
+pub trait RegisterTooltipSection {
+    fn register_tooltip_section(&mut self, section: impl Fn(&World, &Province) -> Option<String> + Send + Sync + 'static) -> &mut Self;
+}
+
+impl RegisterTooltipSection for App {
+    fn register_tooltip_section(&mut self, section: impl Fn(&World, &Province) -> Option<String> + Send + Sync + 'static) -> &mut Self {
+        self.init_resource::<TooltipSections>();
+        self.world_mut().resource_mut::<TooltipSections>().sections.push(Box::new(section));
+        self
+    }
+}
+
+#[derive(Resource, Default)]
+struct ProvinceTooltip {
+    province: Option<ProvinceId>,
+}
+
+#[derive(Component)]
+struct ProvinceTooltipNode;
+
+#[derive(Component)]
+struct ProvinceTooltipText;
+
+pub fn build(app: &mut App) {
+    app.init_resource::<TooltipSections>();
+    app.init_resource::<ProvinceTooltip>();
+    app.add_systems(Startup, init);
+    app.add_systems(Update, (
+        track_hovered_province,
+        update_tooltip_text.run_if(
+            resource_changed::<ProvinceTooltip>
+                .or(resource_changed::<ActiveMapMode>)
+                .or(on_event::<RefreshMapMode>)
+                .or(resource_changed::<GameCalendar>),
+        ),
+        position_tooltip,
+    ).chain());
+}
+
+fn init(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                padding: UiRect::axes(Val::Px(6.0), Val::Px(3.0)),
+                ..default()
+            },
+            BackgroundColor(Color::BLACK.with_alpha(0.7)),
+            Visibility::Hidden,
+            ProvinceTooltipNode,
+        ))
+        .with_children(|p| {
+            p.spawn((
+                Text::default(),
+                TextFont {
+                    font_size: 13.0,
+                    ..default()
+                },
+                ProvinceTooltipText,
+            ));
+        });
+}
+
+fn track_hovered_province(
+    mut events: EventReader<ProvinceHovered>,
+    mut tooltip: ResMut<ProvinceTooltip>,
+) {
+    if let Some(event) = events.read().last() {
+        tooltip.province = event.province;
+    }
+}
+
+// Эксклюзивная система: строки подсказки, как и режимы карты, получают доступ ко всему миру
+fn update_tooltip_text(world: &mut World) {
+    let province = world.resource::<ProvinceTooltip>().province;
+    let content = world.resource_scope(|world, sections: Mut<TooltipSections>| {
+        let province = province.and_then(|id| world.resource::<ProvinceRegistry>().get(id))?;
+
+        let mut content = province.name.clone();
+        for section in sections.sections.iter() {
+            if let Some(line) = section(world, province) {
+                content.push('\n');
+                content.push_str(&line);
+            }
+        }
+        Some(content)
+    });
+
+    let mut texts = world.query_filtered::<&mut Text, With<ProvinceTooltipText>>();
+    if let Ok(mut text) = texts.get_single_mut(world) {
+        text.0 = content.unwrap_or_default();
+    }
+}
+
+fn position_tooltip(
+    cursor: Res<TerrainCursor>,
+    tooltip: Res<ProvinceTooltip>,
+    node: Single<(&mut Node, &mut Visibility), With<ProvinceTooltipNode>>,
+) {
+    let (mut node, mut visibility) = node.into_inner();
+
+    let position = cursor.screen_position.filter(|_| tooltip.province.is_some() && !cursor.over_ui);
+    let Some(position) = position else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+
+    visibility.set_if_neq(Visibility::Inherited);
+    node.left = Val::Px(position.x + CURSOR_OFFSET);
+    node.top = Val::Px(position.y + CURSOR_OFFSET);
+}
@@ -1,7 +1,11 @@
+use crate::core::map::wrap::MapWrap;
+use crate::core::map::MAP_WIDTH;
 use bevy::asset::RenderAssetUsages;
-use bevy::prelude::{default, Assets, Color, Commands, Mesh, Mesh3d, MeshMaterial3d, ResMut, StandardMaterial, Startup, Transform, Vec3};
+use bevy::prelude::{default, Assets, Color, Commands, Mesh, Mesh3d, MeshMaterial3d, Res, ResMut, StandardMaterial, Startup, Transform, Vec3};
 use bevy::render::mesh::{Indices, PrimitiveTopology};
 
+pub const SEA_LEVEL: f32 = 6.15;
+
 pub fn build(app: &mut bevy::prelude::App) {
     app.add_systems(Startup, init);
 }
@@ -10,10 +14,13 @@ pub fn init(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    wrap: Res<MapWrap>,
 ) {
-    let sea_width = 8192.0;
+    // При зацикленной карте море тянется на ширину карты в обе стороны
+    let wrap_copies = if wrap.enabled { 3 } else { 1 };
+    let sea_width = MAP_WIDTH * wrap_copies as f32;
     let sea_height = 4096.0;
-    let sea_subdivisions_x = 32;
+    let sea_subdivisions_x = 32 * wrap_copies;
     let sea_subdivisions_z = 16;
 
     let sea_mesh = create_flat_mesh(sea_width, sea_height, sea_subdivisions_x, sea_subdivisions_z);
@@ -27,7 +34,7 @@ pub fn init(
             ..default()
         })),
         Transform {
-            translation: Vec3::new(4096.0, 6.15, 2048.0),
+            translation: Vec3::new(4096.0, SEA_LEVEL, 2048.0),
             scale: Vec3::new(1.0, 1.0, 1.0),
             ..default()
         },
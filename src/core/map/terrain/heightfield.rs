@@ -0,0 +1,60 @@
+use crate::core::map::terrain::mesh_generator::calc_height;
+use crate::pkg::str::generate_bytes_hash;
+use bevy::prelude::Resource;
+use image::GrayImage;
+use std::sync::Arc;
+
+// Изображение общее для всех копий, чтобы клон можно было дёшево отдать в фоновый поток
+#[derive(Resource, Clone)]
+pub struct Heightfield {
+    heightmap: Arc<GrayImage>,
+    max_height: f32,
+    source_hash: String,
+}
+
+impl Heightfield {
+    pub fn new(heightmap: GrayImage) -> Self {
+        let max_pixel = heightmap.pixels().map(|p| p[0]).max().unwrap_or(0);
+        Self {
+            max_height: calc_height(max_pixel as f32),
+            source_hash: generate_bytes_hash(heightmap.as_raw()),
+            heightmap: Arc::new(heightmap),
+        }
+    }
+
+    pub fn source_hash(&self) -> &str {
+        &self.source_hash
+    }
+
+    pub fn max_height(&self) -> f32 {
+        self.max_height
+    }
+
+    pub fn pixel_height(&self, x: u32, z: u32) -> f32 {
+        let px = x.min(self.heightmap.width() - 1);
+        let pz = z.min(self.heightmap.height() - 1);
+        calc_height(self.heightmap.get_pixel(px, pz)[0] as f32)
+    }
+
+    // Билинейная интерполяция, чтобы высота не прыгала на границах пикселей
+    pub fn height_at(&self, x: f32, z: f32) -> f32 {
+        let max_x = self.heightmap.width() as f32 - 1.0;
+        let max_z = self.heightmap.height() as f32 - 1.0;
+        let x = x.clamp(0.0, max_x);
+        let z = z.clamp(0.0, max_z);
+
+        let x0 = x.floor();
+        let z0 = z.floor();
+        let tx = x - x0;
+        let tz = z - z0;
+
+        let h00 = self.pixel_height(x0 as u32, z0 as u32);
+        let h10 = self.pixel_height(x0 as u32 + 1, z0 as u32);
+        let h01 = self.pixel_height(x0 as u32, z0 as u32 + 1);
+        let h11 = self.pixel_height(x0 as u32 + 1, z0 as u32 + 1);
+
+        let top = h00 + (h10 - h00) * tx;
+        let bottom = h01 + (h11 - h01) * tx;
+        top + (bottom - top) * tz
+    }
+}
@@ -0,0 +1,115 @@
+use bevy::asset::{Asset, Handle, RenderAssetUsages};
+use bevy::pbr::{ExtendedMaterial, MaterialExtension, StandardMaterial};
+use bevy::prelude::{Assets, Image, Resource};
+use bevy::reflect::Reflect;
+use bevy::render::render_resource::{AsBindGroup, Extent3d, ShaderRef, TextureDimension, TextureFormat};
+
+pub type TerrainMaterial = ExtendedMaterial<StandardMaterial, TerrainExtension>;
+
+#[derive(Resource)]
+pub struct TerrainMaterialHandle(pub Handle<TerrainMaterial>);
+
+pub use highlight::TerrainHighlight;
+
+// Derive ShaderType из encase 0.10 оставляет на каждое поле функцию check, которую никто не вызывает.
+// Разрешение dead_code на самой структуре до неё не доходит, поэтому она вынесена в свой модуль
+#[allow(dead_code)]
+mod highlight {
+    use crate::core::map::{MAP_DEPTH, MAP_WIDTH};
+    use bevy::math::Vec2;
+    use bevy::reflect::Reflect;
+    use bevy::render::render_resource::ShaderType;
+
+    // 0 в selected/hovered означает отсутствие подсветки
+    #[derive(ShaderType, Reflect, Debug, Clone, Copy)]
+    pub struct TerrainHighlight {
+        pub selected: u32,
+        pub hovered: u32,
+        pub map_size: Vec2,
+    }
+
+    impl Default for TerrainHighlight {
+        fn default() -> Self {
+            Self {
+                selected: 0,
+                hovered: 0,
+                map_size: Vec2::new(MAP_WIDTH, MAP_DEPTH),
+            }
+        }
+    }
+}
+
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+pub struct TerrainExtension {
+    #[uniform(100)]
+    pub highlight: TerrainHighlight,
+    #[texture(101, sample_type = "u_int")]
+    pub province_ids: Handle<Image>,
+    // Цвет режима карты для каждой провинции, альфа задаёт силу наложения на рельеф
+    #[texture(102)]
+    pub province_colors: Handle<Image>,
+    // Туман войны по провинциям в той же раскладке, что и цвета: 0 — не разведано, середина — разведано, 1 — видно
+    #[texture(103)]
+    pub province_visibility: Handle<Image>,
+}
+
+impl TerrainExtension {
+    pub fn new(images: &mut Assets<Image>) -> Self {
+        Self {
+            highlight: TerrainHighlight::default(),
+            province_ids: images.add(province_id_image(1, 1, &[0])),
+            province_colors: images.add(province_color_image(1, 1, vec![0; 4])),
+            province_visibility: images.add(province_visibility_image(1, 1, vec![u8::MAX])),
+        }
+    }
+}
+
+impl MaterialExtension for TerrainExtension {
+    fn fragment_shader() -> ShaderRef {
+        "shader/terrain.wgsl".into()
+    }
+}
+
+pub fn province_id_image(width: u32, height: u32, ids: &[u16]) -> Image {
+    let data = ids.iter().flat_map(|id| id.to_le_bytes()).collect();
+
+    Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::R16Uint,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+pub fn province_color_image(width: u32, height: u32, data: Vec<u8>) -> Image {
+    Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+pub fn province_visibility_image(width: u32, height: u32, data: Vec<u8>) -> Image {
+    Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::R8Unorm,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
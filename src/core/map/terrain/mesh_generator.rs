@@ -374,7 +374,7 @@ fn check_edges(
     false
 }
 
-fn calc_height(height: f32) -> f32 {
+pub(crate) fn calc_height(height: f32) -> f32 {
     if height < 6.0 {
         return 0.0;
     }
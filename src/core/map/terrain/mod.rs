@@ -6,8 +6,8 @@ use crate::pkg::str::generate_short_hash;
 use bevy::asset::{Assets, Handle};
 use bevy::color::Color;
 use bevy::math::Vec3;
-use bevy::pbr::{MeshMaterial3d, StandardMaterial};
-use bevy::prelude::{default, App, BuildChildren, Commands, Entity, GlobalTransform, Mesh3d, Res, ResMut, Startup, Transform, Visibility};
+use bevy::pbr::{MaterialPlugin, MeshMaterial3d, StandardMaterial};
+use bevy::prelude::{default, App, BuildChildren, Commands, Entity, GlobalTransform, Image, Mesh3d, Res, ResMut, Startup, Transform, Visibility};
 use bevy::render::view::RenderLayers;
 use image::{GrayImage, ImageReader};
 use std::fs::File;
@@ -15,13 +15,19 @@ use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::thread;
 use crate::core::async_tasks::{BackgroundTaskResult, BackgroundTaskSystem, GeneratedChunkData};
+use crate::core::map::terrain::heightfield::Heightfield;
+use crate::core::map::terrain::material::{TerrainExtension, TerrainMaterial, TerrainMaterialHandle};
+use crate::core::map::wrap::Wrapped;
 
 pub(crate) mod mesh_generator;
 pub(crate) mod mesh_loader;
 pub(crate) mod mesh_pool;
 pub(crate) mod cache;
+pub(crate) mod heightfield;
+pub(crate) mod material;
 
 pub fn build(app: &mut App) {
+    app.add_plugins(MaterialPlugin::<TerrainMaterial>::default());
     app.add_systems(Startup, setup);
 }
 
@@ -36,7 +42,8 @@ pub fn setup() {
 
 pub fn generate_terrain(
     mut commands: Commands,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut materials: ResMut<Assets<TerrainMaterial>>,
+    mut images: ResMut<Assets<Image>>,
     task_system: Res<BackgroundTaskSystem>
 ) {
     let width = 8192;
@@ -47,6 +54,18 @@ pub fn generate_terrain(
     let num_chunks_z = height / chunk_size;
 
     let heightmap = load_heightmap("common/map/heightmap.png");
+    commands.insert_resource(Heightfield::new(heightmap.clone()));
+
+    // Один материал на все чанки: подсветка провинций меняется параметрами, а не перестройкой мешей
+    let material_handle = materials.add(TerrainMaterial {
+        base: StandardMaterial {
+            base_color: Color::srgb(0.3, 0.5, 0.4),
+            perceptual_roughness: 1.0,
+            ..default()
+        },
+        extension: TerrainExtension::new(&mut images),
+    });
+    commands.insert_resource(TerrainMaterialHandle(material_handle.clone()));
 
     let parent_entity = commands.spawn((
         Transform::default(),
@@ -67,12 +86,6 @@ pub fn generate_terrain(
             let start_x = x * chunk_size;
             let start_z = z * chunk_size;
 
-            let material_handle = materials.add(StandardMaterial {
-                base_color: Color::srgb(0.3, 0.5, 0.4),
-                perceptual_roughness: 1.0,
-                ..default()
-            });
-
             let chunk_id = generate_short_hash(&chunk_num_id.to_string());
 
             let terrain_chunk = commands.spawn((
@@ -84,12 +97,16 @@ pub fn generate_terrain(
                     current_lod: None,
                     target_lod: None,
                 },
-                MeshMaterial3d::from(material_handle),
+                MeshMaterial3d::from(material_handle.clone()),
                 Transform {
                     translation: Vec3::new(start_x as f32, 0.0, start_z as f32),
                     scale: Vec3::new(1.0, 1.0, 1.0),
                     ..default()
                 },
+                Wrapped {
+                    canonical_x: start_x as f32,
+                    extent: chunk_size as f32,
+                },
                 RenderLayers::layer(1)
             )).id();
 
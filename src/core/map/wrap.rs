@@ -0,0 +1,81 @@
+use crate::core::map::MAP_WIDTH;
+use bevy::math::Vec3;
+use bevy::prelude::{Component, Query, Res, Resource, Transform};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct MapSettings {
+    // Карта замыкается по горизонтали; выключение требует перезапуска
+    pub wrap: bool,
+}
+
+impl Default for MapSettings {
+    fn default() -> Self {
+        Self { wrap: true }
+    }
+}
+
+#[derive(Resource)]
+pub struct MapWrap {
+    pub enabled: bool,
+    view_center_x: f32,
+}
+
+impl MapWrap {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            view_center_x: 0.0,
+        }
+    }
+
+    pub fn canonical_x(&self, x: f32) -> f32 {
+        if self.enabled {
+            x.rem_euclid(MAP_WIDTH)
+        } else {
+            x
+        }
+    }
+
+    pub fn canonical_position(&self, position: Vec3) -> Vec3 {
+        Vec3::new(self.canonical_x(position.x), position.y, position.z)
+    }
+
+    // Копия координаты x, ближайшая к reference (по модулю ширины карты)
+    pub fn nearest_to(&self, x: f32, reference: f32) -> f32 {
+        if !self.enabled {
+            return x;
+        }
+
+        x + ((reference - x) / MAP_WIDTH).round() * MAP_WIDTH
+    }
+
+    pub fn nearest_copy_x(&self, x: f32) -> f32 {
+        self.nearest_to(x, self.view_center_x)
+    }
+
+    pub fn set_view_center_x(&mut self, x: f32) {
+        self.view_center_x = x;
+    }
+}
+
+#[derive(Component)]
+pub struct Wrapped {
+    pub canonical_x: f32,
+    pub extent: f32,
+}
+
+pub fn apply_wrap_offsets(
+    wrap: Res<MapWrap>,
+    mut query: Query<(&Wrapped, &mut Transform)>,
+) {
+    if !wrap.enabled {
+        return;
+    }
+
+    for (wrapped, mut transform) in query.iter_mut() {
+        let half_extent = wrapped.extent / 2.0;
+        transform.translation.x = wrap.nearest_copy_x(wrapped.canonical_x + half_extent) - half_extent;
+    }
+}
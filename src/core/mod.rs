@@ -9,6 +9,18 @@ use debug::panic_handler::trigger_panic;
 mod map;
 pub(crate) mod debug;
 mod async_tasks;
+pub(crate) mod input;
+pub(crate) mod settings;
+pub(crate) mod country;
+pub(crate) mod economy;
+pub(crate) mod diplomacy;
+pub(crate) mod simulation;
+pub(crate) mod save;
+pub(crate) mod unit;
+pub(crate) mod fog;
+pub(crate) mod ai;
+pub(crate) mod pathfinding;
+pub(crate) mod event_log;
 
 pub fn init(app: &mut bevy::prelude::App) {
     let default_plugins = DefaultPlugins.set(AssetPlugin {
@@ -25,7 +37,20 @@ pub fn init(app: &mut bevy::prelude::App) {
         });
 
     app.add_plugins(default_plugins);
+    settings::build(app);
+    input::build(app);
+
     app.add_plugins(MapPlugin);
+    country::build(app);
+    economy::build(app);
+    diplomacy::build(app);
+    unit::build(app);
+    fog::build(app);
+    ai::build(app);
+    pathfinding::build(app);
+    simulation::build(app);
+    save::build(app);
+    event_log::build(app);
     app.add_plugins(DebugPlugin);
 
     async_tasks::build(app);
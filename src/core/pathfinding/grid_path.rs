@@ -0,0 +1,295 @@
+use crate::core::map::terrain::heightfield::Heightfield;
+use crate::core::map::MAP_WIDTH;
+use crate::core::pathfinding::passability::PassabilityGrid;
+use crate::core::unit::movement::BASE_SPEED;
+use bevy::math::{Vec2, Vec3};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::f32::consts::SQRT_2;
+
+// Ограничение на размер поиска в долях площади карты, чтобы оно не зависело от размера клетки:
+// дальние маршруты должны идти через граф провинций
+const MAX_EXPANDED_SHARE: f32 = 0.75;
+
+const NEIGHBOURS: [(i32, i32); 8] = [
+    (1, 0), (-1, 0), (0, 1), (0, -1),
+    (1, 1), (1, -1), (-1, 1), (-1, -1),
+];
+
+#[derive(Clone, Debug)]
+pub struct GridPath {
+    // Сглаженные точки маршрута после стартовой, последняя — точная цель
+    pub waypoints: Vec<Vec2>,
+    // Маршрут, уложенный на рельеф с шагом в клетку, начиная со старта
+    pub points: Vec<Vec3>,
+    // Дни пути от старта до каждой точки points
+    pub days: Vec<f32>,
+    pub eta_days: f32,
+}
+
+// Координаты на выходе непрерывны по x: при переходе через шов карты x выходит за её ширину
+pub fn find_grid_path(
+    grid: &PassabilityGrid,
+    heightfield: &Heightfield,
+    from: Vec2,
+    to: Vec2,
+    wrap: bool,
+) -> Option<GridPath> {
+    let canonical = |p: Vec2| if wrap { Vec2::new(p.x.rem_euclid(MAP_WIDTH), p.y) } else { p };
+
+    let start = grid.cell_at(canonical(from))?;
+    let goal = grid.cell_at(canonical(to))?;
+    if !grid.is_passable(goal.0, goal.1) {
+        return None;
+    }
+
+    let cells = search(grid, start, goal, wrap)?;
+
+    let mut points = Vec::with_capacity(cells.len() + 1);
+    points.push(from);
+    for &(x, z) in cells.iter().skip(1) {
+        points.push(grid.cell_center(x, z));
+    }
+    if let Some(last) = points.last_mut() {
+        *last = to;
+    }
+    unwrap_x(&mut points, wrap);
+
+    let smoothed = smooth(grid, &points, wrap);
+    let (points, days) = drape(grid, heightfield, &smoothed, wrap);
+
+    Some(GridPath {
+        waypoints: smoothed.into_iter().skip(1).collect(),
+        eta_days: days.last().copied().unwrap_or(0.0),
+        points,
+        days,
+    })
+}
+
+fn search(grid: &PassabilityGrid, start: (u32, u32), goal: (u32, u32), wrap: bool) -> Option<Vec<(u32, u32)>> {
+    let width = grid.width();
+    let height = grid.height();
+    let index = |(x, z): (u32, u32)| (z * width + x) as usize;
+
+    let heuristic = |(x, z): (u32, u32)| {
+        let mut dx = x.abs_diff(goal.0);
+        if wrap {
+            dx = dx.min(width - dx);
+        }
+        let dz = z.abs_diff(goal.1);
+        // Октильное расстояние при минимальной стоимости клетки 1
+        (dx.max(dz) - dx.min(dz)) as f32 + dx.min(dz) as f32 * SQRT_2
+    };
+
+    let mut best = vec![f32::INFINITY; (width * height) as usize];
+    let mut came_from = vec![u32::MAX; (width * height) as usize];
+    let mut open = BinaryHeap::new();
+    let mut expanded = 0;
+    let max_expanded = (grid.width() as f32 * grid.height() as f32 * MAX_EXPANDED_SHARE) as usize;
+
+    best[index(start)] = 0.0;
+    open.push(OpenCell { estimate: heuristic(start), cost: 0.0, cell: start });
+
+    while let Some(OpenCell { cost, cell, .. }) = open.pop() {
+        if cell == goal {
+            return Some(reconstruct(&came_from, width, start, goal));
+        }
+        if cost > best[index(cell)] {
+            continue;
+        }
+
+        expanded += 1;
+        if expanded > max_expanded {
+            return None;
+        }
+
+        // Старт может оказаться в непроходимой клетке (берег), из неё разрешено выйти
+        let here = grid.cost(cell.0, cell.1).unwrap_or(1.0);
+
+        for (dx, dz) in NEIGHBOURS {
+            let Some(next) = offset(cell, dx, dz, width, height, wrap) else {
+                continue;
+            };
+            let Some(next_cost) = grid.cost(next.0, next.1) else {
+                continue;
+            };
+
+            // По диагонали нельзя срезать угол непроходимой клетки
+            if dx != 0 && dz != 0 {
+                let side_x = offset(cell, dx, 0, width, height, wrap);
+                let side_z = offset(cell, 0, dz, width, height, wrap);
+                let blocked = |side: Option<(u32, u32)>| side.is_none_or(|(x, z)| !grid.is_passable(x, z));
+                if blocked(side_x) || blocked(side_z) {
+                    continue;
+                }
+            }
+
+            let step = if dx != 0 && dz != 0 { SQRT_2 } else { 1.0 };
+            let total = cost + step * (here + next_cost) / 2.0;
+            if total >= best[index(next)] {
+                continue;
+            }
+
+            best[index(next)] = total;
+            came_from[index(next)] = index(cell) as u32;
+            open.push(OpenCell { estimate: total + heuristic(next), cost: total, cell: next });
+        }
+    }
+
+    None
+}
+
+fn offset(cell: (u32, u32), dx: i32, dz: i32, width: u32, height: u32, wrap: bool) -> Option<(u32, u32)> {
+    let mut x = cell.0 as i32 + dx;
+    let z = cell.1 as i32 + dz;
+    if z < 0 || z >= height as i32 {
+        return None;
+    }
+    if x < 0 || x >= width as i32 {
+        if !wrap {
+            return None;
+        }
+        x = x.rem_euclid(width as i32);
+    }
+    Some((x as u32, z as u32))
+}
+
+fn reconstruct(came_from: &[u32], width: u32, start: (u32, u32), goal: (u32, u32)) -> Vec<(u32, u32)> {
+    let mut cells = vec![goal];
+    let mut current = goal;
+    while current != start {
+        let previous = came_from[(current.1 * width + current.0) as usize];
+        current = (previous % width, previous / width);
+        cells.push(current);
+    }
+    cells.reverse();
+    cells
+}
+
+// Каждая точка сдвигается в копию карты, ближайшую к предыдущей, чтобы отрезки не пересекали всю карту
+fn unwrap_x(points: &mut [Vec2], wrap: bool) {
+    if !wrap {
+        return;
+    }
+    for i in 1..points.len() {
+        let reference = points[i - 1].x;
+        let x = points[i].x;
+        points[i].x = x + ((reference - x) / MAP_WIDTH).round() * MAP_WIDTH;
+    }
+}
+
+// Срезание углов: точка пропускается, если отрезок до следующей проходим и не дороже худшей клетки исходного участка
+fn smooth(grid: &PassabilityGrid, points: &[Vec2], wrap: bool) -> Vec<Vec2> {
+    if points.len() <= 2 {
+        return points.to_vec();
+    }
+
+    let mut result = vec![points[0]];
+    let mut anchor = 0;
+
+    while anchor < points.len() - 1 {
+        let mut next = anchor + 1;
+        let mut worst = segment_max_cost(grid, points[anchor], points[next], wrap).unwrap_or(f32::INFINITY);
+
+        while next + 1 < points.len() {
+            worst = worst.max(segment_max_cost(grid, points[next], points[next + 1], wrap).unwrap_or(f32::INFINITY));
+            match segment_max_cost(grid, points[anchor], points[next + 1], wrap) {
+                Some(cost) if cost <= worst => next += 1,
+                _ => break,
+            }
+        }
+
+        result.push(points[next]);
+        anchor = next;
+    }
+
+    result
+}
+
+fn segment_max_cost(grid: &PassabilityGrid, a: Vec2, b: Vec2, wrap: bool) -> Option<f32> {
+    let step = grid.cell_size() / 2.0;
+    let samples = (a.distance(b) / step).ceil().max(1.0) as u32;
+
+    let mut worst: f32 = 0.0;
+    for i in 0..=samples {
+        let p = a.lerp(b, i as f32 / samples as f32);
+        let p = if wrap { Vec2::new(p.x.rem_euclid(MAP_WIDTH), p.y) } else { p };
+        let (x, z) = grid.cell_at(p)?;
+
+        // Крайние точки — старт и цель, их клетки не проверяются на проходимость
+        let cost = match grid.cost(x, z) {
+            Some(cost) => cost,
+            None if i == 0 || i == samples => 1.0,
+            None => return None,
+        };
+        worst = worst.max(cost);
+    }
+
+    Some(worst)
+}
+
+fn drape(grid: &PassabilityGrid, heightfield: &Heightfield, points: &[Vec2], wrap: bool) -> (Vec<Vec3>, Vec<f32>) {
+    let height_at = |p: Vec2| {
+        let x = if wrap { p.x.rem_euclid(MAP_WIDTH) } else { p.x };
+        heightfield.height_at(x, p.y)
+    };
+
+    let mut draped = Vec::new();
+    let mut days = Vec::new();
+    let mut elapsed = 0.0;
+
+    if let Some(&first) = points.first() {
+        draped.push(Vec3::new(first.x, height_at(first), first.y));
+        days.push(0.0);
+    }
+
+    for pair in points.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        let length = a.distance(b);
+        let samples = (length / grid.cell_size()).ceil().max(1.0) as u32;
+
+        for i in 1..=samples {
+            let previous = a.lerp(b, (i - 1) as f32 / samples as f32);
+            let p = a.lerp(b, i as f32 / samples as f32);
+            draped.push(Vec3::new(p.x, height_at(p), p.y));
+
+            let mid = (previous + p) / 2.0;
+            let mid = if wrap { Vec2::new(mid.x.rem_euclid(MAP_WIDTH), mid.y) } else { mid };
+            let cost = grid.cell_at(mid)
+                .and_then(|(x, z)| grid.cost(x, z))
+                .unwrap_or(1.0);
+            elapsed += length / samples as f32 * cost / BASE_SPEED;
+            days.push(elapsed);
+        }
+    }
+
+    (draped, days)
+}
+
+// BinaryHeap — max-куча, поэтому сравнение перевёрнуто
+struct OpenCell {
+    estimate: f32,
+    cost: f32,
+    cell: (u32, u32),
+}
+
+impl PartialEq for OpenCell {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OpenCell {}
+
+impl PartialOrd for OpenCell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenCell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+            .then_with(|| self.cell.cmp(&other.cell))
+    }
+}
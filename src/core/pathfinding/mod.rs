@@ -0,0 +1,172 @@
+pub(crate) mod grid_path;
+pub(crate) mod passability;
+pub(crate) mod province_graph;
+
+use crate::core::async_tasks::{BackgroundTaskResult, BackgroundTaskSystem};
+use crate::core::map::province::adjacency::{build_province_graph, ProvinceGraph};
+use crate::core::map::province::{ProvinceId, ProvinceRegistry};
+use crate::core::map::terrain::heightfield::Heightfield;
+use crate::core::map::wrap::MapWrap;
+use crate::core::map::terrain::generate_terrain;
+use crate::core::pathfinding::grid_path::{find_grid_path, GridPath};
+use crate::core::pathfinding::passability::{start_passability_build, PassabilityGrid};
+use crate::core::pathfinding::province_graph::{PathGraph, ProvincePath};
+use bevy::math::Vec2;
+use bevy::prelude::{App, IntoSystemConfigs, Res, ResMut, Resource, Startup};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::thread;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PathHandle(u64);
+
+#[derive(Clone, Debug)]
+pub enum FoundPath {
+    Provinces(ProvincePath),
+    Grid(GridPath),
+}
+
+#[derive(Clone, Debug)]
+pub enum PathStatus {
+    Pending,
+    Found(FoundPath),
+    NotFound,
+}
+
+pub struct PathQueryResult {
+    pub handle: PathHandle,
+    pub path: Option<FoundPath>,
+}
+
+#[derive(Resource, Default)]
+pub struct Pathfinder {
+    graph: Arc<PathGraph>,
+    // Сетка проходимости собирается в фоне и появляется через несколько кадров после старта
+    grid: Option<Arc<PassabilityGrid>>,
+    heightfield: Option<Heightfield>,
+    wrap: bool,
+    next_handle: u64,
+    results: HashMap<PathHandle, PathStatus>,
+}
+
+impl Pathfinder {
+    // Соседние провинции считаются сразу, дальние маршруты уходят в фоновый поток
+    pub fn request(&mut self, from: ProvinceId, to: ProvinceId, tasks: &BackgroundTaskSystem) -> PathHandle {
+        let handle = self.next_handle();
+
+        if !self.graph.contains(from) || !self.graph.contains(to) {
+            self.results.insert(handle, PathStatus::NotFound);
+            return handle;
+        }
+
+        if self.is_short(from, to) {
+            let path = self.graph.find_path(from, to).map(FoundPath::Provinces);
+            self.complete(PathQueryResult { handle, path });
+            return handle;
+        }
+
+        let graph = self.graph.clone();
+        spawn_query(handle, tasks, move || graph.find_path(from, to).map(FoundPath::Provinces));
+
+        handle
+    }
+
+    // Маршрут по графу провинций сразу, в том же вызове: для симуляции, где результат нужен в текущем тике
+    pub fn find_now(&self, from: ProvinceId, to: ProvinceId) -> Option<ProvincePath> {
+        self.graph.find_path(from, to)
+    }
+
+    // Точный маршрут по сетке проходимости между точками карты, всегда в фоновом потоке
+    pub fn request_grid(&mut self, from: Vec2, to: Vec2, tasks: &BackgroundTaskSystem) -> PathHandle {
+        let handle = self.next_handle();
+
+        let (Some(grid), Some(heightfield)) = (self.grid.clone(), self.heightfield.clone()) else {
+            self.results.insert(handle, PathStatus::NotFound);
+            return handle;
+        };
+
+        let wrap = self.wrap;
+        spawn_query(handle, tasks, move || {
+            find_grid_path(&grid, &heightfield, from, to, wrap).map(FoundPath::Grid)
+        });
+
+        handle
+    }
+
+    pub fn grid_ready(&self) -> bool {
+        self.grid.is_some()
+    }
+
+    pub(crate) fn set_grid(&mut self, grid: PassabilityGrid) {
+        self.grid = Some(Arc::new(grid));
+    }
+
+    // Забирает готовый результат; ожидающий запрос остаётся на месте
+    pub fn take(&mut self, handle: PathHandle) -> Option<PathStatus> {
+        match self.results.get(&handle)? {
+            PathStatus::Pending => Some(PathStatus::Pending),
+            _ => self.results.remove(&handle),
+        }
+    }
+
+    pub fn cancel(&mut self, handle: PathHandle) {
+        self.results.remove(&handle);
+    }
+
+    // Результат забирается опросом через take, отдельного события о готовности нет
+    pub(crate) fn complete(&mut self, result: PathQueryResult) {
+        // Отменённый запрос мог завершиться позже: его результат никому не нужен
+        let Some(status) = self.results.get_mut(&result.handle) else {
+            return;
+        };
+        if !matches!(status, PathStatus::Pending) {
+            return;
+        }
+
+        *status = match result.path {
+            Some(path) => PathStatus::Found(path),
+            None => PathStatus::NotFound,
+        };
+    }
+
+    fn next_handle(&mut self) -> PathHandle {
+        let handle = PathHandle(self.next_handle);
+        self.next_handle += 1;
+        self.results.insert(handle, PathStatus::Pending);
+        handle
+    }
+
+    fn is_short(&self, from: ProvinceId, to: ProvinceId) -> bool {
+        from == to || self.graph.is_neighbour(from, to)
+    }
+}
+
+pub fn build(app: &mut App) {
+    app.init_resource::<Pathfinder>();
+    app.add_systems(Startup, (
+        build_path_graph.after(build_province_graph),
+        start_passability_build.after(generate_terrain),
+    ));
+}
+
+fn spawn_query(handle: PathHandle, tasks: &BackgroundTaskSystem, query: impl FnOnce() -> Option<FoundPath> + Send + 'static) {
+    let sender = tasks.sender.clone();
+    thread::spawn(move || {
+        let path = query();
+        if let Err(e) = sender.send(BackgroundTaskResult::PathFound(PathQueryResult { handle, path })) {
+            println!("Не удалось отправить найденный путь в основной поток: {:?}", e);
+        }
+    });
+}
+
+fn build_path_graph(
+    mut pathfinder: ResMut<Pathfinder>,
+    graph: Res<ProvinceGraph>,
+    registry: Res<ProvinceRegistry>,
+    heightfield: Res<Heightfield>,
+    wrap: Res<MapWrap>,
+) {
+    pathfinder.graph = Arc::new(PathGraph::build(&graph, &registry, wrap.enabled));
+    pathfinder.heightfield = Some(heightfield.clone());
+    pathfinder.wrap = wrap.enabled;
+}
@@ -0,0 +1,197 @@
+use crate::core::async_tasks::{BackgroundTaskResult, BackgroundTaskSystem};
+use crate::core::map::sea::SEA_LEVEL;
+use crate::core::map::terrain::heightfield::Heightfield;
+use crate::core::map::{MAP_DEPTH, MAP_WIDTH};
+use crate::core::unit::movement::slope_speed_factor;
+use crate::pkg::dir::{cache_directory, init_dir};
+use crate::pkg::str::generate_short_hash;
+use bevy::math::Vec2;
+use bevy::prelude::Res;
+use bincode::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+
+// Стоимость клетки хранится в u8: 0 — непроходимо, COST_UNIT — ровная местность
+pub const IMPASSABLE: u8 = 0;
+const COST_UNIT: f32 = 16.0;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct PassabilityConfig {
+    pub cell_size: f32,
+    pub max_slope: f32,
+    pub peak_height: f32,
+}
+
+impl Default for PassabilityConfig {
+    fn default() -> Self {
+        Self {
+            cell_size: 8.0,
+            max_slope: 1.5,
+            peak_height: 80.0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Encode, Decode, Clone)]
+pub struct PassabilityGrid {
+    width: u32,
+    height: u32,
+    cell_size: f32,
+    costs: Vec<u8>,
+}
+
+impl PassabilityGrid {
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    pub fn cell_at(&self, position: Vec2) -> Option<(u32, u32)> {
+        let x = (position.x / self.cell_size).floor();
+        let z = (position.y / self.cell_size).floor();
+        if x < 0.0 || z < 0.0 || x >= self.width as f32 || z >= self.height as f32 {
+            return None;
+        }
+        Some((x as u32, z as u32))
+    }
+
+    pub fn cell_center(&self, x: u32, z: u32) -> Vec2 {
+        Vec2::new((x as f32 + 0.5) * self.cell_size, (z as f32 + 0.5) * self.cell_size)
+    }
+
+    pub fn raw_cost(&self, x: u32, z: u32) -> u8 {
+        self.costs[(z * self.width + x) as usize]
+    }
+
+    pub fn is_passable(&self, x: u32, z: u32) -> bool {
+        self.raw_cost(x, z) != IMPASSABLE
+    }
+
+    // Множитель времени прохода клетки относительно ровной местности, не меньше 1
+    pub fn cost(&self, x: u32, z: u32) -> Option<f32> {
+        match self.raw_cost(x, z) {
+            IMPASSABLE => None,
+            cost => Some(cost as f32 / COST_UNIT),
+        }
+    }
+
+    fn build(heightfield: &Heightfield, config: &PassabilityConfig) -> Self {
+        let cell_size = config.cell_size.max(1.0);
+        let width = (MAP_WIDTH / cell_size).ceil() as u32;
+        let height = (MAP_DEPTH / cell_size).ceil() as u32;
+        let half = cell_size / 2.0;
+
+        let mut costs = Vec::with_capacity((width * height) as usize);
+        for z in 0..height {
+            for x in 0..width {
+                let cx = (x as f32 + 0.5) * cell_size;
+                let cz = (z as f32 + 0.5) * cell_size;
+                let center = heightfield.height_at(cx, cz);
+
+                if center <= SEA_LEVEL || center >= config.peak_height {
+                    costs.push(IMPASSABLE);
+                    continue;
+                }
+
+                // Уклон по центральным разностям на краях клетки
+                let slope_x = (heightfield.height_at(cx + half, cz) - heightfield.height_at(cx - half, cz)) / cell_size;
+                let slope_z = (heightfield.height_at(cx, cz + half) - heightfield.height_at(cx, cz - half)) / cell_size;
+                let slope = (slope_x * slope_x + slope_z * slope_z).sqrt();
+
+                if slope >= config.max_slope {
+                    costs.push(IMPASSABLE);
+                    continue;
+                }
+
+                let cost = COST_UNIT / slope_speed_factor(slope);
+                costs.push(cost.round().clamp(COST_UNIT, u8::MAX as f32) as u8);
+            }
+        }
+
+        Self { width, height, cell_size, costs }
+    }
+}
+
+pub(super) fn start_passability_build(
+    heightfield: Res<Heightfield>,
+    task_system: Res<BackgroundTaskSystem>,
+) {
+    let heightfield = heightfield.clone();
+    let config = load_config("common/map/passability.ron");
+    let sender = task_system.sender.clone();
+
+    thread::spawn(move || {
+        let cache_path = passability_cache(heightfield.source_hash(), &config);
+
+        let grid = match load_grid(&cache_path) {
+            Ok(grid) => grid,
+            Err(_) => {
+                let grid = PassabilityGrid::build(&heightfield, &config);
+                if let Err(e) = save_grid(&grid, &cache_path) {
+                    println!("Не удалось сохранить сетку проходимости в кэш: {}", e);
+                }
+                grid
+            }
+        };
+
+        #[cfg(debug_assertions)]
+        println!("Сетка проходимости готова: {}x{}", grid.width, grid.height);
+
+        if let Err(e) = sender.send(BackgroundTaskResult::PassabilityReady(grid)) {
+            println!("Не удалось отправить сетку проходимости в основной поток: {:?}", e);
+        }
+    });
+}
+
+fn load_config(path: &str) -> PassabilityConfig {
+    let Ok(content) = fs::read_to_string(path) else {
+        return PassabilityConfig::default();
+    };
+
+    match ron::from_str(&content) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Не удалось разобрать настройки проходимости {}: {}", path, e);
+            PassabilityConfig::default()
+        }
+    }
+}
+
+pub fn passability_cache_dir() -> PathBuf {
+    cache_directory().join("passability")
+}
+
+// Ключ кэша зависит и от карты высот, и от порогов: изменение любого из них пересобирает сетку
+fn passability_cache(heightmap_hash: &str, config: &PassabilityConfig) -> PathBuf {
+    let config_key = format!("{}_{}_{}", config.cell_size, config.max_slope, config.peak_height);
+    passability_cache_dir().join(format!("grid_{}_{}.bin", heightmap_hash, generate_short_hash(&config_key)))
+}
+
+fn load_grid(path: &PathBuf) -> std::io::Result<PassabilityGrid> {
+    let buffer = fs::read(path)?;
+
+    let config = bincode::config::standard();
+    let (grid, _) = bincode::decode_from_slice::<PassabilityGrid, _>(&buffer, config)
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+
+    Ok(grid)
+}
+
+fn save_grid(grid: &PassabilityGrid, path: &PathBuf) -> std::io::Result<()> {
+    init_dir(passability_cache_dir())?;
+
+    let config = bincode::config::standard();
+    let encoded = bincode::encode_to_vec(grid, config)
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+    fs::write(path, encoded)
+}
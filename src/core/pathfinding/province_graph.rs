@@ -0,0 +1,176 @@
+use crate::core::map::province::adjacency::{AdjacencyKind, ProvinceGraph};
+use crate::core::map::province::{Province, ProvinceId, ProvinceRegistry};
+use crate::core::map::MAP_WIDTH;
+use crate::core::unit::movement::{slope_speed_factor, BASE_SPEED};
+use bevy::math::Vec2;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+// Переправа через пролив занимает дни сверх пути между центрами провинций
+const STRAIT_CROSSING_DAYS: f32 = 3.0;
+
+#[derive(Clone, Copy, Debug)]
+struct PathEdge {
+    to: ProvinceId,
+    days: f32,
+}
+
+#[derive(Clone, Debug)]
+struct PathNode {
+    centroid: Vec2,
+    edges: Vec<PathEdge>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ProvincePath {
+    // Провинции маршрута, начиная со стартовой
+    pub provinces: Vec<ProvinceId>,
+    // Центры провинций после стартовой, по ним армия идёт
+    pub waypoints: Vec<Vec2>,
+    pub eta_days: f32,
+}
+
+// Неизменяемый снимок графа с ценами рёбер в днях: его можно отдать в фоновый поток
+#[derive(Default)]
+pub struct PathGraph {
+    nodes: HashMap<ProvinceId, PathNode>,
+    wrap: bool,
+}
+
+impl PathGraph {
+    pub fn build(graph: &ProvinceGraph, registry: &ProvinceRegistry, wrap: bool) -> Self {
+        let mut nodes = HashMap::new();
+
+        for province in registry.iter().filter(|p| !p.is_sea) {
+            let edges = graph.neighbours(province.id).iter()
+                .filter_map(|adjacency| {
+                    // Сухопутная армия в море не заходит, проливы — единственный путь через воду
+                    if !matches!(adjacency.kind, AdjacencyKind::Land | AdjacencyKind::Strait) {
+                        return None;
+                    }
+                    let neighbour = registry.get(adjacency.neighbour)?;
+
+                    let distance = wrapped_distance(province.centroid, neighbour.centroid, wrap);
+                    let factor = (terrain_cost(province) + terrain_cost(neighbour)) / 2.0;
+                    let mut days = distance / BASE_SPEED * factor;
+                    if adjacency.kind == AdjacencyKind::Strait {
+                        days += STRAIT_CROSSING_DAYS;
+                    }
+
+                    Some(PathEdge { to: neighbour.id, days })
+                })
+                .collect();
+
+            nodes.insert(province.id, PathNode { centroid: province.centroid, edges });
+        }
+
+        Self { nodes, wrap }
+    }
+
+    pub fn contains(&self, id: ProvinceId) -> bool {
+        self.nodes.contains_key(&id)
+    }
+
+    pub fn is_neighbour(&self, from: ProvinceId, to: ProvinceId) -> bool {
+        self.nodes.get(&from).is_some_and(|node| node.edges.iter().any(|edge| edge.to == to))
+    }
+
+    pub fn find_path(&self, from: ProvinceId, to: ProvinceId) -> Option<ProvincePath> {
+        let goal = self.nodes.get(&to)?;
+        self.nodes.get(&from)?;
+
+        // Эвристика — путь по прямой по ровной местности, дешевле любого реального маршрута
+        let heuristic = |id: ProvinceId| {
+            self.nodes.get(&id)
+                .map(|node| wrapped_distance(node.centroid, goal.centroid, self.wrap) / BASE_SPEED)
+                .unwrap_or(0.0)
+        };
+
+        let mut open = BinaryHeap::new();
+        let mut best: HashMap<ProvinceId, f32> = HashMap::new();
+        let mut came_from: HashMap<ProvinceId, ProvinceId> = HashMap::new();
+
+        best.insert(from, 0.0);
+        open.push(OpenEntry { estimate: heuristic(from), cost: 0.0, province: from });
+
+        while let Some(OpenEntry { cost, province, .. }) = open.pop() {
+            if province == to {
+                return Some(self.reconstruct(&came_from, to, cost));
+            }
+            if cost > best.get(&province).copied().unwrap_or(f32::INFINITY) {
+                continue;
+            }
+
+            for edge in self.nodes[&province].edges.iter() {
+                let next_cost = cost + edge.days;
+                if next_cost >= best.get(&edge.to).copied().unwrap_or(f32::INFINITY) {
+                    continue;
+                }
+
+                best.insert(edge.to, next_cost);
+                came_from.insert(edge.to, province);
+                open.push(OpenEntry { estimate: next_cost + heuristic(edge.to), cost: next_cost, province: edge.to });
+            }
+        }
+
+        None
+    }
+
+    fn reconstruct(&self, came_from: &HashMap<ProvinceId, ProvinceId>, to: ProvinceId, cost: f32) -> ProvincePath {
+        let mut provinces = vec![to];
+        let mut current = to;
+        while let Some(&previous) = came_from.get(&current) {
+            provinces.push(previous);
+            current = previous;
+        }
+        provinces.reverse();
+
+        let waypoints = provinces.iter()
+            .skip(1)
+            .map(|id| self.nodes[id].centroid)
+            .collect();
+
+        ProvincePath { provinces, waypoints, eta_days: cost }
+    }
+}
+
+// Та же модель, что и в move_units: замедляет только уклон, высота сама по себе не важна
+fn terrain_cost(province: &Province) -> f32 {
+    1.0 / slope_speed_factor(province.terrain.mean_slope)
+}
+
+fn wrapped_distance(a: Vec2, b: Vec2, wrap: bool) -> f32 {
+    let mut dx = (a.x - b.x).abs();
+    if wrap {
+        dx = dx.min(MAP_WIDTH - dx);
+    }
+    Vec2::new(dx, a.y - b.y).length()
+}
+
+// BinaryHeap — max-куча, поэтому сравнение перевёрнуто
+struct OpenEntry {
+    estimate: f32,
+    cost: f32,
+    province: ProvinceId,
+}
+
+impl PartialEq for OpenEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OpenEntry {}
+
+impl PartialOrd for OpenEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+            .then_with(|| self.province.cmp(&other.province))
+    }
+}
@@ -0,0 +1,114 @@
+use crate::core::save::{capture, save_path, write_save, SaveGame, SaveGameRequest};
+use crate::core::settings::UserSettings;
+use crate::core::simulation::calendar::GameCalendar;
+use crate::core::simulation::MonthPassed;
+use bevy::prelude::{EventReader, EventWriter, Local, Res, World};
+use chrono::Datelike;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::sync::Mutex;
+
+pub const EMERGENCY_SAVE_NAME: &str = "emergency";
+
+// Последний согласованный снимок для аварийного сохранения из panic hook, где доступа к миру уже нет
+static EMERGENCY_SNAPSHOT: Mutex<Option<SaveGame>> = Mutex::new(None);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum AutosaveInterval {
+    Disabled,
+    Monthly,
+    Yearly,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct AutosaveSettings {
+    pub interval: AutosaveInterval,
+    pub slots: u8,
+}
+
+impl Default for AutosaveSettings {
+    fn default() -> Self {
+        Self {
+            interval: AutosaveInterval::Monthly,
+            slots: 3,
+        }
+    }
+}
+
+pub fn autosave_name(slot: u8) -> String {
+    format!("autosave_{}", slot)
+}
+
+pub(super) fn autosave(
+    settings: Res<UserSettings>,
+    mut months: EventReader<MonthPassed>,
+    mut requests: EventWriter<SaveGameRequest>,
+) {
+    let autosave = &settings.autosave;
+
+    let due = months.read().any(|month| match autosave.interval {
+        AutosaveInterval::Disabled => false,
+        AutosaveInterval::Monthly => true,
+        AutosaveInterval::Yearly => month.date.month() == 1,
+    });
+
+    if !due || autosave.slots == 0 {
+        return;
+    }
+
+    rotate_autosaves(autosave.slots);
+    requests.send(SaveGameRequest { name: autosave_name(1) });
+}
+
+// Самое новое автосохранение всегда в слоте 1, самое старое вытесняется
+fn rotate_autosaves(slots: u8) {
+    let oldest = save_path(&autosave_name(slots));
+    if oldest.exists() {
+        if let Err(e) = fs::remove_file(&oldest) {
+            eprintln!("Не удалось удалить старое автосохранение {:?}: {}", oldest, e);
+        }
+    }
+
+    for slot in (1..slots).rev() {
+        let from = save_path(&autosave_name(slot));
+        if !from.exists() {
+            continue;
+        }
+
+        if let Err(e) = fs::rename(&from, save_path(&autosave_name(slot + 1))) {
+            eprintln!("Не удалось сдвинуть автосохранение {:?}: {}", from, e);
+        }
+    }
+}
+
+pub(super) fn update_emergency_snapshot(world: &mut World, mut last_day: Local<Option<u64>>) {
+    let day = world.resource::<GameCalendar>().days_elapsed();
+    if *last_day == Some(day) {
+        return;
+    }
+    *last_day = Some(day);
+
+    let snapshot = capture(world);
+    if let Ok(mut slot) = EMERGENCY_SNAPSHOT.lock() {
+        *slot = Some(snapshot);
+    }
+}
+
+// Вызывается из panic hook: блокировку не ждём, паника могла случиться при обновлении снимка
+pub fn emergency_save() {
+    let Ok(slot) = EMERGENCY_SNAPSHOT.try_lock() else {
+        eprintln!("Аварийное сохранение пропущено: снимок недоступен");
+        return;
+    };
+
+    let Some(snapshot) = slot.as_ref() else {
+        return;
+    };
+
+    let path = save_path(EMERGENCY_SAVE_NAME);
+    match write_save(snapshot, &path) {
+        Ok(()) => eprintln!("Аварийное сохранение записано: {:?}", path),
+        Err(e) => eprintln!("Не удалось записать аварийное сохранение: {}", e),
+    }
+}
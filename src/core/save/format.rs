@@ -0,0 +1,125 @@
+use crc::{Crc, CRC_32_ISO_HDLC};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt;
+use std::io::{Read, Write};
+
+const MAGIC: &[u8; 4] = b"FASV";
+
+// Версия самого контейнера (заголовок, сжатие, контрольная сумма), не данных внутри
+const CONTAINER_VERSION: u16 = 1;
+
+// magic + версия контейнера + версия схемы + длина данных + CRC
+const HEADER_SIZE: usize = 4 + 2 + 4 + 4 + 4;
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    NotASave,
+    UnsupportedContainer(u16),
+    Truncated,
+    ChecksumMismatch { expected: u32, actual: u32 },
+    Decompress(std::io::Error),
+    Encode(String),
+    Decode(String),
+    NewerVersion { version: u32, supported: u32 },
+    MissingMigration(u32),
+    Migration { from: u32, message: String },
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(e) => write!(f, "ошибка ввода-вывода: {}", e),
+            SaveError::NotASave => write!(f, "файл не является сохранением"),
+            SaveError::UnsupportedContainer(version) => write!(f, "неподдерживаемая версия контейнера сохранения: {}", version),
+            SaveError::Truncated => write!(f, "файл сохранения обрезан"),
+            SaveError::ChecksumMismatch { expected, actual } => {
+                write!(f, "контрольная сумма не совпадает (ожидалась {:08x}, получена {:08x})", expected, actual)
+            }
+            SaveError::Decompress(e) => write!(f, "не удалось распаковать данные: {}", e),
+            SaveError::Encode(e) => write!(f, "не удалось сериализовать данные: {}", e),
+            SaveError::Decode(e) => write!(f, "не удалось разобрать данные: {}", e),
+            SaveError::NewerVersion { version, supported } => {
+                write!(f, "сохранение создано более новой версией игры (схема {}, поддерживается до {})", version, supported)
+            }
+            SaveError::MissingMigration(version) => write!(f, "нет миграции со схемы {}", version),
+            SaveError::Migration { from, message } => write!(f, "ошибка миграции со схемы {}: {}", from, message),
+        }
+    }
+}
+
+impl From<std::io::Error> for SaveError {
+    fn from(e: std::io::Error) -> Self {
+        SaveError::Io(e)
+    }
+}
+
+pub struct SaveContainer {
+    pub schema_version: u32,
+    pub payload: Vec<u8>,
+}
+
+pub fn write_container(schema_version: u32, payload: &[u8]) -> Result<Vec<u8>, SaveError> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(payload)?;
+    let compressed = encoder.finish()?;
+
+    let crc = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+
+    let mut bytes = Vec::with_capacity(HEADER_SIZE + compressed.len());
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&CONTAINER_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&schema_version.to_le_bytes());
+    bytes.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&crc.checksum(&compressed).to_le_bytes());
+    bytes.extend_from_slice(&compressed);
+
+    Ok(bytes)
+}
+
+pub fn read_container(bytes: &[u8]) -> Result<SaveContainer, SaveError> {
+    if bytes.len() < MAGIC.len() || &bytes[..MAGIC.len()] != MAGIC {
+        return Err(SaveError::NotASave);
+    }
+    if bytes.len() < HEADER_SIZE {
+        return Err(SaveError::Truncated);
+    }
+
+    let container_version = u16::from_le_bytes([bytes[4], bytes[5]]);
+    if container_version != CONTAINER_VERSION {
+        return Err(SaveError::UnsupportedContainer(container_version));
+    }
+
+    let schema_version = u32::from_le_bytes(bytes[6..10].try_into().unwrap());
+    let length = u32::from_le_bytes(bytes[10..14].try_into().unwrap()) as usize;
+    let expected = u32::from_le_bytes(bytes[14..18].try_into().unwrap());
+
+    let compressed = bytes.get(HEADER_SIZE..HEADER_SIZE + length).ok_or(SaveError::Truncated)?;
+
+    let actual = Crc::<u32>::new(&CRC_32_ISO_HDLC).checksum(compressed);
+    if actual != expected {
+        return Err(SaveError::ChecksumMismatch { expected, actual });
+    }
+
+    let mut payload = Vec::new();
+    DeflateDecoder::new(compressed)
+        .read_to_end(&mut payload)
+        .map_err(SaveError::Decompress)?;
+
+    Ok(SaveContainer { schema_version, payload })
+}
+
+pub fn encode_payload<T: Serialize>(value: &T) -> Result<Vec<u8>, SaveError> {
+    bincode::serde::encode_to_vec(value, bincode::config::standard())
+        .map_err(|e| SaveError::Encode(e.to_string()))
+}
+
+pub fn decode_payload<T: DeserializeOwned>(payload: &[u8]) -> Result<T, SaveError> {
+    bincode::serde::decode_from_slice::<T, _>(payload, bincode::config::standard())
+        .map(|(value, _)| value)
+        .map_err(|e| SaveError::Decode(e.to_string()))
+}
@@ -0,0 +1,264 @@
+use crate::core::save::format::{decode_payload, encode_payload, SaveError};
+use crate::core::save::{SaveGame, SavedDiplomacy, SAVE_SCHEMA_VERSION};
+use crate::core::simulation::SimulationSeed;
+
+// Шаг миграции переводит данные схемы `from` в схему `from + 1`.
+// Старые версии структур для decode_payload лежат рядом с шагом, текущая — SaveGame
+pub struct Migration {
+    pub from: u32,
+    pub description: &'static str,
+    pub apply: fn(Vec<u8>) -> Result<Vec<u8>, SaveError>,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 1,
+        description: "армии",
+        apply: v1_to_v2,
+    },
+    Migration {
+        from: 2,
+        description: "зерно симуляции",
+        apply: v2_to_v3,
+    },
+    Migration {
+        from: 3,
+        description: "казна стран",
+        apply: v3_to_v4,
+    },
+    Migration {
+        from: 4,
+        description: "туман войны",
+        apply: v4_to_v5,
+    },
+    Migration {
+        from: 5,
+        description: "дипломатия",
+        apply: v5_to_v6,
+    },
+];
+
+// Схема 1 зафиксирована как была: до появления армий
+mod v1 {
+    use crate::core::map::camera::bookmarks::CameraBookmark;
+    use crate::core::save::SavedCountry;
+    use serde::Deserialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Deserialize)]
+    pub struct SaveGame {
+        pub date: i32,
+        pub days_elapsed: u64,
+        pub countries: Vec<SavedCountry>,
+        pub camera: Option<CameraBookmark>,
+        pub bookmarks: BTreeMap<u8, CameraBookmark>,
+    }
+}
+
+// Схема 2: армии без зерна симуляции
+mod v2 {
+    use crate::core::map::camera::bookmarks::CameraBookmark;
+    use crate::core::save::{SavedCountry, SavedUnit};
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize, Deserialize)]
+    pub struct SaveGame {
+        pub date: i32,
+        pub days_elapsed: u64,
+        pub countries: Vec<SavedCountry>,
+        pub camera: Option<CameraBookmark>,
+        pub bookmarks: BTreeMap<u8, CameraBookmark>,
+        pub units: Vec<SavedUnit>,
+    }
+}
+
+// Схема 3: зерно симуляции без казны стран
+mod v3 {
+    use crate::core::map::camera::bookmarks::CameraBookmark;
+    use crate::core::save::{SavedCountry, SavedUnit};
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize, Deserialize)]
+    pub struct SaveGame {
+        pub date: i32,
+        pub days_elapsed: u64,
+        pub countries: Vec<SavedCountry>,
+        pub camera: Option<CameraBookmark>,
+        pub bookmarks: BTreeMap<u8, CameraBookmark>,
+        pub units: Vec<SavedUnit>,
+        pub seed: u64,
+    }
+}
+
+// Схема 4: казна стран без тумана войны и выбранной игроком страны
+mod v4 {
+    use crate::core::map::camera::bookmarks::CameraBookmark;
+    use crate::core::save::{SavedCountry, SavedTreasury, SavedUnit};
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize, Deserialize)]
+    pub struct SaveGame {
+        pub date: i32,
+        pub days_elapsed: u64,
+        pub countries: Vec<SavedCountry>,
+        pub camera: Option<CameraBookmark>,
+        pub bookmarks: BTreeMap<u8, CameraBookmark>,
+        pub units: Vec<SavedUnit>,
+        pub seed: u64,
+        pub treasuries: Vec<SavedTreasury>,
+    }
+}
+
+// Схема 5: туман войны и страна игрока без дипломатии
+mod v5 {
+    use crate::core::map::camera::bookmarks::CameraBookmark;
+    use crate::core::save::{SavedCountry, SavedTreasury, SavedUnit, SavedVisibility};
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize, Deserialize)]
+    pub struct SaveGame {
+        pub date: i32,
+        pub days_elapsed: u64,
+        pub countries: Vec<SavedCountry>,
+        pub camera: Option<CameraBookmark>,
+        pub bookmarks: BTreeMap<u8, CameraBookmark>,
+        pub units: Vec<SavedUnit>,
+        pub seed: u64,
+        pub treasuries: Vec<SavedTreasury>,
+        pub player: Option<String>,
+        pub visibility: Vec<SavedVisibility>,
+    }
+}
+
+fn v1_to_v2(payload: Vec<u8>) -> Result<Vec<u8>, SaveError> {
+    let old: v1::SaveGame = decode_payload(&payload)?;
+    encode_payload(&v2::SaveGame {
+        date: old.date,
+        days_elapsed: old.days_elapsed,
+        countries: old.countries,
+        camera: old.camera,
+        bookmarks: old.bookmarks,
+        units: Vec::new(),
+    })
+}
+
+fn v2_to_v3(payload: Vec<u8>) -> Result<Vec<u8>, SaveError> {
+    let old: v2::SaveGame = decode_payload(&payload)?;
+    encode_payload(&v3::SaveGame {
+        date: old.date,
+        days_elapsed: old.days_elapsed,
+        countries: old.countries,
+        camera: old.camera,
+        bookmarks: old.bookmarks,
+        units: old.units,
+        seed: SimulationSeed::default().0,
+    })
+}
+
+// Казна начинается с нуля, как в новой партии
+fn v3_to_v4(payload: Vec<u8>) -> Result<Vec<u8>, SaveError> {
+    let old: v3::SaveGame = decode_payload(&payload)?;
+    encode_payload(&v4::SaveGame {
+        date: old.date,
+        days_elapsed: old.days_elapsed,
+        countries: old.countries,
+        camera: old.camera,
+        bookmarks: old.bookmarks,
+        units: old.units,
+        seed: old.seed,
+        treasuries: Vec::new(),
+    })
+}
+
+// Игрок в старых сохранениях не выбран, а туман без разведки пересчитается на первом же тике
+fn v4_to_v5(payload: Vec<u8>) -> Result<Vec<u8>, SaveError> {
+    let old: v4::SaveGame = decode_payload(&payload)?;
+    encode_payload(&v5::SaveGame {
+        date: old.date,
+        days_elapsed: old.days_elapsed,
+        countries: old.countries,
+        camera: old.camera,
+        bookmarks: old.bookmarks,
+        units: old.units,
+        seed: old.seed,
+        treasuries: old.treasuries,
+        player: None,
+        visibility: Vec::new(),
+    })
+}
+
+// Старые партии продолжаются в мире: без войн, союзов и накопленных мнений
+fn v5_to_v6(payload: Vec<u8>) -> Result<Vec<u8>, SaveError> {
+    let old: v5::SaveGame = decode_payload(&payload)?;
+    encode_payload(&SaveGame {
+        date: old.date,
+        days_elapsed: old.days_elapsed,
+        countries: old.countries,
+        camera: old.camera,
+        bookmarks: old.bookmarks,
+        units: old.units,
+        seed: old.seed,
+        treasuries: old.treasuries,
+        player: old.player,
+        visibility: old.visibility,
+        diplomacy: SavedDiplomacy::default(),
+    })
+}
+
+pub fn migrate(version: u32, payload: Vec<u8>) -> Result<Vec<u8>, SaveError> {
+    if version > SAVE_SCHEMA_VERSION {
+        return Err(SaveError::NewerVersion { version, supported: SAVE_SCHEMA_VERSION });
+    }
+
+    let mut version = version;
+    let mut payload = payload;
+
+    while version < SAVE_SCHEMA_VERSION {
+        let step = MIGRATIONS.iter()
+            .find(|m| m.from == version)
+            .ok_or(SaveError::MissingMigration(version))?;
+
+        #[cfg(debug_assertions)]
+        println!("Миграция сохранения {} -> {}: {}", version, version + 1, step.description);
+
+        payload = (step.apply)(payload)
+            .map_err(|e| SaveError::Migration { from: version, message: e.to_string() })?;
+        version += 1;
+    }
+
+    Ok(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::save::{read_save, SAVE_EXTENSION};
+    use std::fs;
+
+    // Перед повышением SAVE_SCHEMA_VERSION сюда кладётся сохранение текущей версии
+    const SAVE_CORPUS_DIR: &str = "common/saves/corpus";
+
+    // Все сохранения из корпуса старых версий должны загружаться текущей сборкой
+    #[test]
+    fn save_corpus_loads() {
+        let entries = fs::read_dir(SAVE_CORPUS_DIR).expect("нет каталога корпуса сохранений");
+
+        let mut loaded = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(SAVE_EXTENSION) {
+                continue;
+            }
+
+            if let Err(e) = read_save(&path) {
+                panic!("сохранение из корпуса {:?} не загружается: {}", path, e);
+            }
+            loaded += 1;
+        }
+
+        assert!(loaded > 0, "корпус сохранений пуст");
+    }
+}
@@ -0,0 +1,465 @@
+pub(crate) mod autosave;
+pub(crate) mod format;
+pub(crate) mod migration;
+
+use crate::core::ai::AiState;
+use crate::core::country::{CountryId, CountryRegistry, PlayerCountry, ProvinceControllerChanged, ProvinceOwnerChanged};
+use crate::core::diplomacy::{Diplomacy, PendingDiplomaticRequests, War};
+use crate::core::economy::{Treasuries, Treasury};
+use crate::core::fog::{update_fog_of_war, CountryVisibility, FogOfWar};
+use crate::core::input::{ActionState, InputAction};
+use crate::core::map::camera::bookmarks::{current_camera_view, CameraBookmark, CameraBookmarks};
+use crate::core::map::camera::fly_to::CameraFlyTo;
+use crate::core::map::province::selection::SelectedProvince;
+use crate::core::map::province::{ProvinceId, ProvinceRegistry};
+use crate::core::save::autosave::{autosave, update_emergency_snapshot};
+use crate::core::save::format::{decode_payload, encode_payload, read_container, write_container, SaveError};
+use crate::core::save::migration::migrate;
+use crate::core::simulation::calendar::GameCalendar;
+use crate::core::simulation::{run_simulation, SimulationClock, SimulationSeed};
+use crate::core::unit::battle::Battle;
+use crate::core::unit::occupation::OccupationProgress;
+use crate::core::unit::{NextUnitIndex, SpawnUnit, Unit, UnitPath, UnitPosition};
+use crate::pkg::dir::{init_dir, saves_directory};
+use bevy::ecs::system::RunSystemOnce;
+use bevy::math::Vec2;
+use bevy::prelude::{App, DespawnRecursiveExt, Entity, Event, EventWriter, Events, IntoSystemConfigs, Res, Update, With, World};
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// При изменении SaveGame версия увеличивается, а в migration::MIGRATIONS добавляется шаг со старой версии
+pub const SAVE_SCHEMA_VERSION: u32 = 6;
+pub const SAVE_EXTENSION: &str = "sav";
+pub const QUICKSAVE_NAME: &str = "quicksave";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SavedCountry {
+    pub tag: String,
+    pub name: String,
+    pub color: [u8; 3],
+    pub capital: Option<ProvinceId>,
+    pub owned: Vec<ProvinceId>,
+    pub controlled: Vec<ProvinceId>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SavedUnit {
+    pub owner: String,
+    pub strength: u32,
+    pub position: [f32; 2],
+    pub path: Vec<[f32; 2]>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SavedTreasury {
+    pub country: String,
+    pub gold: f64,
+    pub manpower: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SavedVisibility {
+    pub country: String,
+    pub explored: Vec<ProvinceId>,
+    pub visible: Vec<ProvinceId>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SavedWar {
+    pub attacker: String,
+    pub defender: String,
+    pub attackers: Vec<String>,
+    pub defenders: Vec<String>,
+    pub started: u64,
+}
+
+// Страны указаны тегами, дни — в днях от начала партии, как days_elapsed
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SavedDiplomacy {
+    pub opinions: Vec<(String, String, i32)>,
+    pub alliances: Vec<(String, String)>,
+    pub truces: Vec<(String, String, u64)>,
+    pub wars: Vec<SavedWar>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SaveGame {
+    // Дата хранится как число дней от начала нашей эры
+    pub date: i32,
+    pub days_elapsed: u64,
+    pub countries: Vec<SavedCountry>,
+    pub camera: Option<CameraBookmark>,
+    pub bookmarks: BTreeMap<u8, CameraBookmark>,
+    pub units: Vec<SavedUnit>,
+    pub seed: u64,
+    pub treasuries: Vec<SavedTreasury>,
+    pub player: Option<String>,
+    pub visibility: Vec<SavedVisibility>,
+    pub diplomacy: SavedDiplomacy,
+}
+
+#[derive(Event)]
+pub struct SaveGameRequest {
+    pub name: String,
+}
+
+#[derive(Event)]
+pub struct LoadGameRequest {
+    pub name: String,
+}
+
+#[derive(Event)]
+pub struct GameSaved {
+    pub path: PathBuf,
+}
+
+#[derive(Event)]
+pub struct GameLoaded {
+    pub path: PathBuf,
+}
+
+pub fn build(app: &mut App) {
+    app.add_event::<SaveGameRequest>();
+    app.add_event::<LoadGameRequest>();
+    app.add_event::<GameSaved>();
+    app.add_event::<GameLoaded>();
+
+    app.add_systems(Update, (
+        quicksave_input,
+        autosave,
+        process_save_requests,
+        update_emergency_snapshot,
+    ).chain().after(run_simulation));
+}
+
+pub fn save_path(name: &str) -> PathBuf {
+    saves_directory().join(format!("{}.{}", name, SAVE_EXTENSION))
+}
+
+fn quicksave_input(
+    actions: Res<ActionState>,
+    mut save: EventWriter<SaveGameRequest>,
+    mut load: EventWriter<LoadGameRequest>,
+) {
+    if actions.just_pressed(InputAction::QuickSave) {
+        save.send(SaveGameRequest { name: QUICKSAVE_NAME.to_string() });
+    }
+
+    if actions.just_pressed(InputAction::QuickLoad) {
+        load.send(LoadGameRequest { name: QUICKSAVE_NAME.to_string() });
+    }
+}
+
+// Эксклюзивная система: снимок берётся между тиками симуляции, поэтому состояние всегда согласовано
+pub(crate) fn process_save_requests(world: &mut World) {
+    let saves: Vec<SaveGameRequest> = world.resource_mut::<Events<SaveGameRequest>>().drain().collect();
+    for request in saves {
+        let path = save_path(&request.name);
+        match write_save(&capture(world), &path) {
+            Ok(()) => {
+                #[cfg(debug_assertions)]
+                println!("Игра сохранена: {:?}", path);
+
+                world.send_event(GameSaved { path });
+            }
+            Err(e) => eprintln!("Не удалось сохранить игру в {:?}: {}", path, e),
+        }
+    }
+
+    let loads: Vec<LoadGameRequest> = world.resource_mut::<Events<LoadGameRequest>>().drain().collect();
+    if let Some(request) = loads.into_iter().last() {
+        let path = save_path(&request.name);
+        match read_save(&path) {
+            Ok(save) => {
+                restore(world, save);
+
+                #[cfg(debug_assertions)]
+                println!("Игра загружена: {:?}", path);
+
+                world.send_event(GameLoaded { path });
+            }
+            Err(e) => eprintln!("Не удалось загрузить сохранение {:?}: {}", path, e),
+        }
+    }
+}
+
+pub fn write_save(save: &SaveGame, path: &Path) -> Result<(), SaveError> {
+    let payload = encode_payload(save)?;
+    let bytes = write_container(SAVE_SCHEMA_VERSION, &payload)?;
+
+    init_dir(saves_directory())?;
+    write_atomic(path, &bytes)?;
+    Ok(())
+}
+
+// Запись во временный файл и переименование: при падении посреди записи прежнее сохранение остаётся целым
+fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let temp_path = path.with_extension(format!("{}.tmp", SAVE_EXTENSION));
+
+    let mut file = File::create(&temp_path)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+
+    fs::rename(&temp_path, path)
+}
+
+pub fn read_save(path: &Path) -> Result<SaveGame, SaveError> {
+    let bytes = fs::read(path)?;
+    let container = read_container(&bytes)?;
+    let payload = migrate(container.schema_version, container.payload)?;
+    decode_payload(&payload)
+}
+
+pub fn capture(world: &mut World) -> SaveGame {
+    let mut units_query = world.query::<(&Unit, &UnitPosition, &UnitPath)>();
+    let calendar = world.resource::<GameCalendar>();
+    let countries = world.resource::<CountryRegistry>();
+
+    let saved_countries = countries.iter()
+        .map(|country| SavedCountry {
+            tag: country.tag.clone(),
+            name: country.name.clone(),
+            color: country.color,
+            capital: country.capital,
+            owned: country.owned.iter().copied().collect(),
+            controlled: country.controlled.iter().copied().collect(),
+        })
+        .collect();
+
+    let date = calendar.date().num_days_from_ce();
+    let days_elapsed = calendar.days_elapsed();
+    let bookmarks = world.resource::<CameraBookmarks>().slots.clone();
+
+    // Армии сохраняются по порядку номеров: после загрузки номера пересчитываются с нуля, а порядок остаётся
+    let mut units: Vec<_> = units_query.iter(world).collect();
+    units.sort_by_key(|(unit, ..)| unit.index);
+    let units = units.into_iter()
+        .filter_map(|(unit, position, path)| {
+            Some(SavedUnit {
+                owner: countries.get(unit.owner)?.tag.clone(),
+                strength: unit.strength,
+                position: position.current.to_array(),
+                path: path.waypoints.iter().map(|p| p.to_array()).collect(),
+            })
+        })
+        .collect();
+
+    let treasuries = world.resource::<Treasuries>().countries.iter()
+        .filter_map(|(id, treasury)| {
+            Some(SavedTreasury {
+                country: countries.get(*id)?.tag.clone(),
+                gold: treasury.gold,
+                manpower: treasury.manpower,
+            })
+        })
+        .collect();
+
+    let visibility = world.resource::<FogOfWar>().countries.iter()
+        .filter_map(|(id, visibility)| {
+            Some(SavedVisibility {
+                country: countries.get(*id)?.tag.clone(),
+                explored: visibility.explored.iter().copied().collect(),
+                visible: visibility.visible.iter().copied().collect(),
+            })
+        })
+        .collect();
+
+    let player = world.resource::<PlayerCountry>().0
+        .and_then(|id| countries.get(id))
+        .map(|country| country.tag.clone());
+    let diplomacy = capture_diplomacy(world.resource::<Diplomacy>(), countries);
+
+    SaveGame {
+        date,
+        days_elapsed,
+        countries: saved_countries,
+        camera: current_camera_view(world),
+        bookmarks,
+        units,
+        seed: world.resource::<SimulationSeed>().0,
+        treasuries,
+        player,
+        visibility,
+        diplomacy,
+    }
+}
+
+fn capture_diplomacy(diplomacy: &Diplomacy, countries: &CountryRegistry) -> SavedDiplomacy {
+    let tag = |id: CountryId| countries.get(id).map(|country| country.tag.clone());
+    let tags = |ids: &BTreeSet<CountryId>| ids.iter().filter_map(|id| tag(*id)).collect();
+
+    SavedDiplomacy {
+        opinions: diplomacy.opinion_entries()
+            .filter_map(|((of, about), opinion)| Some((tag(of)?, tag(about)?, opinion)))
+            .collect(),
+        alliances: diplomacy.alliance_entries()
+            .filter_map(|(a, b)| Some((tag(a)?, tag(b)?)))
+            .collect(),
+        truces: diplomacy.truce_entries()
+            .filter_map(|((a, b), until)| Some((tag(a)?, tag(b)?, until)))
+            .collect(),
+        wars: diplomacy.wars().iter()
+            .filter_map(|war| {
+                Some(SavedWar {
+                    attacker: tag(war.attacker)?,
+                    defender: tag(war.defender)?,
+                    attackers: tags(&war.attackers),
+                    defenders: tags(&war.defenders),
+                    started: war.started,
+                })
+            })
+            .collect(),
+    }
+}
+
+fn restore_diplomacy(saved: &SavedDiplomacy, countries: &CountryRegistry) -> Diplomacy {
+    let id = |tag: &String| countries.id_by_tag(tag);
+    let ids = |tags: &[String]| tags.iter().filter_map(id).collect();
+
+    let mut diplomacy = Diplomacy::default();
+    for (of, about, opinion) in saved.opinions.iter() {
+        if let (Some(of), Some(about)) = (id(of), id(about)) {
+            diplomacy.change_opinion(of, about, *opinion);
+        }
+    }
+    for (a, b) in saved.alliances.iter() {
+        if let (Some(a), Some(b)) = (id(a), id(b)) {
+            diplomacy.insert_alliance(a, b);
+        }
+    }
+    for (a, b, until) in saved.truces.iter() {
+        if let (Some(a), Some(b)) = (id(a), id(b)) {
+            diplomacy.insert_truce(a, b, *until);
+        }
+    }
+    for war in saved.wars.iter() {
+        if let (Some(attacker), Some(defender)) = (id(&war.attacker), id(&war.defender)) {
+            diplomacy.insert_war(War {
+                attacker,
+                defender,
+                attackers: ids(&war.attackers),
+                defenders: ids(&war.defenders),
+                started: war.started,
+            });
+        }
+    }
+
+    diplomacy
+}
+
+pub fn restore(world: &mut World, save: SaveGame) {
+    match NaiveDate::from_num_days_from_ce_opt(save.date) {
+        Some(date) => world.insert_resource(GameCalendar::restore(date, save.days_elapsed)),
+        None => eprintln!("Некорректная дата в сохранении: {}", save.date),
+    }
+
+    let mut countries = CountryRegistry::default();
+    for saved in save.countries.iter() {
+        countries.insert(saved.tag.clone(), saved.name.clone(), saved.color, saved.capital);
+    }
+    for saved in save.countries.iter() {
+        let Some(id) = countries.id_by_tag(&saved.tag) else {
+            continue;
+        };
+        for province in saved.owned.iter() {
+            countries.set_owner(*province, Some(id));
+        }
+        for province in saved.controlled.iter() {
+            countries.set_controller(*province, Some(id));
+        }
+    }
+
+    // Изменения владельцев рассылаются теми же событиями, что и в игре, чтобы обновились границы и режимы карты
+    let provinces: Vec<ProvinceId> = world.resource::<ProvinceRegistry>().iter().map(|p| p.id).collect();
+    let previous = world.resource::<CountryRegistry>();
+    let mut owner_changes = Vec::new();
+    let mut controller_changes = Vec::new();
+    for province in provinces {
+        let (old_owner, new_owner) = (previous.owner_of(province), countries.owner_of(province));
+        if old_owner != new_owner {
+            owner_changes.push(ProvinceOwnerChanged { province, previous: old_owner, owner: new_owner });
+        }
+
+        let (old_controller, new_controller) = (previous.controller_of(province), countries.controller_of(province));
+        if old_controller != new_controller {
+            controller_changes.push(ProvinceControllerChanged { province, previous: old_controller, controller: new_controller });
+        }
+    }
+
+    let mut treasuries = Treasuries::default();
+    for saved in save.treasuries.iter() {
+        if let Some(id) = countries.id_by_tag(&saved.country) {
+            treasuries.countries.insert(id, Treasury { gold: saved.gold, manpower: saved.manpower });
+        }
+    }
+
+    let mut fog = FogOfWar::default();
+    for saved in save.visibility.iter() {
+        if let Some(id) = countries.id_by_tag(&saved.country) {
+            fog.countries.insert(id, CountryVisibility {
+                explored: saved.explored.iter().copied().collect(),
+                visible: saved.visible.iter().copied().collect(),
+            });
+        }
+    }
+    let player = PlayerCountry(save.player.as_deref().and_then(|tag| countries.id_by_tag(tag)));
+    let diplomacy = restore_diplomacy(&save.diplomacy, &countries);
+
+    world.insert_resource(countries);
+    world.insert_resource(treasuries);
+    world.insert_resource(fog);
+    world.insert_resource(player);
+    world.insert_resource(diplomacy);
+    world.send_event_batch(owner_changes);
+    world.send_event_batch(controller_changes);
+
+    world.resource_mut::<CameraBookmarks>().slots = save.bookmarks;
+    if let Some(camera) = save.camera {
+        world.send_event(
+            CameraFlyTo::new(camera.focus)
+                .with_height(camera.height)
+                .with_yaw(camera.yaw)
+                .with_duration(0.0)
+        );
+    }
+
+    world.insert_resource(SimulationSeed(save.seed));
+    world.insert_resource(OccupationProgress::default());
+    world.insert_resource(PendingDiplomaticRequests::default());
+    world.resource_mut::<AiState>().reset();
+
+    // Армии пересоздаются целиком: сущности из сохранения не переносятся, идущие бои начнутся заново (см. Battle)
+    let mut existing: Vec<Entity> = world.query_filtered::<Entity, With<Unit>>().iter(world).collect();
+    existing.extend(world.query_filtered::<Entity, With<Battle>>().iter(world));
+    for entity in existing {
+        world.entity_mut(entity).despawn_recursive();
+    }
+    world.insert_resource(NextUnitIndex::default());
+
+    let countries = world.resource::<CountryRegistry>();
+    let spawns: Vec<SpawnUnit> = save.units.iter()
+        .filter_map(|saved| {
+            Some(SpawnUnit {
+                owner: countries.id_by_tag(&saved.owner)?,
+                strength: saved.strength,
+                position: Vec2::from_array(saved.position),
+                path: saved.path.iter().copied().map(Vec2::from_array).collect(),
+            })
+        })
+        .collect();
+    world.send_event_batch(spawns);
+
+    // Сохранения до тумана войны не несут разведки: открываем хотя бы свои провинции, не дожидаясь тика
+    if save.visibility.is_empty() {
+        if let Err(e) = world.run_system_once(update_fog_of_war) {
+            eprintln!("Не удалось пересчитать туман войны: {}", e);
+        }
+    }
+
+    world.resource_mut::<SelectedProvince>().0 = None;
+    world.resource_mut::<SimulationClock>().paused = true;
+}
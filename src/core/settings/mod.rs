@@ -0,0 +1,60 @@
+use crate::core::input::InputSettings;
+use crate::core::map::camera::bookmarks::CameraBookmarks;
+use crate::core::map::wrap::MapSettings;
+use crate::core::save::autosave::AutosaveSettings;
+use crate::pkg::dir::{init_dir, settings_directory};
+use bevy::prelude::{App, Resource};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Resource, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct UserSettings {
+    pub input: InputSettings,
+    pub camera_bookmarks: CameraBookmarks,
+    pub autosave: AutosaveSettings,
+    pub map: MapSettings,
+}
+
+impl UserSettings {
+    pub fn load() -> Self {
+        let path = settings_file();
+
+        let mut settings = match fs::read_to_string(&path) {
+            Ok(content) => match ron::from_str::<UserSettings>(&content) {
+                Ok(settings) => settings,
+                Err(e) => {
+                    eprintln!("Не удалось разобрать файл настроек {:?}: {}", path, e);
+                    UserSettings::default()
+                }
+            },
+            Err(_) => {
+                let settings = UserSettings::default();
+                if let Err(e) = settings.save() {
+                    eprintln!("Не удалось сохранить настройки по умолчанию: {}", e);
+                }
+                settings
+            }
+        };
+
+        settings.input.fill_missing_bindings();
+        settings
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        init_dir(settings_directory())?;
+
+        let content = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+        fs::write(settings_file(), content)
+    }
+}
+
+pub fn settings_file() -> PathBuf {
+    settings_directory().join("settings.ron")
+}
+
+pub fn build(app: &mut App) {
+    app.insert_resource(UserSettings::load());
+}
@@ -0,0 +1,48 @@
+use bevy::prelude::Resource;
+use chrono::{Datelike, NaiveDate};
+
+pub const START_DATE: (i32, u32, u32) = (1444, 11, 11);
+
+#[derive(Resource, Clone, Debug)]
+pub struct GameCalendar {
+    date: NaiveDate,
+    days_elapsed: u64,
+}
+
+impl Default for GameCalendar {
+    fn default() -> Self {
+        let (year, month, day) = START_DATE;
+        Self::new(NaiveDate::from_ymd_opt(year, month, day).expect("некорректная стартовая дата"))
+    }
+}
+
+impl GameCalendar {
+    pub fn new(date: NaiveDate) -> Self {
+        Self { date, days_elapsed: 0 }
+    }
+
+    pub fn restore(date: NaiveDate, days_elapsed: u64) -> Self {
+        Self { date, days_elapsed }
+    }
+
+    pub fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    pub fn days_elapsed(&self) -> u64 {
+        self.days_elapsed
+    }
+
+    // Возвращает true, если с этим днём начался новый месяц
+    pub fn advance_day(&mut self) -> bool {
+        let next = self.date.succ_opt().expect("дата вышла за пределы календаря");
+        let new_month = next.month() != self.date.month();
+        self.date = next;
+        self.days_elapsed += 1;
+        new_month
+    }
+
+    pub fn format(&self) -> String {
+        self.date.format("%d.%m.%Y").to_string()
+    }
+}
@@ -0,0 +1,50 @@
+use crate::core::simulation::calendar::GameCalendar;
+use crate::core::simulation::SimulationClock;
+use bevy::color::Color;
+use bevy::prelude::{default, resource_changed, Alpha, App, BackgroundColor, BuildChildren, ChildBuild, Commands, Component, Condition, IntoSystemConfigs, Node, PositionType, Res, Single, Startup, Text, TextFont, UiRect, Update, Val, With};
+
+#[derive(Component)]
+struct DateText;
+
+pub fn build(app: &mut App) {
+    app.add_systems(Startup, init);
+    app.add_systems(Update, update_date_text.run_if(resource_changed::<GameCalendar>.or(resource_changed::<SimulationClock>)));
+}
+
+fn init(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(8.0),
+                right: Val::Px(8.0),
+                padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                ..default()
+            },
+            BackgroundColor(Color::BLACK.with_alpha(0.55)),
+        ))
+        .with_children(|p| {
+            p.spawn((
+                Text::default(),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                DateText,
+            ));
+        });
+}
+
+fn update_date_text(
+    calendar: Res<GameCalendar>,
+    clock: Res<SimulationClock>,
+    mut text: Single<&mut Text, With<DateText>>,
+) {
+    let status = if clock.paused {
+        "пауза".to_string()
+    } else {
+        format!("скорость {}", clock.speed())
+    };
+
+    text.0 = format!("{}  |  {}", calendar.format(), status);
+}
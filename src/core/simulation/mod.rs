@@ -0,0 +1,199 @@
+pub(crate) mod calendar;
+mod date_panel;
+
+use crate::core::input::{ActionState, InputAction};
+use crate::core::simulation::calendar::GameCalendar;
+use bevy::ecs::schedule::ScheduleLabel;
+use bevy::prelude::{App, DetectChangesMut, Event, IntoSystemConfigs, Res, ResMut, Resource, Time, Update, World};
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+
+pub const MIN_SPEED: u8 = 1;
+pub const MAX_SPEED: u8 = 5;
+
+// Реальная длительность игрового дня в секундах для скоростей 1..=5
+const DAY_DURATION: [f32; 5] = [2.0, 1.0, 0.5, 0.2, 0.05];
+
+// Ограничение на случай долгого кадра: лишние дни отбрасываются, а не копятся
+const MAX_TICKS_PER_FRAME: u32 = 8;
+
+const DEFAULT_SEED: u64 = 0x1444_1111;
+
+// Один игровой день. Системы здесь не должны зависеть от Time: результат определяется только числом тиков
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DailyTick;
+
+// Выполняется после DailyTick в первый день каждого месяца
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MonthlyTick;
+
+// Дата наступившего дня берётся из GameCalendar, он уже обновлён к моменту события
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DayPassed;
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct MonthPassed {
+    pub date: NaiveDate,
+}
+
+// Общее зерно партии: от него производятся все случайные величины симуляции, сохраняется вместе с игрой
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct SimulationSeed(pub u64);
+
+impl Default for SimulationSeed {
+    fn default() -> Self {
+        Self(DEFAULT_SEED)
+    }
+}
+
+// Результаты, которые считаются вне тика, но нужны симуляции к определённому дню. Пока задержка
+// не снята, этот день не наступает и часы стоят: итог партии не зависит от скорости машины
+#[derive(Resource, Default)]
+pub struct SimulationHolds {
+    holds: BTreeMap<&'static str, u64>,
+}
+
+impl SimulationHolds {
+    pub fn hold(&mut self, holder: &'static str, day: u64) {
+        self.holds.insert(holder, day);
+    }
+
+    pub fn release(&mut self, holder: &'static str) {
+        self.holds.remove(holder);
+    }
+
+    pub fn is_held_by(&self, holder: &'static str) -> bool {
+        self.holds.contains_key(holder)
+    }
+
+    fn blocks(&self, day: u64) -> bool {
+        self.holds.values().any(|held| *held <= day)
+    }
+}
+
+#[derive(Resource)]
+pub struct SimulationClock {
+    pub paused: bool,
+    speed: u8,
+    accumulator: f32,
+}
+
+impl Default for SimulationClock {
+    fn default() -> Self {
+        Self {
+            paused: true,
+            speed: MIN_SPEED,
+            accumulator: 0.0,
+        }
+    }
+}
+
+impl SimulationClock {
+    pub fn speed(&self) -> u8 {
+        self.speed
+    }
+
+    pub fn set_speed(&mut self, speed: u8) {
+        self.speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    // Доля текущего дня, уже прошедшая в реальном времени: для плавной интерполяции между тиками
+    pub fn tick_progress(&self) -> f32 {
+        let day = DAY_DURATION[(self.speed - MIN_SPEED) as usize];
+        (self.accumulator / day).clamp(0.0, 1.0)
+    }
+
+    fn consume(&mut self, delta: f32) -> u32 {
+        if self.paused {
+            return 0;
+        }
+
+        let day = DAY_DURATION[(self.speed - MIN_SPEED) as usize];
+        self.accumulator += delta;
+
+        let ticks = (self.accumulator / day) as u32;
+        self.accumulator -= ticks as f32 * day;
+
+        if ticks > MAX_TICKS_PER_FRAME {
+            self.accumulator = 0.0;
+            return MAX_TICKS_PER_FRAME;
+        }
+
+        ticks
+    }
+
+    // Дни, которые не успели наступить из-за задержки, возвращаются и пройдут в следующих кадрах
+    fn refund(&mut self, ticks: u32) {
+        let day = DAY_DURATION[(self.speed - MIN_SPEED) as usize];
+        self.accumulator += ticks as f32 * day;
+    }
+}
+
+pub fn build(app: &mut App) {
+    app.init_resource::<GameCalendar>();
+    app.init_resource::<SimulationClock>();
+    app.init_resource::<SimulationSeed>();
+    app.init_resource::<SimulationHolds>();
+    app.init_schedule(DailyTick);
+    app.init_schedule(MonthlyTick);
+    app.add_event::<DayPassed>();
+    app.add_event::<MonthPassed>();
+    app.add_systems(Update, (simulation_controls, run_simulation).chain());
+
+    date_panel::build(app);
+}
+
+fn simulation_controls(
+    actions: Res<ActionState>,
+    mut clock: ResMut<SimulationClock>,
+) {
+    if actions.just_pressed(InputAction::TogglePause) {
+        clock.toggle_pause();
+    }
+
+    if actions.just_pressed(InputAction::SpeedUp) {
+        let speed = clock.speed.saturating_add(1);
+        clock.set_speed(speed);
+    }
+
+    if actions.just_pressed(InputAction::SpeedDown) {
+        let speed = clock.speed.saturating_sub(1);
+        clock.set_speed(speed);
+    }
+}
+
+pub fn run_simulation(world: &mut World) {
+    // Пока день задержан, реальное время не копится: после снятия задержки дни не догоняются рывком
+    if world.resource::<SimulationHolds>().blocks(world.resource::<GameCalendar>().days_elapsed() + 1) {
+        return;
+    }
+
+    let delta = world.resource::<Time>().delta_secs();
+    let ticks = world.resource_mut::<SimulationClock>().bypass_change_detection().consume(delta);
+
+    for tick in 0..ticks {
+        let next_day = world.resource::<GameCalendar>().days_elapsed() + 1;
+        if world.resource::<SimulationHolds>().blocks(next_day) {
+            world.resource_mut::<SimulationClock>().bypass_change_detection().refund(ticks - tick);
+            break;
+        }
+
+        let (date, new_month) = {
+            let mut calendar = world.resource_mut::<GameCalendar>();
+            let new_month = calendar.advance_day();
+            (calendar.date(), new_month)
+        };
+
+        world.send_event(DayPassed);
+        world.run_schedule(DailyTick);
+
+        if new_month {
+            world.send_event(MonthPassed { date });
+            world.run_schedule(MonthlyTick);
+        }
+    }
+}
@@ -0,0 +1,514 @@
+use crate::core::country::{CountryId, CountryRegistry};
+use crate::core::diplomacy::Diplomacy;
+use crate::core::map::province::adjacency::ProvinceGraph;
+use crate::core::map::province::{Province, ProvinceId, ProvinceRegistry, TerrainKind};
+use crate::core::simulation::calendar::GameCalendar;
+use crate::core::simulation::SimulationSeed;
+use crate::core::unit::{Unit, UnitPath, UnitPosition};
+use crate::pkg::rng::SeededRng;
+use bevy::prelude::{Commands, Component, DespawnRecursiveExt, Entity, Event, EventWriter, Query, Res, With, Without, World};
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+// Доля силы, которую сторона наносит противнику за день, в промилле: база плюс бросок кубика 1..=6
+const BASE_LOSS_PERMILLE: u64 = 10;
+const ROLL_LOSS_PERMILLE: u64 = 4;
+const ROLL_SIDES: u32 = 6;
+// Сторона бежит, когда от неё остаётся меньше этой доли (в процентах) исходной силы
+const BREAK_PERCENT: u64 = 40;
+const MAX_BATTLE_DAYS: u32 = 30;
+
+// Доля урона атакующего (в процентах), которая доходит до обороняющегося
+fn attacker_percent(terrain: TerrainKind) -> u64 {
+    match terrain {
+        TerrainKind::Plains => 100,
+        TerrainKind::Hills => 80,
+        TerrainKind::Mountains => 60,
+    }
+}
+
+const RIVER_CROSSING_PERCENT: u64 = 75;
+
+// Один день боя; номер дня — позиция в BattleReport::rounds
+#[derive(Clone, Debug, PartialEq)]
+pub struct BattleRound {
+    pub attacker_roll: u32,
+    pub defender_roll: u32,
+    pub attacker_losses: u32,
+    pub defender_losses: u32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BattleReport {
+    pub province: ProvinceId,
+    pub attacker: CountryId,
+    pub defender: CountryId,
+    pub started: NaiveDate,
+    pub terrain: TerrainKind,
+    pub river_crossing: bool,
+    pub attacker_initial: u32,
+    pub defender_initial: u32,
+    pub rounds: Vec<BattleRound>,
+    pub winner: Option<CountryId>,
+}
+
+impl BattleReport {
+    // Сторона страны в бою: true — атакующие. Стороны берутся из той войны, в которой сошлись
+    // атакующий и обороняющийся: союзники по другой войне в этот бой не вступают
+    pub fn side_of(&self, country: CountryId, diplomacy: &Diplomacy) -> Option<bool> {
+        let war = diplomacy.war_between(self.attacker, self.defender)?;
+        let attacking = war.side_of(self.attacker)?;
+        war.side_of(country).map(|side| side == attacking)
+    }
+
+    pub fn attacker_losses(&self) -> u32 {
+        self.rounds.iter().map(|r| r.attacker_losses).sum()
+    }
+
+    pub fn defender_losses(&self) -> u32 {
+        self.rounds.iter().map(|r| r.defender_losses).sum()
+    }
+}
+
+// Бои не сохраняются: после загрузки армии вступают в бой заново, с новым зерном от дня загрузки.
+// Поэтому бой, шедший в момент сохранения, не воспроизводится точно так же, как в непрерывной партии
+#[derive(Component)]
+pub struct Battle {
+    rng: SeededRng,
+    report: BattleReport,
+}
+
+impl Battle {
+    pub fn new(seed: u64, report: BattleReport) -> Self {
+        Self { rng: SeededRng::new(seed), report }
+    }
+
+    pub fn report(&self) -> &BattleReport {
+        &self.report
+    }
+
+    // Бросает кубики и записывает потери дня; распределять их по армиям — дело вызывающего
+    fn fight_round(&mut self, attacker_strength: u32, defender_strength: u32) -> Option<BattleRound> {
+        if attacker_strength == 0 || defender_strength == 0 {
+            return None;
+        }
+
+        let attacker_roll = self.rng.range(1, ROLL_SIDES);
+        let defender_roll = self.rng.range(1, ROLL_SIDES);
+
+        let mut attacker_percent = attacker_percent(self.report.terrain);
+        if self.report.river_crossing {
+            attacker_percent = attacker_percent * RIVER_CROSSING_PERCENT / 100;
+        }
+
+        let to_defender = attacker_strength as u64 * (BASE_LOSS_PERMILLE + ROLL_LOSS_PERMILLE * attacker_roll as u64) * attacker_percent / 100_000;
+        let to_attacker = defender_strength as u64 * (BASE_LOSS_PERMILLE + ROLL_LOSS_PERMILLE * defender_roll as u64) / 1000;
+
+        let round = BattleRound {
+            attacker_roll,
+            defender_roll,
+            attacker_losses: (to_attacker.max(1) as u32).min(attacker_strength),
+            defender_losses: (to_defender.max(1) as u32).min(defender_strength),
+        };
+        self.report.rounds.push(round.clone());
+        Some(round)
+    }
+}
+
+#[derive(Component)]
+pub struct InBattle(pub Entity);
+
+// Отступающая армия не вступает в бой, пока не дойдёт до цели отступления
+#[derive(Component)]
+pub struct Retreating;
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct BattleStarted {
+    pub province: ProvinceId,
+    pub attacker: CountryId,
+    pub defender: CountryId,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct BattleEnded {
+    pub report: BattleReport,
+}
+
+pub(super) fn finish_retreats(
+    mut commands: Commands,
+    units: Query<(Entity, &UnitPath), With<Retreating>>,
+) {
+    for (entity, path) in units.iter() {
+        if path.waypoints.is_empty() {
+            commands.entity(entity).remove::<Retreating>();
+        }
+    }
+}
+
+// Строка подсказки для провинции, где идёт бой: стороны, день и последние броски
+pub(super) fn battle_tooltip(world: &World, province: &Province) -> Option<String> {
+    let report = world.iter_entities()
+        .filter_map(|entity| entity.get::<Battle>())
+        .map(Battle::report)
+        .find(|report| report.province == province.id)?;
+
+    let countries = world.resource::<CountryRegistry>();
+    let name = |id: CountryId| countries.get(id).map(|c| c.name.as_str()).unwrap_or("?");
+
+    let mut line = format!(
+        "Битва с {}: {} против {}, день {}",
+        report.started.format("%d.%m.%Y"),
+        name(report.attacker),
+        name(report.defender),
+        report.rounds.len(),
+    );
+    if let Some(round) = report.rounds.last() {
+        let _ = write!(line, ", кубики {}:{}", round.attacker_roll, round.defender_roll);
+    }
+    Some(line)
+}
+
+#[derive(bevy::ecs::system::SystemParam)]
+pub(super) struct BattleContext<'w> {
+    calendar: Res<'w, GameCalendar>,
+    seed: Res<'w, SimulationSeed>,
+    countries: Res<'w, CountryRegistry>,
+    diplomacy: Res<'w, Diplomacy>,
+    registry: Res<'w, ProvinceRegistry>,
+    graph: Res<'w, ProvinceGraph>,
+}
+
+// Армии, которые могут вступить в бой: не сражаются и не отступают
+type IdleUnits<'w, 's> = Query<'w, 's, (Entity, &'static Unit, &'static UnitPosition), (Without<InBattle>, Without<Retreating>)>;
+
+struct PresentUnit {
+    entity: Entity,
+    owner: CountryId,
+    index: u32,
+    entered_from: Option<ProvinceId>,
+}
+
+// Армии перебираются по владельцу и номеру, провинции — по id: одинаковый мир даёт одинаковые бои
+pub(super) fn start_battles(
+    mut commands: Commands,
+    context: BattleContext,
+    units: IdleUnits,
+    battles: Query<(Entity, &Battle)>,
+    mut started: EventWriter<BattleStarted>,
+) {
+    let BattleContext { calendar, seed, countries, diplomacy, registry, graph } = context;
+
+    let mut by_province: BTreeMap<ProvinceId, Vec<PresentUnit>> = BTreeMap::new();
+    for (entity, unit, position) in units.iter() {
+        if let Some(province) = position.province {
+            by_province.entry(province).or_default().push(PresentUnit {
+                entity,
+                owner: unit.owner,
+                index: unit.index,
+                entered_from: position.entered_from,
+            });
+        }
+    }
+
+    for (province, mut present) in by_province {
+        present.sort_by_key(|unit| (unit.owner, unit.index));
+
+        // Подкрепления сторон и их союзников по войне присоединяются к уже идущему бою;
+        // остальные армии провинции могут начать свой бой, если их страны воюют между собой
+        let mut ongoing: Vec<(Entity, &Battle)> = battles.iter()
+            .filter(|(_, battle)| battle.report.province == province)
+            .collect();
+        ongoing.sort_by_key(|(_, battle)| (battle.report.attacker, battle.report.defender));
+
+        present.retain(|unit| {
+            let joined = ongoing.iter()
+                .find(|(_, battle)| battle.report.side_of(unit.owner, &diplomacy).is_some());
+            if let Some((battle_entity, _)) = joined {
+                commands.entity(unit.entity).insert(InBattle(*battle_entity));
+            }
+            joined.is_none()
+        });
+
+        let mut opened = 0u64;
+        loop {
+            let mut owners: Vec<CountryId> = present.iter().map(|unit| unit.owner).collect();
+            owners.dedup();
+
+            let pair = owners.iter()
+                .flat_map(|a| owners.iter().map(move |b| (*a, *b)))
+                .find(|(a, b)| a < b && diplomacy.at_war(*a, *b));
+            let Some((a, b)) = pair else {
+                break;
+            };
+
+            // Обороняется тот, кто контролирует провинцию; иначе — страна с меньшим id
+            let defender = if countries.controller_of(province) == Some(b) { b } else { a };
+            let attacker = if defender == a { b } else { a };
+
+            let terrain = registry.get(province)
+                .map(|p| p.terrain.kind())
+                .unwrap_or(TerrainKind::Plains);
+
+            let battle_seed = SeededRng::from_parts(&[seed.0, province.0 as u64, calendar.days_elapsed(), opened]).next_u64();
+            opened += 1;
+
+            let mut report = BattleReport {
+                province,
+                attacker,
+                defender,
+                started: calendar.date(),
+                terrain,
+                river_crossing: false,
+                attacker_initial: 0,
+                defender_initial: 0,
+                rounds: Vec::new(),
+                winner: None,
+            };
+            report.river_crossing = present.iter()
+                .filter(|unit| report.side_of(unit.owner, &diplomacy) == Some(true))
+                .any(|unit| unit.entered_from.is_some_and(|from| graph.is_river_crossing(from, province)));
+
+            let battle = commands.spawn_empty().id();
+            present.retain(|unit| {
+                let fighting = report.side_of(unit.owner, &diplomacy).is_some();
+                if fighting {
+                    commands.entity(unit.entity).insert(InBattle(battle));
+                }
+                !fighting
+            });
+            commands.entity(battle).insert(Battle::new(battle_seed, report));
+
+            started.send(BattleStarted { province, attacker, defender });
+        }
+    }
+}
+
+pub(crate) fn fight_battles(
+    mut commands: Commands,
+    registry: Res<ProvinceRegistry>,
+    countries: Res<CountryRegistry>,
+    diplomacy: Res<Diplomacy>,
+    mut battles: Query<(Entity, &mut Battle)>,
+    mut units: Query<(Entity, &mut Unit, &InBattle, &UnitPosition, &mut UnitPath)>,
+    mut ended: EventWriter<BattleEnded>,
+) {
+    let mut battle_list: Vec<(Entity, ProvinceId)> = battles.iter().map(|(e, b)| (e, b.report.province)).collect();
+    battle_list.sort_by_key(|(_, province)| *province);
+
+    for (battle_entity, _) in battle_list {
+        let Ok((_, mut battle)) = battles.get_mut(battle_entity) else {
+            continue;
+        };
+        let battle = &mut *battle;
+
+        // Порядок участников определяет, кому достаётся остаток потерь: он не должен зависеть от Entity
+        let mut participants: Vec<(Entity, CountryId, u32, u32)> = units.iter()
+            .filter(|(_, _, in_battle, ..)| in_battle.0 == battle_entity)
+            .map(|(entity, unit, ..)| (entity, unit.owner, unit.index, unit.strength))
+            .collect();
+        participants.sort_by_key(|(_, owner, index, _)| (*owner, *index));
+
+        // Мир заключён посреди боя: армии расходятся без победителя и без отступления
+        if !diplomacy.at_war(battle.report.attacker, battle.report.defender) {
+            for (entity, ..) in participants.iter() {
+                commands.entity(*entity).remove::<InBattle>();
+            }
+            ended.send(BattleEnded { report: battle.report.clone() });
+            commands.entity(battle_entity).despawn();
+            continue;
+        }
+
+        // Страна, вышедшая из войны сепаратным миром, покидает бой
+        for (entity, owner, ..) in participants.iter() {
+            if battle.report.side_of(*owner, &diplomacy).is_none() {
+                commands.entity(*entity).remove::<InBattle>();
+            }
+        }
+
+        let side = |attacking: bool| -> Vec<(Entity, u32)> {
+            participants.iter()
+                .filter(|(_, owner, ..)| battle.report.side_of(*owner, &diplomacy) == Some(attacking))
+                .map(|(e, _, _, s)| (*e, *s))
+                .collect()
+        };
+        let attackers = side(true);
+        let defenders = side(false);
+
+        let attacker_strength: u32 = attackers.iter().map(|(_, s)| s).sum();
+        let defender_strength: u32 = defenders.iter().map(|(_, s)| s).sum();
+
+        // Исходная сила растёт вместе с подкреплениями, чтобы порог бегства считался от всех участников
+        let report = &mut battle.report;
+        report.attacker_initial = report.attacker_initial.max(attacker_strength + report.attacker_losses());
+        report.defender_initial = report.defender_initial.max(defender_strength + report.defender_losses());
+
+        let mut round_losses = (0, 0);
+        if let Some(round) = battle.fight_round(attacker_strength, defender_strength) {
+            apply_losses(&mut units, &defenders, round.defender_losses);
+            apply_losses(&mut units, &attackers, round.attacker_losses);
+            round_losses = (round.attacker_losses, round.defender_losses);
+        }
+
+        let report = &mut battle.report;
+        let attacker_left = attacker_strength - round_losses.0;
+        let defender_left = defender_strength - round_losses.1;
+
+        let broken = |left: u32, initial: u32| (left as u64) * 100 < initial as u64 * BREAK_PERCENT;
+        let attacker_broken = broken(attacker_left, report.attacker_initial);
+        let defender_broken = broken(defender_left, report.defender_initial);
+        let timed_out = report.rounds.len() as u32 >= MAX_BATTLE_DAYS;
+
+        if !attacker_broken && !defender_broken && !timed_out {
+            continue;
+        }
+
+        // Побеждает сторона с большей сохранившейся долей сил, при равенстве — обороняющийся
+        let attacker_ratio = attacker_left as u64 * report.defender_initial.max(1) as u64;
+        let defender_ratio = defender_left as u64 * report.attacker_initial.max(1) as u64;
+        let (winner, loser) = if attacker_ratio > defender_ratio {
+            (report.attacker, report.defender)
+        } else {
+            (report.defender, report.attacker)
+        };
+        report.winner = Some(winner);
+
+        let retreat_to = countries.get(loser)
+            .and_then(|country| country.capital)
+            .and_then(|capital| registry.get(capital))
+            .map(|province| province.centroid);
+
+        for (entity, owner, ..) in participants.iter() {
+            let Ok((_, unit, _, position, mut path)) = units.get_mut(*entity) else {
+                continue;
+            };
+
+            if unit.strength == 0 {
+                commands.entity(*entity).despawn_recursive();
+                continue;
+            }
+
+            let mut entity_commands = commands.entity(*entity);
+            entity_commands.remove::<InBattle>();
+
+            if report.side_of(*owner, &diplomacy) == Some(loser == report.attacker) {
+                // Отступление туда, откуда армия пришла, иначе к столице
+                let target = position.entered_from
+                    .and_then(|from| registry.get(from))
+                    .map(|province| province.centroid)
+                    .or(retreat_to);
+                if let Some(target) = target {
+                    path.waypoints.clear();
+                    path.waypoints.push_back(target);
+                    entity_commands.insert(Retreating);
+                }
+            }
+        }
+
+        #[cfg(debug_assertions)]
+        println!(
+            "Битва в провинции {} завершена за {} дн.: победитель {:?}, потери атакующего {}, обороняющегося {}",
+            report.province.0,
+            report.rounds.len(),
+            winner,
+            report.attacker_losses(),
+            report.defender_losses(),
+        );
+
+        ended.send(BattleEnded { report: report.clone() });
+        commands.entity(battle_entity).despawn();
+    }
+}
+
+fn apply_losses(
+    units: &mut Query<(Entity, &mut Unit, &InBattle, &UnitPosition, &mut UnitPath)>,
+    side: &[(Entity, u32)],
+    losses: u32,
+) {
+    let strengths: Vec<u32> = side.iter().map(|(_, strength)| *strength).collect();
+    for ((entity, _), loss) in side.iter().zip(allocate_losses(&strengths, losses)) {
+        if let Ok((_, mut unit, ..)) = units.get_mut(*entity) {
+            unit.strength -= loss.min(unit.strength);
+        }
+    }
+}
+
+// Потери делятся пропорционально силе армий, остаток от деления достаётся первым по порядку
+fn allocate_losses(strengths: &[u32], losses: u32) -> Vec<u32> {
+    let total: u64 = strengths.iter().map(|s| *s as u64).sum();
+    if total == 0 {
+        return vec![0; strengths.len()];
+    }
+
+    let mut allocated: Vec<u32> = strengths.iter()
+        .map(|strength| ((losses as u64 * *strength as u64 / total) as u32).min(*strength))
+        .collect();
+    let mut applied: u32 = allocated.iter().sum();
+
+    for (loss, strength) in allocated.iter_mut().zip(strengths) {
+        if applied >= losses {
+            break;
+        }
+        let extra = (losses - applied).min(strength - *loss);
+        *loss += extra;
+        applied += extra;
+    }
+
+    allocated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{allocate_losses, Battle, BattleReport, MAX_BATTLE_DAYS};
+    use crate::core::country::CountryId;
+    use crate::core::map::province::{ProvinceId, TerrainKind};
+    use chrono::NaiveDate;
+
+    fn simulate(seed: u64) -> BattleReport {
+        let report = BattleReport {
+            province: ProvinceId(1),
+            attacker: CountryId(0),
+            defender: CountryId(1),
+            started: NaiveDate::from_ymd_opt(1444, 11, 11).unwrap(),
+            terrain: TerrainKind::Hills,
+            river_crossing: true,
+            attacker_initial: 12_000,
+            defender_initial: 9_000,
+            rounds: Vec::new(),
+            winner: None,
+        };
+        let mut battle = Battle::new(seed, report);
+
+        let (mut attackers, mut defenders) = (12_000, 9_000);
+        for _ in 0..MAX_BATTLE_DAYS {
+            let Some(round) = battle.fight_round(attackers, defenders) else {
+                break;
+            };
+            attackers -= round.attacker_losses;
+            defenders -= round.defender_losses;
+        }
+        battle.report
+    }
+
+    #[test]
+    fn same_seed_gives_same_report() {
+        assert_eq!(simulate(0x1444), simulate(0x1444));
+    }
+
+    #[test]
+    fn different_seeds_give_different_rolls() {
+        assert_ne!(simulate(1).rounds, simulate(2).rounds);
+    }
+
+    #[test]
+    fn losses_are_allocated_in_full_and_in_order() {
+        let allocated = allocate_losses(&[3, 3, 3], 5);
+        assert_eq!(allocated, vec![3, 1, 1]);
+        assert_eq!(allocate_losses(&[3, 3, 3], 5), allocated);
+    }
+
+    #[test]
+    fn losses_never_exceed_strength() {
+        assert_eq!(allocate_losses(&[1, 10], 11), vec![1, 10]);
+    }
+}
@@ -0,0 +1,215 @@
+pub(crate) mod battle;
+pub(crate) mod movement;
+pub(crate) mod occupation;
+mod orders;
+pub(crate) mod path_preview;
+mod selection;
+mod visuals;
+
+use crate::core::country::loader::load_countries;
+use crate::core::country::{CountryId, CountryRegistry};
+use crate::core::map::province::{ProvinceId, ProvinceMap, ProvinceRegistry};
+use crate::core::simulation::DailyTick;
+use crate::core::map::picking::update_terrain_cursor;
+use crate::core::map::province::tooltip::RegisterTooltipSection;
+use crate::core::unit::battle::{battle_tooltip, fight_battles, finish_retreats, start_battles, BattleEnded, BattleStarted};
+use crate::core::unit::movement::move_units;
+use crate::core::unit::occupation::{occupy_provinces, OccupationProgress};
+use crate::core::unit::orders::{apply_resolved_orders, issue_move_orders, OrderState};
+use crate::core::unit::path_preview::{init_path_preview, receive_path_preview, request_path_preview, update_eta_label, update_path_preview_line, PathPreview};
+use crate::core::unit::selection::{init_selection_box, select_units, update_selection_box, BoxSelectState};
+use crate::core::unit::visuals::{init_unit_assets, spawn_unit_visuals, sync_unit_transforms, update_selection_rings};
+use bevy::math::Vec2;
+use bevy::prelude::{App, Commands, Component, Entity, Event, EventReader, EventWriter, IntoSystemConfigs, Query, Res, ResMut, Resource, Startup, Update};
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::fs;
+
+#[derive(Component, Clone, Debug)]
+pub struct Unit {
+    pub owner: CountryId,
+    pub strength: u32,
+    // Порядковый номер армии: по нему, а не по Entity, упорядочиваются армии в симуляции.
+    // В сохранении армии идут по возрастанию номера, поэтому после загрузки порядок тот же
+    pub index: u32,
+}
+
+// Номер для следующей созданной армии
+#[derive(Resource, Default)]
+pub struct NextUnitIndex(pub u32);
+
+// Позиция в канонических координатах карты, previous — положение на прошлом тике для интерполяции
+#[derive(Component, Clone, Debug)]
+pub struct UnitPosition {
+    pub current: Vec2,
+    pub previous: Vec2,
+    pub province: Option<ProvinceId>,
+    // Провинция, из которой армия пришла в текущую: нужна для переправы через реку в бою
+    pub entered_from: Option<ProvinceId>,
+}
+
+#[derive(Component, Clone, Debug, Default)]
+pub struct UnitPath {
+    pub waypoints: VecDeque<Vec2>,
+}
+
+#[derive(Component)]
+pub struct Selected;
+
+#[derive(Event, Clone, Debug)]
+pub struct SpawnUnit {
+    pub owner: CountryId,
+    pub strength: u32,
+    pub position: Vec2,
+    pub path: Vec<Vec2>,
+}
+
+// queue = true добавляет точки в конец текущего маршрута вместо замены
+#[derive(Event, Clone, Debug)]
+pub struct MoveUnit {
+    pub unit: Entity,
+    pub path: Vec<Vec2>,
+    pub queue: bool,
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct UnitArrived {
+    pub unit: Entity,
+    pub position: Vec2,
+}
+
+#[derive(Deserialize, Default)]
+struct ScenarioUnits {
+    #[serde(default)]
+    units: Vec<UnitDefinition>,
+}
+
+#[derive(Deserialize)]
+struct UnitDefinition {
+    owner: String,
+    province: u16,
+    strength: u32,
+}
+
+pub fn build(app: &mut App) {
+    app.init_resource::<NextUnitIndex>();
+    app.add_event::<SpawnUnit>();
+    app.add_event::<MoveUnit>();
+    app.add_event::<UnitArrived>();
+    app.add_systems(Startup, (init_unit_assets, spawn_scenario_units.after(load_countries)));
+    app.add_systems(Update, (spawn_units, spawn_unit_visuals, apply_move_orders).chain());
+    app.add_systems(Update, (sync_unit_transforms, update_selection_rings));
+    app.add_event::<BattleStarted>();
+    app.add_event::<BattleEnded>();
+    app.register_tooltip_section(battle_tooltip);
+    app.init_resource::<OccupationProgress>();
+    app.add_systems(DailyTick, (move_units, finish_retreats, start_battles, fight_battles, occupy_provinces).chain());
+
+    app.init_resource::<PathPreview>();
+    app.add_systems(Startup, init_path_preview);
+    app.add_systems(Update, (
+        request_path_preview,
+        receive_path_preview,
+        update_path_preview_line,
+        update_eta_label,
+    ).chain().after(update_terrain_cursor));
+
+    app.init_resource::<BoxSelectState>();
+    app.init_resource::<OrderState>();
+    app.add_systems(Startup, init_selection_box);
+    app.add_systems(Update, (
+        select_units,
+        update_selection_box,
+        issue_move_orders.after(receive_path_preview),
+        apply_resolved_orders,
+    ).chain().after(update_terrain_cursor).before(apply_move_orders));
+}
+
+fn spawn_scenario_units(
+    countries: Res<CountryRegistry>,
+    provinces: Res<ProvinceRegistry>,
+    mut spawn: EventWriter<SpawnUnit>,
+) {
+    let path = "common/data/scenario.ron";
+    let Ok(content) = fs::read_to_string(path) else {
+        return;
+    };
+
+    let scenario = match ron::from_str::<ScenarioUnits>(&content) {
+        Ok(scenario) => scenario,
+        Err(e) => {
+            eprintln!("Не удалось разобрать армии сценария {}: {}", path, e);
+            return;
+        }
+    };
+
+    for definition in scenario.units {
+        let Some(owner) = countries.id_by_tag(&definition.owner) else {
+            eprintln!("Армия принадлежит неизвестной стране {}", definition.owner);
+            continue;
+        };
+        let Some(province) = provinces.get(ProvinceId(definition.province)) else {
+            eprintln!("Армия стоит в неизвестной провинции {}", definition.province);
+            continue;
+        };
+
+        spawn.send(SpawnUnit {
+            owner,
+            strength: definition.strength,
+            position: province.centroid,
+            path: Vec::new(),
+        });
+    }
+}
+
+fn spawn_units(
+    mut commands: Commands,
+    map: Option<Res<ProvinceMap>>,
+    mut next_index: ResMut<NextUnitIndex>,
+    mut events: EventReader<SpawnUnit>,
+) {
+    for event in events.read() {
+        spawn_unit(&mut commands, map.as_deref(), &mut next_index, event);
+    }
+}
+
+// Симуляция создаёт армии прямо в тике, а не событием: оно было бы обработано лишь в кадре после всех тиков
+pub(crate) fn spawn_unit(commands: &mut Commands, map: Option<&ProvinceMap>, next_index: &mut NextUnitIndex, spawn: &SpawnUnit) {
+    let index = next_index.0;
+    next_index.0 += 1;
+
+    let province = map.and_then(|map| map.province_at(spawn.position.x, spawn.position.y));
+
+    commands.spawn((
+        Unit {
+            owner: spawn.owner,
+            strength: spawn.strength,
+            index,
+        },
+        UnitPosition {
+            current: spawn.position,
+            previous: spawn.position,
+            province,
+            entered_from: None,
+        },
+        UnitPath {
+            waypoints: spawn.path.iter().copied().collect(),
+        },
+    ));
+}
+
+pub(crate) fn apply_move_orders(
+    mut events: EventReader<MoveUnit>,
+    mut units: Query<&mut UnitPath>,
+) {
+    for order in events.read() {
+        let Ok(mut path) = units.get_mut(order.unit) else {
+            continue;
+        };
+
+        if !order.queue {
+            path.waypoints.clear();
+        }
+        path.waypoints.extend(order.path.iter().copied());
+    }
+}
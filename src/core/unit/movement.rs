@@ -0,0 +1,91 @@
+use crate::core::map::province::ProvinceMap;
+use crate::core::map::terrain::heightfield::Heightfield;
+use crate::core::map::wrap::MapWrap;
+use crate::core::unit::battle::InBattle;
+use crate::core::unit::{UnitArrived, UnitPath, UnitPosition};
+use bevy::math::Vec2;
+use bevy::prelude::{Entity, EventWriter, Query, Res, Without};
+
+// Расстояние, которое армия проходит за день по ровной местности
+pub const BASE_SPEED: f32 = 30.0;
+
+// Уклон проверяется на коротких отрезках, иначе хребет между точками маршрута не замедлит армию
+const SLOPE_SAMPLE_STEP: f32 = 4.0;
+const SLOPE_PENALTY: f32 = 4.0;
+const MIN_SPEED_FACTOR: f32 = 0.25;
+
+pub fn slope_speed_factor(slope: f32) -> f32 {
+    (1.0 / (1.0 + SLOPE_PENALTY * slope.abs())).max(MIN_SPEED_FACTOR)
+}
+
+pub(super) fn move_units(
+    heightfield: Option<Res<Heightfield>>,
+    map: Option<Res<ProvinceMap>>,
+    wrap: Res<MapWrap>,
+    mut units: Query<(Entity, &mut UnitPosition, &mut UnitPath), Without<InBattle>>,
+    mut arrived: EventWriter<UnitArrived>,
+) {
+    let Some(heightfield) = heightfield else {
+        return;
+    };
+
+    for (entity, mut position, mut path) in units.iter_mut() {
+        position.previous = position.current;
+
+        if path.waypoints.is_empty() {
+            continue;
+        }
+
+        // Бюджет дня тратится в "ровных" единицах: подъём съедает его быстрее
+        let mut budget = BASE_SPEED;
+        let mut current = position.current;
+
+        while budget > 0.0 {
+            let Some(&waypoint) = path.waypoints.front() else {
+                break;
+            };
+
+            // Цель берётся в копии карты, ближайшей к армии, чтобы идти через шов, а не в обход
+            let target = Vec2::new(wrap.nearest_to(waypoint.x, current.x), waypoint.y);
+            let remaining = current.distance(target);
+            if remaining <= f32::EPSILON {
+                path.waypoints.pop_front();
+                continue;
+            }
+
+            let step = remaining.min(SLOPE_SAMPLE_STEP);
+            let next = current + (target - current) / remaining * step;
+
+            let from_height = heightfield.height_at(wrap.canonical_x(current.x), current.y);
+            let to_height = heightfield.height_at(wrap.canonical_x(next.x), next.y);
+            let factor = slope_speed_factor((to_height - from_height) / step);
+
+            let cost = step / factor;
+            if cost > budget {
+                current += (target - current) / remaining * budget * factor;
+                budget = 0.0;
+            } else {
+                current = next;
+                budget -= cost;
+                if step >= remaining {
+                    path.waypoints.pop_front();
+                }
+            }
+        }
+
+        let current = Vec2::new(wrap.canonical_x(current.x), current.y);
+
+        // previous сдвигается в ту же копию карты, что и current, чтобы интерполяция не пересекала всю карту
+        position.previous.x = wrap.nearest_to(position.previous.x, current.x);
+        position.current = current;
+        let province = map.as_ref().and_then(|map| map.province_at(current.x, current.y));
+        if province != position.province {
+            position.entered_from = position.province;
+            position.province = province;
+        }
+
+        if path.waypoints.is_empty() {
+            arrived.send(UnitArrived { unit: entity, position: current });
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use crate::core::country::{ChangeProvinceController, CountryId, CountryRegistry};
+use crate::core::diplomacy::Diplomacy;
+use crate::core::map::province::ProvinceId;
+use crate::core::unit::battle::InBattle;
+use crate::core::unit::{Unit, UnitPath, UnitPosition};
+use bevy::prelude::{EventWriter, Query, Res, ResMut, Resource, Without};
+use std::collections::BTreeMap;
+
+// Столько дней армия должна простоять в провинции без сопротивления, чтобы взять её под контроль
+pub const OCCUPATION_DAYS: u32 = 10;
+
+// Прогресс не сохраняется: после загрузки осада начинается заново
+#[derive(Resource, Default)]
+pub struct OccupationProgress {
+    pub provinces: BTreeMap<ProvinceId, (CountryId, u32)>,
+}
+
+// Захватывать можно только у страны, с которой идёт война; своя провинция под чужим контролем освобождается тем же способом. Отступающие армии идут по маршруту и не осаждают
+pub(crate) fn occupy_provinces(
+    countries: Res<CountryRegistry>,
+    diplomacy: Res<Diplomacy>,
+    mut progress: ResMut<OccupationProgress>,
+    units: Query<(&Unit, &UnitPosition, &UnitPath), Without<InBattle>>,
+    mut changes: EventWriter<ChangeProvinceController>,
+) {
+    let mut present: BTreeMap<ProvinceId, Vec<(CountryId, u32, bool)>> = BTreeMap::new();
+    for (unit, position, path) in units.iter() {
+        if let Some(province) = position.province {
+            present.entry(province).or_default().push((unit.owner, unit.index, path.waypoints.is_empty()));
+        }
+    }
+
+    let mut occupiers = BTreeMap::new();
+    for (province, mut units) in present {
+        let Some(controller) = countries.controller_of(province) else {
+            continue;
+        };
+        // Как и в бою, порядок по стране и номеру армии: Entity после загрузки другие
+        units.sort_by_key(|(owner, index, _)| (*owner, *index));
+
+        let occupier = units.iter()
+            .find(|(owner, _, standing)| *standing && diplomacy.at_war(*owner, controller))
+            .map(|(owner, ..)| *owner);
+        let Some(occupier) = occupier else {
+            continue;
+        };
+
+        // Пока в провинции стоят армии, враждебные осаждающему, контроль не переходит
+        let contested = units.iter().any(|(owner, ..)| diplomacy.at_war(*owner, occupier));
+        if !contested {
+            occupiers.insert(province, occupier);
+        }
+    }
+
+    progress.provinces.retain(|province, (country, _)| occupiers.get(province) == Some(country));
+
+    for (province, occupier) in occupiers {
+        let (_, days) = progress.provinces.entry(province).or_insert((occupier, 0));
+        *days += 1;
+        if *days < OCCUPATION_DAYS {
+            continue;
+        }
+
+        progress.provinces.remove(&province);
+        changes.send(ChangeProvinceController { province, controller: Some(occupier) });
+
+        #[cfg(debug_assertions)]
+        println!("Провинция {} занята страной {:?}", province.0, occupier);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::core::async_tasks::BackgroundTaskSystem;
+use crate::core::country::PlayerCountry;
+use crate::core::input::{ActionState, InputAction, CLICK_DRAG_THRESHOLD};
+use crate::core::map::picking::TerrainCursor;
+use crate::core::map::province::adjacency::ProvinceGraph;
+use crate::core::map::province::ProvinceMap;
+use crate::core::map::wrap::MapWrap;
+use crate::core::pathfinding::{FoundPath, PathHandle, PathStatus, Pathfinder};
+use crate::core::unit::path_preview::{crosses_strait, PathPreview, RouteValidity};
+use crate::core::unit::{MoveUnit, Selected, Unit, UnitPath, UnitPosition};
+use bevy::math::Vec2;
+use bevy::prelude::{Entity, EventWriter, Query, Res, ResMut, Resource, With};
+
+// Цель приказа должна совпасть с показанным маршрутом с такой точностью, чтобы взять его без пересчёта
+const PREVIEW_MATCH_DISTANCE: f32 = 6.0;
+
+#[derive(Clone, Copy)]
+struct PendingOrder {
+    unit: Entity,
+    handle: PathHandle,
+    from: Vec2,
+    target: Vec2,
+    queue: bool,
+    // Запрос уже идёт по графу провинций: дальше отступать некуда
+    by_provinces: bool,
+}
+
+#[derive(bevy::ecs::system::SystemParam)]
+pub(super) struct OrderInput<'w> {
+    actions: Res<'w, ActionState>,
+    cursor: Res<'w, TerrainCursor>,
+}
+
+#[derive(Resource, Default)]
+pub(crate) struct OrderState {
+    press_position: Option<Vec2>,
+    pending: Vec<PendingOrder>,
+}
+
+// Маршрут приказа ищется по сетке проходимости, а если она не готова или пути не нашла — по графу провинций
+#[derive(bevy::ecs::system::SystemParam)]
+pub(super) struct OrderPaths<'w> {
+    tasks: Res<'w, BackgroundTaskSystem>,
+    pathfinder: ResMut<'w, Pathfinder>,
+    map: Option<Res<'w, ProvinceMap>>,
+    graph: Res<'w, ProvinceGraph>,
+    wrap: Res<'w, MapWrap>,
+}
+
+impl OrderPaths<'_> {
+    fn request(&mut self, unit: Entity, from: Vec2, target: Vec2, queue: bool) -> Option<PendingOrder> {
+        if self.pathfinder.grid_ready() {
+            let handle = self.pathfinder.request_grid(from, target, &self.tasks);
+            return Some(PendingOrder { unit, handle, from, target, queue, by_provinces: false });
+        }
+
+        let handle = self.request_provinces(from, target)?;
+        Some(PendingOrder { unit, handle, from, target, queue, by_provinces: true })
+    }
+
+    fn request_provinces(&mut self, from: Vec2, target: Vec2) -> Option<PathHandle> {
+        let map = self.map.as_ref()?;
+        let start = map.province_at(self.wrap.canonical_x(from.x), from.y)?;
+        let goal = map.province_at(self.wrap.canonical_x(target.x), target.y)?;
+        Some(self.pathfinder.request(start, goal, &self.tasks))
+    }
+}
+
+// Клик ПКМ без перетаскивания — приказ выбранным армиям; перетаскивание той же кнопкой двигает камеру
+pub(super) fn issue_move_orders(
+    input: OrderInput,
+    preview: Res<PathPreview>,
+    player: Res<PlayerCountry>,
+    mut state: ResMut<OrderState>,
+    mut paths: OrderPaths,
+    mut moves: EventWriter<MoveUnit>,
+    units: Query<(Entity, &Unit, &UnitPosition, &UnitPath), With<Selected>>,
+) {
+    let OrderInput { actions, cursor } = input;
+
+    if actions.just_pressed(InputAction::Order) {
+        state.press_position = if cursor.over_ui { None } else { cursor.screen_position };
+    }
+
+    if !actions.just_released(InputAction::Order) {
+        return;
+    }
+
+    let Some(press) = state.press_position.take() else {
+        return;
+    };
+    let is_click = cursor.screen_position.is_some_and(|position| position.distance(press) <= CLICK_DRAG_THRESHOLD);
+    let Some(target) = cursor.world_position.filter(|_| is_click) else {
+        return;
+    };
+    let target = Vec2::new(target.x, target.z);
+    let queue = actions.pressed(InputAction::Additive);
+
+    // Чужими армиями управляет ИИ, приказы игрока получают только свои
+    let own = units.iter().filter(|(_, unit, ..)| player.0.is_none_or(|player| unit.owner == player));
+    for (entity, _, position, path) in own {
+        // Новый приказ отменяет ещё не рассчитанный прежний, если только это не точка в очередь
+        if !queue {
+            state.pending.retain(|order| {
+                if order.unit == entity {
+                    paths.pathfinder.cancel(order.handle);
+                }
+                order.unit != entity
+            });
+        }
+
+        // Маршрут из превью уже посчитан от текущей позиции: для приказа без очереди берём его сразу
+        let previewed = preview.route.as_ref().filter(|route| {
+            !queue
+                && preview.unit() == Some(entity)
+                && route.validity == RouteValidity::Valid
+                && route.target.distance(target) <= PREVIEW_MATCH_DISTANCE
+        });
+        if let Some(route) = previewed {
+            moves.send(MoveUnit { unit: entity, path: route.waypoints.clone(), queue: false });
+            continue;
+        }
+
+        // Точка в очередь строится от конца маршрута, включая ещё не рассчитанные приказы
+        let queued_end = state.pending.iter().rev()
+            .find(|order| order.unit == entity)
+            .map(|order| order.target)
+            .or_else(|| path.waypoints.back().copied());
+        let from = match queued_end {
+            Some(end) if queue => end,
+            _ => position.current,
+        };
+
+        match paths.request(entity, from, target, queue) {
+            Some(order) => state.pending.push(order),
+            None => {
+                #[cfg(debug_assertions)]
+                println!("Маршрут для армии {:?} не найден", entity);
+            }
+        }
+    }
+}
+
+// Очередь приказов разбирается по порядку, чтобы точки маршрута добавлялись в том порядке, в каком их ставили
+pub(super) fn apply_resolved_orders(
+    mut state: ResMut<OrderState>,
+    mut paths: OrderPaths,
+    mut moves: EventWriter<MoveUnit>,
+) {
+    let mut index = 0;
+    while index < state.pending.len() {
+        let order = state.pending[index];
+
+        let earlier_pending = state.pending[..index].iter().any(|earlier| earlier.unit == order.unit);
+        if earlier_pending {
+            index += 1;
+            continue;
+        }
+
+        match paths.pathfinder.take(order.handle) {
+            Some(PathStatus::Pending) => {
+                index += 1;
+                continue;
+            }
+            Some(PathStatus::Found(FoundPath::Grid(path))) => {
+                moves.send(MoveUnit { unit: order.unit, path: path.waypoints, queue: order.queue });
+            }
+            // Через пролив без флота не пройти, как и в превью маршрута
+            Some(PathStatus::Found(FoundPath::Provinces(path))) if crosses_strait(&paths.graph, &path.provinces) => {
+                #[cfg(debug_assertions)]
+                println!("Армии {:?} нужен флот, чтобы пройти через пролив", order.unit);
+            }
+            Some(PathStatus::Found(FoundPath::Provinces(path))) => {
+                let mut waypoints = path.waypoints;
+                waypoints.push(order.target);
+                moves.send(MoveUnit { unit: order.unit, path: waypoints, queue: order.queue });
+            }
+            _ => {
+                if !order.by_provinces {
+                    if let Some(handle) = paths.request_provinces(order.from, order.target) {
+                        state.pending[index] = PendingOrder { handle, by_provinces: true, ..order };
+                        index += 1;
+                        continue;
+                    }
+                }
+
+                #[cfg(debug_assertions)]
+                println!("Маршрут для армии {:?} не найден", order.unit);
+            }
+        }
+
+        state.pending.remove(index);
+    }
+}
@@ -0,0 +1,468 @@
+use crate::core::async_tasks::BackgroundTaskSystem;
+use crate::core::map::picking::TerrainCursor;
+use crate::core::map::province::adjacency::{AdjacencyKind, ProvinceGraph};
+use crate::core::map::province::{ProvinceId, ProvinceMap, ProvinceRegistry};
+use crate::core::map::terrain::heightfield::Heightfield;
+use crate::core::map::wrap::{MapWrap, Wrapped};
+use crate::core::map::MAP_WIDTH;
+use crate::core::pathfinding::{FoundPath, PathHandle, PathStatus, Pathfinder};
+use crate::core::unit::{Selected, UnitPosition};
+use bevy::asset::{Assets, Handle, RenderAssetUsages};
+use bevy::color::Color;
+use bevy::math::primitives::Sphere;
+use bevy::math::{Vec2, Vec3};
+use bevy::pbr::{MeshMaterial3d, StandardMaterial};
+use bevy::prelude::{default, Alpha, AlphaMode, BackgroundColor, BuildChildren, Camera, ChildBuild, Children, Commands, Component, DespawnRecursiveExt, DetectChanges, Entity, GlobalTransform, Mesh, Mesh3d, Node, PositionType, Query, Res, ResMut, Resource, Single, Text, TextFont, Transform, UiRect, Val, Visibility, With};
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+
+const RIBBON_HALF_WIDTH: f32 = 1.2;
+const RIBBON_LIFT: f32 = 0.6;
+const MARKER_RADIUS: f32 = 1.6;
+// Курсор должен сместиться дальше этого, чтобы маршрут пересчитался
+const RETARGET_DISTANCE: f32 = 6.0;
+// Для упрощённых маршрутов (проливы, недоступная цель) линия дробится с таким шагом
+const DRAPE_STEP: f32 = 8.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RouteValidity {
+    Valid,
+    Blocked,
+    NeedsSeaTransport,
+    // Сетка проходимости ещё собирается, маршрут посчитается, когда она будет готова
+    Pending,
+}
+
+#[derive(Clone, Debug)]
+pub struct PreviewRoute {
+    pub validity: RouteValidity,
+    pub target: Vec2,
+    // Точки, по которым пойдёт армия; пусто, если маршрута нет
+    pub waypoints: Vec<Vec2>,
+    pub points: Vec<Vec3>,
+    pub days: Vec<f32>,
+    pub eta_days: f32,
+}
+
+#[derive(Resource, Default)]
+pub struct PathPreview {
+    unit: Option<Entity>,
+    from: Vec2,
+    target: Option<Vec2>,
+    grid_request: Option<PathHandle>,
+    province_request: Option<PathHandle>,
+    pub route: Option<PreviewRoute>,
+}
+
+impl PathPreview {
+    pub fn unit(&self) -> Option<Entity> {
+        self.unit
+    }
+
+    fn clear(&mut self, pathfinder: &mut Pathfinder) {
+        for handle in [self.grid_request.take(), self.province_request.take()].into_iter().flatten() {
+            pathfinder.cancel(handle);
+        }
+        self.unit = None;
+        self.target = None;
+        self.route = None;
+    }
+
+    fn pending(&self) -> bool {
+        self.grid_request.is_some() || self.province_request.is_some()
+    }
+}
+
+#[derive(Component)]
+pub struct PathPreviewLine;
+
+#[derive(Component)]
+pub struct EtaLabel;
+
+#[derive(Resource)]
+pub struct PathPreviewAssets {
+    marker: Handle<Mesh>,
+    marker_material: Handle<StandardMaterial>,
+    valid: Handle<StandardMaterial>,
+    blocked: Handle<StandardMaterial>,
+    sea: Handle<StandardMaterial>,
+    pending: Handle<StandardMaterial>,
+}
+
+impl PathPreviewAssets {
+    fn material(&self, validity: RouteValidity) -> Handle<StandardMaterial> {
+        match validity {
+            RouteValidity::Valid => self.valid.clone(),
+            RouteValidity::Blocked => self.blocked.clone(),
+            RouteValidity::NeedsSeaTransport => self.sea.clone(),
+            RouteValidity::Pending => self.pending.clone(),
+        }
+    }
+}
+
+pub(super) fn init_path_preview(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut ribbon = |color: Color| materials.add(StandardMaterial {
+        base_color: color,
+        unlit: true,
+        alpha_mode: AlphaMode::Blend,
+        ..default()
+    });
+
+    commands.insert_resource(PathPreviewAssets {
+        marker: meshes.add(Sphere::new(MARKER_RADIUS)),
+        marker_material: ribbon(Color::srgb(1.0, 1.0, 0.9)),
+        valid: ribbon(Color::srgba(0.3, 0.9, 0.4, 0.8)),
+        blocked: ribbon(Color::srgba(0.9, 0.2, 0.2, 0.8)),
+        sea: ribbon(Color::srgba(0.3, 0.6, 1.0, 0.8)),
+        pending: ribbon(Color::srgba(0.8, 0.8, 0.8, 0.5)),
+    });
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+                ..default()
+            },
+            BackgroundColor(Color::BLACK.with_alpha(0.6)),
+            Visibility::Hidden,
+            EtaLabel,
+        ))
+        .with_children(|p| {
+            p.spawn((
+                Text::default(),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+            ));
+        });
+}
+
+// Маршрут показывается для первой выбранной армии к точке рельефа под курсором
+pub(super) fn request_path_preview(
+    cursor: Res<TerrainCursor>,
+    tasks: Res<BackgroundTaskSystem>,
+    heightfield: Option<Res<Heightfield>>,
+    wrap: Res<MapWrap>,
+    mut pathfinder: ResMut<Pathfinder>,
+    mut preview: ResMut<PathPreview>,
+    selected: Query<(Entity, &UnitPosition), With<Selected>>,
+) {
+    let unit = selected.iter().min_by_key(|(entity, _)| *entity);
+    let target = cursor.world_position.filter(|_| !cursor.over_ui).map(|p| Vec2::new(p.x, p.z));
+
+    let (Some((entity, position)), Some(target)) = (unit, target) else {
+        if preview.unit.is_some() {
+            preview.clear(&mut pathfinder);
+        }
+        return;
+    };
+
+    // Линия ожидания пересчитывается, как только сетка готова, даже если курсор не двигался
+    let retry = pathfinder.grid_ready()
+        && preview.route.as_ref().is_some_and(|route| route.validity == RouteValidity::Pending);
+    let unchanged = !retry
+        && preview.unit == Some(entity)
+        && preview.from.distance(position.current) < RETARGET_DISTANCE
+        && preview.target.is_some_and(|old| old.distance(target) < RETARGET_DISTANCE);
+    // Пока идёт расчёт, новый не запускается: иначе движение мыши плодит потоки
+    if unchanged || preview.pending() {
+        return;
+    }
+
+    preview.unit = Some(entity);
+    preview.from = position.current;
+    preview.target = Some(target);
+
+    // До готовности сетки проходимости точный маршрут не построить: показывается прямая линия ожидания
+    if !pathfinder.grid_ready() {
+        preview.route = heightfield.map(|heightfield| straight_route(RouteValidity::Pending, position.current, target, &heightfield, &wrap));
+        return;
+    }
+
+    preview.grid_request = Some(pathfinder.request_grid(position.current, target, &tasks));
+}
+
+// Провинции, по которым маршрут строится, если сетка проходимости пути не нашла
+#[derive(bevy::ecs::system::SystemParam)]
+pub(super) struct ProvinceRoutes<'w> {
+    map: Option<Res<'w, ProvinceMap>>,
+    registry: Res<'w, ProvinceRegistry>,
+    graph: Res<'w, ProvinceGraph>,
+}
+
+pub(super) fn receive_path_preview(
+    tasks: Res<BackgroundTaskSystem>,
+    provinces: ProvinceRoutes,
+    heightfield: Option<Res<Heightfield>>,
+    wrap: Res<MapWrap>,
+    mut pathfinder: ResMut<Pathfinder>,
+    mut preview: ResMut<PathPreview>,
+) {
+    let ProvinceRoutes { map, registry, graph } = provinces;
+    let (Some(map), Some(heightfield)) = (map, heightfield) else {
+        return;
+    };
+    let Some(target) = preview.target else {
+        return;
+    };
+    let from = preview.from;
+
+    if let Some(handle) = preview.grid_request {
+        match pathfinder.take(handle) {
+            Some(PathStatus::Pending) => return,
+            Some(PathStatus::Found(FoundPath::Grid(path))) => {
+                preview.grid_request = None;
+                preview.route = Some(PreviewRoute {
+                    validity: RouteValidity::Valid,
+                    target,
+                    waypoints: path.waypoints,
+                    points: path.points,
+                    days: path.days,
+                    eta_days: path.eta_days,
+                });
+                return;
+            }
+            _ => preview.grid_request = None,
+        }
+
+        // Сетка не нашла путь (нет прохода по суше или поиск слишком большой): маршрут строится по графу провинций
+        let provinces = (
+            map.province_at(wrap.canonical_x(from.x), from.y),
+            map.province_at(target.x, target.y).filter(|id| registry.get(*id).is_some_and(|p| !p.is_sea)),
+        );
+        if let (Some(start), Some(goal)) = provinces {
+            preview.province_request = Some(pathfinder.request(start, goal, &tasks));
+        } else {
+            preview.route = Some(straight_route(RouteValidity::Blocked, from, target, &heightfield, &wrap));
+            return;
+        }
+    }
+
+    let Some(handle) = preview.province_request else {
+        return;
+    };
+
+    match pathfinder.take(handle) {
+        Some(PathStatus::Pending) => {}
+        Some(PathStatus::Found(FoundPath::Provinces(path))) => {
+            preview.province_request = None;
+
+            let mut waypoints = path.waypoints.clone();
+            waypoints.push(target);
+            let mut line = vec![from];
+            line.extend(waypoints.iter().copied());
+            let (points, days) = drape_line(&line, path.eta_days, &heightfield, &wrap);
+
+            // Флот нужен, только если маршрут действительно идёт через пролив
+            let validity = if crosses_strait(&graph, &path.provinces) {
+                RouteValidity::NeedsSeaTransport
+            } else {
+                RouteValidity::Valid
+            };
+
+            preview.route = Some(PreviewRoute {
+                validity,
+                target,
+                waypoints: if validity == RouteValidity::Valid { waypoints } else { Vec::new() },
+                points,
+                days,
+                eta_days: path.eta_days,
+            });
+        }
+        _ => {
+            preview.province_request = None;
+            preview.route = Some(straight_route(RouteValidity::Blocked, from, target, &heightfield, &wrap));
+        }
+    }
+}
+
+pub fn crosses_strait(graph: &ProvinceGraph, provinces: &[ProvinceId]) -> bool {
+    provinces.windows(2).any(|pair| {
+        graph.neighbours(pair[0]).iter().any(|adjacency| adjacency.neighbour == pair[1] && adjacency.kind == AdjacencyKind::Strait)
+    })
+}
+
+fn straight_route(validity: RouteValidity, from: Vec2, target: Vec2, heightfield: &Heightfield, wrap: &MapWrap) -> PreviewRoute {
+    let (points, days) = drape_line(&[from, target], 0.0, heightfield, wrap);
+    PreviewRoute {
+        validity,
+        target,
+        waypoints: Vec::new(),
+        points,
+        days,
+        eta_days: 0.0,
+    }
+}
+
+// Укладывает ломаную на рельеф, распределяя дни пропорционально длине
+fn drape_line(line: &[Vec2], eta_days: f32, heightfield: &Heightfield, wrap: &MapWrap) -> (Vec<Vec3>, Vec<f32>) {
+    let mut continuous = line.to_vec();
+    for i in 1..continuous.len() {
+        continuous[i].x = wrap.nearest_to(continuous[i].x, continuous[i - 1].x);
+    }
+
+    let total: f32 = continuous.windows(2).map(|w| w[0].distance(w[1])).sum();
+    let mut points = Vec::new();
+    let mut days = Vec::new();
+    let mut travelled = 0.0;
+
+    for (i, pair) in continuous.windows(2).enumerate() {
+        let (a, b) = (pair[0], pair[1]);
+        let length = a.distance(b);
+        let steps = (length / DRAPE_STEP).ceil().max(1.0) as u32;
+
+        for step in u32::from(i > 0)..=steps {
+            let p = a.lerp(b, step as f32 / steps as f32);
+            let distance = travelled + length * step as f32 / steps as f32;
+            points.push(Vec3::new(p.x, heightfield.height_at(wrap.canonical_x(p.x), p.y), p.y));
+            days.push(if total > 0.0 { eta_days * distance / total } else { 0.0 });
+        }
+        travelled += length;
+    }
+
+    (points, days)
+}
+
+pub(super) fn update_path_preview_line(
+    mut commands: Commands,
+    preview: Res<PathPreview>,
+    assets: Res<PathPreviewAssets>,
+    wrap: Res<MapWrap>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    lines: Query<Entity, With<PathPreviewLine>>,
+) {
+    if !preview.is_changed() {
+        return;
+    }
+
+    for entity in lines.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let Some(route) = preview.route.as_ref() else {
+        return;
+    };
+    // Меш строится от самой западной точки, чтобы Wrapped правильно выбирал копию карты
+    let min_x = route.points.iter().map(|p| p.x).fold(f32::INFINITY, f32::min);
+    let max_x = route.points.iter().map(|p| p.x).fold(f32::NEG_INFINITY, f32::max);
+    let origin = Vec3::new(min_x, 0.0, 0.0);
+    let extent = max_x - min_x;
+
+    let Some(mesh) = build_ribbon(&route.points, origin) else {
+        return;
+    };
+
+    commands
+        .spawn((
+            PathPreviewLine,
+            Mesh3d(meshes.add(mesh)),
+            MeshMaterial3d(assets.material(route.validity)),
+            Transform::from_xyz(wrap.nearest_copy_x(origin.x), 0.0, 0.0),
+            Visibility::default(),
+            Wrapped {
+                canonical_x: wrap.canonical_x(origin.x),
+                extent: extent.min(MAP_WIDTH),
+            },
+        ))
+        .with_children(|parent| {
+            if matches!(route.validity, RouteValidity::Blocked | RouteValidity::Pending) {
+                return;
+            }
+
+            // Метка в точке, где заканчивается каждый полный день пути
+            let mut next_day = 1.0;
+            for (point, day) in route.points.iter().zip(route.days.iter()) {
+                if *day < next_day {
+                    continue;
+                }
+                next_day = day.floor() + 1.0;
+
+                parent.spawn((
+                    Mesh3d(assets.marker.clone()),
+                    MeshMaterial3d(assets.marker_material.clone()),
+                    Transform::from_xyz(point.x - origin.x, point.y + RIBBON_LIFT + MARKER_RADIUS, point.z),
+                ));
+            }
+        });
+}
+
+fn build_ribbon(points: &[Vec3], origin: Vec3) -> Option<Mesh> {
+    if points.len() < 2 {
+        return None;
+    }
+
+    let mut positions = Vec::with_capacity(points.len() * 2);
+    let mut indices = Vec::with_capacity((points.len() - 1) * 6);
+
+    for i in 0..points.len() {
+        let prev = points[i.saturating_sub(1)];
+        let next = points[(i + 1).min(points.len() - 1)];
+        let direction = Vec2::new(next.x - prev.x, next.z - prev.z).normalize_or_zero();
+        let side = Vec2::new(-direction.y, direction.x) * RIBBON_HALF_WIDTH;
+
+        for offset in [side, -side] {
+            positions.push([points[i].x - origin.x + offset.x, points[i].y + RIBBON_LIFT, points[i].z + offset.y]);
+        }
+    }
+
+    for i in 0..(points.len() as u32 - 1) {
+        let a = i * 2;
+        indices.extend_from_slice(&[a, a + 2, a + 1, a + 1, a + 2, a + 3]);
+    }
+
+    let normals = vec![Vec3::Y.to_array(); positions.len()];
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default());
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_indices(Indices::U32(indices));
+    Some(mesh)
+}
+
+pub(super) fn update_eta_label(
+    preview: Res<PathPreview>,
+    wrap: Res<MapWrap>,
+    camera: Query<(&Camera, &GlobalTransform)>,
+    label: Single<(&mut Node, &mut Visibility, &Children), With<EtaLabel>>,
+    mut texts: Query<&mut Text>,
+) {
+    let (mut node, mut visibility, children) = label.into_inner();
+
+    let screen = preview.route.as_ref().and_then(|route| {
+        let end = *route.points.last()?;
+        let (camera, transform) = camera.get_single().ok()?;
+        let world = Vec3::new(wrap.nearest_copy_x(wrap.canonical_x(end.x)), end.y, end.z);
+        camera.world_to_viewport(transform, world).ok().map(|screen| (route, screen))
+    });
+
+    let Some((route, screen)) = screen else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    *visibility = Visibility::Inherited;
+    node.left = Val::Px(screen.x + 12.0);
+    node.top = Val::Px(screen.y - 12.0);
+
+    if !preview.is_changed() {
+        return;
+    }
+
+    let content = match route.validity {
+        RouteValidity::Valid => format!("{} дн.", route.eta_days.ceil() as u32),
+        RouteValidity::NeedsSeaTransport => format!("нужен флот, ~{} дн.", route.eta_days.ceil() as u32),
+        RouteValidity::Blocked => "путь недоступен".to_string(),
+        RouteValidity::Pending => "маршрут рассчитывается…".to_string(),
+    };
+
+    for child in children.iter() {
+        if let Ok(mut text) = texts.get_mut(*child) {
+            text.0 = content.clone();
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use crate::core::fog::UnitVisibility;
+use crate::core::input::{ActionState, InputAction, CLICK_DRAG_THRESHOLD};
+use crate::core::map::picking::TerrainCursor;
+use crate::core::unit::{Selected, Unit, UnitPosition};
+use bevy::color::Color;
+use bevy::math::{Rect, Vec2};
+use bevy::prelude::{default, Alpha, BackgroundColor, BorderColor, Camera, Commands, Component, Entity, GlobalTransform, Has, Node, PositionType, Query, Res, ResMut, Resource, Single, UiRect, Val, Visibility, With};
+
+// Радиус в пикселях, в котором одиночный клик попадает в армию
+const UNIT_PICK_RADIUS: f32 = 18.0;
+
+#[derive(Resource, Default)]
+pub(crate) struct BoxSelectState {
+    press_position: Option<Vec2>,
+}
+
+impl BoxSelectState {
+    // Прямоугольник рамки, если кнопка зажата и курсор ушёл дальше порога клика
+    fn drag_rect(&self, cursor: Option<Vec2>) -> Option<Rect> {
+        let (press, cursor) = (self.press_position?, cursor?);
+        if press.distance(cursor) <= CLICK_DRAG_THRESHOLD {
+            return None;
+        }
+        Some(Rect::from_corners(press, cursor))
+    }
+}
+
+#[derive(Component)]
+pub struct SelectionBox;
+
+pub(super) fn init_selection_box(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            border: UiRect::all(Val::Px(1.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgb(0.9, 0.9, 0.6).with_alpha(0.12)),
+        BorderColor(Color::srgb(0.9, 0.9, 0.6)),
+        Visibility::Hidden,
+        SelectionBox,
+    ));
+}
+
+// Рамка по ЛКМ выделяет армии по их экранной проекции, клик выбирает ближайшую. Shift добавляет к выделению
+pub(super) fn select_units(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    cursor: Res<TerrainCursor>,
+    mut state: ResMut<BoxSelectState>,
+    camera: Query<(&Camera, &GlobalTransform)>,
+    units: Query<(Entity, &Unit, &UnitPosition, &GlobalTransform, Has<Selected>)>,
+    visibility: UnitVisibility,
+) {
+    if actions.just_pressed(InputAction::Select) {
+        state.press_position = if cursor.over_ui { None } else { cursor.screen_position };
+    }
+
+    if !actions.just_released(InputAction::Select) {
+        return;
+    }
+
+    let drag = state.drag_rect(cursor.screen_position);
+    let Some(press) = state.press_position.take() else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = camera.get_single() else {
+        return;
+    };
+
+    let additive = actions.pressed(InputAction::Additive);
+    // Армии под туманом выделить нельзя ни кликом, ни рамкой
+    let on_screen = units.iter().filter_map(|(entity, unit, position, transform, selected)| {
+        if !visibility.shows(unit, position) {
+            return None;
+        }
+        let screen = camera.world_to_viewport(camera_transform, transform.translation()).ok()?;
+        Some((entity, screen, selected))
+    });
+
+    let hits: Vec<Entity> = match drag {
+        Some(rect) => on_screen.filter(|(_, screen, _)| rect.contains(*screen)).map(|(entity, ..)| entity).collect(),
+        None => {
+            let click = cursor.screen_position.unwrap_or(press);
+            on_screen
+                .map(|(entity, screen, _)| (entity, screen.distance(click)))
+                .filter(|(_, distance)| *distance <= UNIT_PICK_RADIUS)
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(entity, _)| entity)
+                .into_iter()
+                .collect()
+        }
+    };
+
+    if !additive {
+        for (entity, .., selected) in units.iter() {
+            if selected && !hits.contains(&entity) {
+                commands.entity(entity).remove::<Selected>();
+            }
+        }
+    }
+
+    for entity in hits {
+        commands.entity(entity).insert(Selected);
+    }
+}
+
+pub(super) fn update_selection_box(
+    actions: Res<ActionState>,
+    cursor: Res<TerrainCursor>,
+    state: Res<BoxSelectState>,
+    selection_box: Single<(&mut Node, &mut Visibility), With<SelectionBox>>,
+) {
+    let (mut node, mut visibility) = selection_box.into_inner();
+
+    let rect = state.drag_rect(cursor.screen_position).filter(|_| actions.pressed(InputAction::Select));
+    let Some(rect) = rect else {
+        if *visibility != Visibility::Hidden {
+            *visibility = Visibility::Hidden;
+        }
+        return;
+    };
+
+    *visibility = Visibility::Inherited;
+    node.left = Val::Px(rect.min.x);
+    node.top = Val::Px(rect.min.y);
+    node.width = Val::Px(rect.width());
+    node.height = Val::Px(rect.height());
+}
@@ -0,0 +1,146 @@
+use crate::core::country::{CountryId, CountryRegistry};
+use crate::core::map::sea::SEA_LEVEL;
+use crate::core::map::terrain::heightfield::Heightfield;
+use crate::core::map::wrap::MapWrap;
+use crate::core::simulation::SimulationClock;
+use crate::core::unit::{Selected, Unit, UnitPosition};
+use bevy::asset::{Assets, Handle};
+use bevy::color::Color;
+use bevy::math::primitives::{Annulus, Capsule3d};
+use bevy::math::{Quat, Vec3};
+use bevy::pbr::{MeshMaterial3d, StandardMaterial};
+use bevy::prelude::{default, Added, BuildChildren, ChildBuild, Children, Commands, Component, Entity, Has, Mesh, Mesh3d, Query, Res, ResMut, Resource, Transform, Visibility, With};
+use std::collections::HashMap;
+use std::f32::consts::FRAC_PI_2;
+
+const BODY_RADIUS: f32 = 3.0;
+const BODY_LENGTH: f32 = 6.0;
+const RING_INNER_RADIUS: f32 = 5.0;
+const RING_OUTER_RADIUS: f32 = 6.5;
+// Кольцо чуть приподнято над землёй, чтобы не мерцало вместе с террейном
+const RING_LIFT: f32 = 0.3;
+
+#[derive(Component)]
+pub struct SelectionRing;
+
+#[derive(Resource)]
+pub struct UnitAssets {
+    body: Handle<Mesh>,
+    ring: Handle<Mesh>,
+    ring_material: Handle<StandardMaterial>,
+    country_materials: HashMap<CountryId, Handle<StandardMaterial>>,
+    fallback_material: Handle<StandardMaterial>,
+}
+
+pub(super) fn init_unit_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(UnitAssets {
+        body: meshes.add(Capsule3d::new(BODY_RADIUS, BODY_LENGTH)),
+        ring: meshes.add(Annulus::new(RING_INNER_RADIUS, RING_OUTER_RADIUS)),
+        ring_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(1.0, 0.85, 0.2),
+            unlit: true,
+            ..default()
+        }),
+        country_materials: HashMap::new(),
+        fallback_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.5, 0.5, 0.5),
+            perceptual_roughness: 0.9,
+            ..default()
+        }),
+    });
+}
+
+pub(super) fn spawn_unit_visuals(
+    mut commands: Commands,
+    mut assets: ResMut<UnitAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    countries: Res<CountryRegistry>,
+    units: Query<(Entity, &Unit), Added<Unit>>,
+) {
+    for (entity, unit) in units.iter() {
+        let material = match countries.get(unit.owner) {
+            Some(country) => assets.country_materials
+                .entry(unit.owner)
+                .or_insert_with(|| {
+                    let [r, g, b] = country.color;
+                    materials.add(StandardMaterial {
+                        base_color: Color::srgb_u8(r, g, b),
+                        perceptual_roughness: 0.9,
+                        ..default()
+                    })
+                })
+                .clone(),
+            None => assets.fallback_material.clone(),
+        };
+
+        commands.entity(entity)
+            .insert((
+                Mesh3d(assets.body.clone()),
+                MeshMaterial3d(material),
+                Transform::default(),
+                Visibility::default(),
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    SelectionRing,
+                    Mesh3d(assets.ring.clone()),
+                    MeshMaterial3d(assets.ring_material.clone()),
+                    Transform::from_xyz(0.0, RING_LIFT - (BODY_LENGTH / 2.0 + BODY_RADIUS), 0.0)
+                        .with_rotation(Quat::from_rotation_x(-FRAC_PI_2)),
+                    Visibility::Hidden,
+                ));
+            });
+    }
+}
+
+// Позиция между тиками интерполируется по доле прошедшего дня, сама симуляция шагает только в DailyTick
+pub(super) fn sync_unit_transforms(
+    clock: Res<SimulationClock>,
+    wrap: Res<MapWrap>,
+    heightfield: Option<Res<Heightfield>>,
+    mut units: Query<(&UnitPosition, &mut Transform), With<Unit>>,
+) {
+    let Some(heightfield) = heightfield else {
+        return;
+    };
+
+    let t = if clock.paused { 1.0 } else { clock.tick_progress() };
+
+    for (position, mut transform) in units.iter_mut() {
+        let point = position.previous.lerp(position.current, t);
+        let ground = heightfield.height_at(wrap.canonical_x(point.x), point.y).max(SEA_LEVEL);
+
+        transform.translation = Vec3::new(
+            wrap.nearest_copy_x(wrap.canonical_x(point.x)),
+            ground + BODY_LENGTH / 2.0 + BODY_RADIUS,
+            point.y,
+        );
+
+        let direction = position.current - position.previous;
+        if direction.length_squared() > f32::EPSILON {
+            transform.rotation = Quat::from_rotation_y(direction.x.atan2(direction.y));
+        }
+    }
+}
+
+pub(super) fn update_selection_rings(
+    units: Query<(Has<Selected>, &Children), With<Unit>>,
+    mut rings: Query<&mut Visibility, With<SelectionRing>>,
+) {
+    for (selected, children) in units.iter() {
+        for child in children.iter() {
+            let Ok(mut visibility) = rings.get_mut(*child) else {
+                continue;
+            };
+
+            let target = if selected { Visibility::Inherited } else { Visibility::Hidden };
+            if *visibility != target {
+                *visibility = target;
+            }
+        }
+    }
+}
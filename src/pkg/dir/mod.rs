@@ -1,7 +1,7 @@
 use std::fs;
 use std::path::PathBuf;
 use dirs;
-use dirs::cache_dir;
+use dirs::{cache_dir, config_dir, data_dir};
 
 pub fn init_dir(path: PathBuf) -> Result<(), std::io::Error> {
     fs::create_dir_all(path)?;
@@ -14,3 +14,16 @@ pub fn cache_directory() -> PathBuf {
         .join("fallen-age")
         .join("cache")
 }
+
+pub fn settings_directory() -> PathBuf {
+    config_dir()
+        .expect("Failed to get config directory")
+        .join("fallen-age")
+}
+
+pub fn saves_directory() -> PathBuf {
+    data_dir()
+        .expect("Failed to get data directory")
+        .join("fallen-age")
+        .join("saves")
+}
@@ -1,2 +1,3 @@
 pub(crate) mod dir;
+pub(crate) mod rng;
 pub(crate) mod str;
@@ -6,4 +6,9 @@ pub fn generate_short_hash(input: &str) -> String {
 
     let hex_hash = format!("{:x}", hash);
     hex_hash[..6].to_string()
-}
\ No newline at end of file
+}
+
+pub fn generate_bytes_hash(input: &[u8]) -> String {
+    let crc = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+    format!("{:08x}", crc.checksum(input))
+}